@@ -4,6 +4,12 @@ extern crate ini;
 use ui::sidebar::SideBar;
 
 fn main() {
+    // `SideBar health export|import ...` работает без окна
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("health") {
+        std::process::exit(ui::health_export::run_cli(&args[1..]));
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_min_inner_size([300.0, 220.0]),
@@ -0,0 +1,489 @@
+use crate::ui::activitywatch_reader::{ActivitySource, AwEvent, AwEventData};
+use crate::ui::data_store::database_path;
+use crate::ui::settings::{
+    get_tracker_command, get_tracker_enabled, get_tracker_idle_command, get_tracker_idle_threshold_secs,
+    get_tracker_interval_secs, get_tracker_probe,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// Трекер пишет без учёта простоя; виджет экранного времени об этом предупреждает
+static IDLE_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+pub fn idle_unavailable() -> bool {
+    IDLE_UNAVAILABLE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusedWindow {
+    pub app: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowProbe {
+    X11,
+    Sway,
+    Hyprland,
+    Command,
+}
+
+impl WindowProbe {
+    // "auto" определяется по переменным окружения композитора
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value {
+            "x11" => Some(Self::X11),
+            "sway" => Some(Self::Sway),
+            "hyprland" => Some(Self::Hyprland),
+            "command" => Some(Self::Command),
+            _ => {
+                if env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+                    Some(Self::Hyprland)
+                } else if env::var("SWAYSOCK").is_ok() {
+                    Some(Self::Sway)
+                } else if env::var("DISPLAY").is_ok() {
+                    Some(Self::X11)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn focused_window(&self) -> Option<FocusedWindow> {
+        match self {
+            Self::X11 => x11_focused_window(),
+            Self::Sway => sway_focused_window(),
+            Self::Hyprland => hyprland_focused_window(),
+            Self::Command => command_focused_window(&get_tracker_command()),
+        }
+    }
+}
+
+fn run_command(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Значение после "=" в выводе xprop, без кавычек
+fn xprop_value(output: &str, property: &str) -> Option<String> {
+    output
+        .lines()
+        .find(|line| line.starts_with(property))
+        .and_then(|line| line.split_once('='))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn x11_focused_window() -> Option<FocusedWindow> {
+    let root = run_command("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window_id = root.split_whitespace().last()?.trim_end_matches(',').to_string();
+    if window_id == "0x0" {
+        return None;
+    }
+
+    let props = run_command("xprop", &["-id", &window_id, "WM_CLASS", "_NET_WM_NAME"])?;
+
+    // WM_CLASS(STRING) = "navigator", "firefox" — берём класс, а не instance
+    let app = xprop_value(&props, "WM_CLASS")
+        .and_then(|value| value.split(',').next_back().map(|s| s.trim().trim_matches('"').to_string()))
+        .unwrap_or_default();
+    let title = xprop_value(&props, "_NET_WM_NAME")
+        .map(|value| value.trim_matches('"').to_string())
+        .unwrap_or_default();
+
+    Some(FocusedWindow { app, title })
+}
+
+fn find_focused_sway_node(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) && node["pid"].is_number() {
+        return Some(node);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(find_focused_sway_node)
+}
+
+fn sway_focused_window() -> Option<FocusedWindow> {
+    let tree: Value = serde_json::from_str(&run_command("swaymsg", &["-t", "get_tree"])?).ok()?;
+    let node = find_focused_sway_node(&tree)?;
+
+    // У XWayland-окон нет app_id, только window_properties.class
+    let app = node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str())
+        .unwrap_or("")
+        .to_string();
+    let title = node["name"].as_str().unwrap_or("").to_string();
+
+    Some(FocusedWindow { app, title })
+}
+
+fn hyprland_focused_window() -> Option<FocusedWindow> {
+    let window: Value = serde_json::from_str(&run_command("hyprctl", &["activewindow", "-j"])?).ok()?;
+
+    Some(FocusedWindow {
+        app: window["class"].as_str()?.to_string(),
+        title: window["title"].as_str().unwrap_or("").to_string(),
+    })
+}
+
+// Пользовательская команда: JSON {"app": ..., "title": ...} или две строки app/title
+fn command_focused_window(command: &str) -> Option<FocusedWindow> {
+    if command.trim().is_empty() {
+        return None;
+    }
+
+    let output = run_command("sh", &["-c", command])?;
+
+    if let Ok(value) = serde_json::from_str::<Value>(&output) {
+        return Some(FocusedWindow {
+            app: value["app"].as_str()?.to_string(),
+            title: value["title"].as_str().unwrap_or("").to_string(),
+        });
+    }
+
+    let mut lines = output.lines();
+    let app = lines.next()?.trim().to_string();
+    let title = lines.next().unwrap_or("").trim().to_string();
+
+    if app.is_empty() {
+        None
+    } else {
+        Some(FocusedWindow { app, title })
+    }
+}
+
+// Время простоя в миллисекундах: "12345" от xprintidle или "(uint64 12345,)" от gdbus
+fn parse_idle_ms(output: &str) -> Option<u64> {
+    let digits: String = output
+        .trim()
+        .trim_start_matches('(')
+        .trim_start_matches("uint64")
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+// None — простой измерить нечем. Своя команда важнее встроенных способов;
+// на sway и Hyprland без неё простой неизвестен
+fn idle_seconds(probe: WindowProbe) -> Option<u64> {
+    let command = get_tracker_idle_command();
+    let output = if !command.trim().is_empty() {
+        run_command("sh", &["-c", &command])?
+    } else if probe == WindowProbe::X11 {
+        run_command("xprintidle", &[])?
+    } else {
+        // GNOME на Wayland
+        run_command(
+            "gdbus",
+            &[
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ],
+        )?
+    };
+    parse_idle_ms(&output).map(|ms| ms / 1000)
+}
+
+pub struct ActivityStore {
+    conn: Connection,
+}
+
+impl ActivityStore {
+    // Таблица spans живёт в общей базе SideBar
+    pub fn default_path() -> PathBuf {
+        database_path()
+    }
+
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS spans (
+                id INTEGER PRIMARY KEY,
+                app TEXT NOT NULL,
+                title TEXT NOT NULL,
+                start INTEGER NOT NULL,
+                end INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS spans_end ON spans(end);",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn start_span(&self, window: &FocusedWindow, at: i64) -> rusqlite::Result<i64> {
+        self.conn.execute(
+            "INSERT INTO spans (app, title, start, end) VALUES (?1, ?2, ?3, ?3)",
+            params![window.app, window.title, at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn extend_span(&self, id: i64, at: i64) -> rusqlite::Result<()> {
+        self.conn
+            .execute("UPDATE spans SET end = ?1 WHERE id = ?2", params![at, id])?;
+        Ok(())
+    }
+
+    pub fn events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> rusqlite::Result<Vec<AwEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT app, title, start, end FROM spans WHERE end > ?1 AND start < ?2 ORDER BY start",
+        )?;
+
+        let rows = stmt.query_map(params![start.timestamp(), end.timestamp()], |row| {
+            let app: String = row.get(0)?;
+            let title: String = row.get(1)?;
+            let span_start: i64 = row.get(2)?;
+            let span_end: i64 = row.get(3)?;
+            Ok((app, title, span_start, span_end))
+        })?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let (app, title, span_start, span_end) = row?;
+            let timestamp = DateTime::from_timestamp(span_start, 0).unwrap_or_default();
+            events.push(AwEvent {
+                timestamp: timestamp.to_rfc3339(),
+                duration: (span_end - span_start) as f64,
+                data: AwEventData { app, title },
+            });
+        }
+
+        Ok(events)
+    }
+}
+
+// Источник данных для sunburst из собственной базы SideBar
+pub struct LocalTrackerSource {
+    db_path: PathBuf,
+}
+
+impl LocalTrackerSource {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+}
+
+impl ActivitySource for LocalTrackerSource {
+    fn name(&self) -> &str {
+        "SideBar tracker"
+    }
+
+    fn fetch_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+        if !self.db_path.exists() {
+            return Err("built-in tracker has no data yet".into());
+        }
+        Ok(ActivityStore::open(&self.db_path)?.events(start, end)?)
+    }
+}
+
+pub struct ActivityTracker;
+
+impl ActivityTracker {
+    pub fn start_if_enabled() {
+        if !get_tracker_enabled() {
+            return;
+        }
+
+        let Some(probe) = WindowProbe::from_setting(&get_tracker_probe()) else {
+            eprintln!("Activity tracker: no supported window probe found");
+            return;
+        };
+
+        std::thread::spawn(move || {
+            if let Err(e) = Self::sample_loop(probe) {
+                eprintln!("Activity tracker stopped: {}", e);
+            }
+        });
+    }
+
+    fn sample_loop(probe: WindowProbe) -> Result<(), Box<dyn Error>> {
+        let store = ActivityStore::open(&ActivityStore::default_path())?;
+        let interval = get_tracker_interval_secs().max(1);
+        let idle_threshold = get_tracker_idle_threshold_secs();
+
+        let mut sampler = Sampler::default();
+
+        loop {
+            let now = Utc::now().timestamp();
+            let idle = idle_seconds(probe);
+            IDLE_UNAVAILABLE.store(idle.is_none(), Ordering::Relaxed);
+            let window = active_window(idle, idle_threshold, || probe.focused_window());
+
+            sampler.sample(&store, window, now)?;
+            std::thread::sleep(Duration::from_secs(interval));
+        }
+    }
+}
+
+// У sway и Hyprland нет своего счётчика простоя: без idle_command окно пишется без фильтра,
+// и время вдали от клавиатуры засчитывается как активное
+fn active_window(
+    idle: Option<u64>,
+    threshold: u64,
+    focused: impl FnOnce() -> Option<FocusedWindow>,
+) -> Option<FocusedWindow> {
+    match idle {
+        Some(idle) if idle >= threshold => None,
+        _ => focused(),
+    }
+}
+
+// Склеивает подряд идущие замеры одного окна в один интервал
+#[derive(Default)]
+struct Sampler {
+    current: Option<(i64, FocusedWindow)>,
+}
+
+impl Sampler {
+    // None — простой или неизвестно, что в фокусе: текущий интервал обрывается
+    fn sample(&mut self, store: &ActivityStore, window: Option<FocusedWindow>, now: i64) -> rusqlite::Result<()> {
+        match (&self.current, window) {
+            (Some((id, active)), Some(window)) if *active == window => {
+                store.extend_span(*id, now)?;
+            }
+            (previous, Some(window)) => {
+                // Закрываем предыдущий интервал в момент переключения окна
+                if let Some((id, _)) = previous {
+                    store.extend_span(*id, now)?;
+                }
+                let id = store.start_span(&window, now)?;
+                self.current = Some((id, window));
+            }
+            (_, None) => {
+                self.current = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::activitywatch_reader::{aggregate_usage, FixtureSource};
+    use crate::ui::test_support::temp_dir;
+
+    fn window(app: &str, title: &str) -> Option<FocusedWindow> {
+        Some(FocusedWindow {
+            app: app.to_string(),
+            title: title.to_string(),
+        })
+    }
+
+    fn utc(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn sampler_merges_samples_of_the_same_window() {
+        let path = temp_dir("tracker").join("sidebar.db");
+        let store = ActivityStore::open(&path).unwrap();
+        let mut sampler = Sampler::default();
+
+        sampler.sample(&store, window("kitty", "vim"), 1000).unwrap();
+        sampler.sample(&store, window("kitty", "vim"), 1010).unwrap();
+        sampler.sample(&store, window("kitty", "vim"), 1020).unwrap();
+        sampler.sample(&store, window("firefox", "Docs"), 1030).unwrap();
+        // Простой обрывает интервал, после него firefox начинается заново
+        sampler.sample(&store, None, 1040).unwrap();
+        sampler.sample(&store, window("firefox", "Docs"), 1100).unwrap();
+        sampler.sample(&store, window("firefox", "Docs"), 1110).unwrap();
+
+        let events = store.events(utc(0), utc(2000)).unwrap();
+        let spans: Vec<(&str, f64)> = events.iter().map(|event| (event.data.app.as_str(), event.duration)).collect();
+        assert_eq!(spans, vec![("kitty", 30.0), ("firefox", 0.0), ("firefox", 10.0)]);
+        assert_eq!(events[0].timestamp, utc(1000).to_rfc3339());
+    }
+
+    #[test]
+    fn store_returns_spans_overlapping_the_range() {
+        let path = temp_dir("tracker").join("sidebar.db");
+        let store = ActivityStore::open(&path).unwrap();
+        let kitty = window("kitty", "vim").unwrap();
+
+        let before = store.start_span(&kitty, 100).unwrap();
+        store.extend_span(before, 200).unwrap();
+        let across = store.start_span(&kitty, 900).unwrap();
+        store.extend_span(across, 1100).unwrap();
+        let after = store.start_span(&kitty, 2000).unwrap();
+        store.extend_span(after, 2100).unwrap();
+
+        let (start, end) = (utc(1000), utc(1500));
+        let events = LocalTrackerSource::new(path).fetch_events(start, end).unwrap();
+        assert_eq!(events.len(), 1);
+
+        // Часть до начала интервала отрезает общая агрегация
+        let usage = aggregate_usage(&events, start, end);
+        assert_eq!(usage[0].seconds, 100.0);
+    }
+
+    #[test]
+    fn local_source_without_database_is_an_error() {
+        let source = LocalTrackerSource::new(temp_dir("tracker").join("missing.db"));
+        assert!(source.fetch_events(utc(0), utc(1)).is_err());
+    }
+
+    #[test]
+    fn fixture_source_feeds_the_aggregation() {
+        let path = temp_dir("fixture").join("events.json");
+        std::fs::write(
+            &path,
+            r#"[
+                {"timestamp": "2024-05-01T10:00:00+00:00", "duration": 600, "data": {"app": "kitty", "title": "vim"}},
+                {"timestamp": "2024-05-01T10:10:00+00:00", "duration": 300, "data": {"app": "firefox"}},
+                {"timestamp": "2024-05-01T10:15:00+00:00", "duration": 120, "data": {"app": "kitty", "title": "htop"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let (start, end) = (utc(1714557600), utc(1714561200));
+        let events = FixtureSource::new(path).fetch_events(start, end).unwrap();
+        let usage = aggregate_usage(&events, start, end);
+
+        assert_eq!(usage.len(), 2);
+        assert_eq!((usage[0].app.as_str(), usage[0].seconds), ("kitty", 720.0));
+        assert_eq!(usage[1].titles, vec![(String::new(), 300.0)]);
+    }
+
+    #[test]
+    fn idle_output_is_parsed_from_known_tools() {
+        assert_eq!(parse_idle_ms("15230\n"), Some(15230));
+        assert_eq!(parse_idle_ms("(uint64 4200,)\n"), Some(4200));
+        assert_eq!(parse_idle_ms("Error: no such method"), None);
+    }
+
+    #[test]
+    fn idle_filter_is_skipped_without_an_idle_source() {
+        let window = || {
+            Some(FocusedWindow {
+                app: "foot".to_string(),
+                title: "vim".to_string(),
+            })
+        };
+        assert_eq!(active_window(Some(10), 300, window), window());
+        assert_eq!(active_window(Some(300), 300, window), None);
+        assert_eq!(active_window(None, 300, window), window());
+    }
+}
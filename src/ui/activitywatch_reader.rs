@@ -0,0 +1,484 @@
+use crate::ui::activity_tracker::{ActivityStore, LocalTrackerSource};
+use crate::ui::settings::{
+    get_activity_fixture_path, get_activity_source, get_activitywatch_db_path, get_activitywatch_url,
+};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_AW_URL: &str = "http://localhost:5600";
+const WINDOW_BUCKET_TYPE: &str = "currentwindow";
+const REQUEST_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AwEvent {
+    pub timestamp: String, // RFC 3339, как отдаёт aw-server
+    pub duration: f64,     // Длительность в секундах
+    pub data: AwEventData,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AwEventData {
+    #[serde(default)]
+    pub app: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct AwBucket {
+    id: String,
+    #[serde(rename = "type")]
+    bucket_type: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppUsage {
+    pub app: String,
+    pub seconds: f64,
+    pub titles: Vec<(String, f64)>, // Отсортированы по убыванию времени
+}
+
+pub struct AwClient {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl AwClient {
+    pub fn new(base_url: &str) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+
+    fn window_buckets(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let url = format!("{}/api/0/buckets/", self.base_url);
+        let buckets: HashMap<String, AwBucket> = self.client.get(&url).send()?.error_for_status()?.json()?;
+
+        Ok(buckets
+            .into_values()
+            .filter(|bucket| bucket.bucket_type == WINDOW_BUCKET_TYPE)
+            .map(|bucket| bucket.id)
+            .collect())
+    }
+
+    pub fn fetch_window_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+        let mut events = Vec::new();
+
+        for bucket_id in self.window_buckets()? {
+            let url = format!("{}/api/0/buckets/{}/events", self.base_url, bucket_id);
+            let bucket_events: Vec<AwEvent> = self
+                .client
+                .get(&url)
+                .query(&[
+                    ("start", start.to_rfc3339()),
+                    ("end", end.to_rfc3339()),
+                    ("limit", "-1".to_string()),
+                ])
+                .send()?
+                .error_for_status()?
+                .json()?;
+            events.extend(bucket_events);
+        }
+
+        Ok(events)
+    }
+}
+
+// База aw-server (peewee), которую использует сборка aw-qt
+pub fn default_db_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("activitywatch/aw-server/peewee-sqlite.v2.db")
+}
+
+pub fn read_window_events_from_db(
+    path: &Path,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    // peewee хранит время строкой вида "2024-05-01 10:00:00.123456+00:00".
+    // Берём и события, начатые до интервала, но заходящие в него: лишнее обрежет clipped_duration
+    let format = "%Y-%m-%d %H:%M:%S";
+    let mut stmt = conn.prepare(
+        "SELECT e.timestamp, e.duration, e.datastr
+         FROM eventmodel e
+         JOIN bucketmodel b ON e.bucket_id = b.key
+         WHERE b.type = ?1
+           AND julianday(e.timestamp) < julianday(?3)
+           AND julianday(e.timestamp) + e.duration / 86400.0 > julianday(?2)",
+    )?;
+
+    let rows = stmt.query_map(
+        params![
+            WINDOW_BUCKET_TYPE,
+            start.format(format).to_string(),
+            end.format(format).to_string()
+        ],
+        |row| {
+            let timestamp: String = row.get(0)?;
+            let duration: f64 = row.get(1)?;
+            let datastr: String = row.get(2)?;
+            Ok((timestamp, duration, datastr))
+        },
+    )?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        let (timestamp, duration, datastr) = row?;
+        let data = serde_json::from_str(&datastr).unwrap_or_default();
+        events.push(AwEvent {
+            timestamp: timestamp.replacen(' ', "T", 1),
+            duration,
+            data,
+        });
+    }
+
+    Ok(events)
+}
+
+// Обрезаем события, выходящие за границы интервала
+fn clipped_duration(event: &AwEvent, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let Ok(event_start) = DateTime::parse_from_rfc3339(&event.timestamp) else {
+        return event.duration.max(0.0);
+    };
+    let event_start = event_start.with_timezone(&Utc);
+    let event_end = event_start + chrono::Duration::milliseconds((event.duration * 1000.0) as i64);
+
+    let clipped_start = event_start.max(start);
+    let clipped_end = event_end.min(end);
+
+    if clipped_end > clipped_start {
+        (clipped_end - clipped_start).num_milliseconds() as f64 / 1000.0
+    } else {
+        0.0
+    }
+}
+
+pub fn aggregate_usage(events: &[AwEvent], start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<AppUsage> {
+    let mut apps: HashMap<String, HashMap<String, f64>> = HashMap::new();
+
+    for event in events {
+        let seconds = clipped_duration(event, start, end);
+        if seconds <= 0.0 {
+            continue;
+        }
+
+        let app = if event.data.app.is_empty() {
+            "unknown".to_string()
+        } else {
+            event.data.app.clone()
+        };
+
+        *apps
+            .entry(app)
+            .or_default()
+            .entry(event.data.title.clone())
+            .or_default() += seconds;
+    }
+
+    let mut usage: Vec<AppUsage> = apps
+        .into_iter()
+        .map(|(app, titles)| {
+            let mut titles: Vec<(String, f64)> = titles.into_iter().collect();
+            titles.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            AppUsage {
+                seconds: titles.iter().map(|(_, s)| s).sum(),
+                app,
+                titles,
+            }
+        })
+        .collect();
+
+    usage.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+    usage
+}
+
+pub fn today_range() -> (DateTime<Utc>, DateTime<Utc>) {
+    let now = Local::now();
+    let midnight = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .unwrap_or(now);
+
+    (midnight.with_timezone(&Utc), now.with_timezone(&Utc))
+}
+
+pub trait ActivitySource: Send {
+    fn name(&self) -> &str;
+    fn fetch_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<AwEvent>, Box<dyn Error>>;
+}
+
+// Сначала REST API, при недоступности сервера — напрямую из базы
+pub struct ActivityWatchSource {
+    base_url: String,
+    db_path: PathBuf,
+}
+
+impl ActivityWatchSource {
+    pub fn new(base_url: &str, db_path: PathBuf) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            db_path,
+        }
+    }
+}
+
+impl ActivitySource for ActivityWatchSource {
+    fn name(&self) -> &str {
+        "ActivityWatch"
+    }
+
+    fn fetch_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+        match AwClient::new(&self.base_url).fetch_window_events(start, end) {
+            Ok(events) => Ok(events),
+            Err(api_err) => read_window_events_from_db(&self.db_path, start, end)
+                .map_err(|db_err| format!("aw-server: {}; database: {}", api_err, db_err).into()),
+        }
+    }
+}
+
+// JSON-файл с событиями в формате aw-server, для демонстрации и отладки
+pub struct FixtureSource {
+    path: PathBuf,
+}
+
+impl FixtureSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl ActivitySource for FixtureSource {
+    fn name(&self) -> &str {
+        "Fixture"
+    }
+
+    fn fetch_events(&self, _start: DateTime<Utc>, _end: DateTime<Utc>) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+// Перебирает источники по порядку, пока один не ответит
+pub struct FallbackSource {
+    sources: Vec<Box<dyn ActivitySource>>,
+}
+
+impl ActivitySource for FallbackSource {
+    fn name(&self) -> &str {
+        "Auto"
+    }
+
+    fn fetch_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<AwEvent>, Box<dyn Error>> {
+        let mut errors = Vec::new();
+
+        for source in &self.sources {
+            match source.fetch_events(start, end) {
+                Ok(events) => return Ok(events),
+                Err(e) => errors.push(format!("{}: {}", source.name(), e)),
+            }
+        }
+
+        Err(errors.join("\n").into())
+    }
+}
+
+pub fn create_activity_source() -> Box<dyn ActivitySource> {
+    let db_path = match get_activitywatch_db_path() {
+        path if path.is_empty() => default_db_path(),
+        path => PathBuf::from(path),
+    };
+    let activitywatch = ActivityWatchSource::new(&get_activitywatch_url(), db_path);
+    let builtin = LocalTrackerSource::new(ActivityStore::default_path());
+
+    match get_activity_source().as_str() {
+        "activitywatch" => Box::new(activitywatch),
+        "builtin" => Box::new(builtin),
+        "fixture" => Box::new(FixtureSource::new(PathBuf::from(get_activity_fixture_path()))),
+        _ => Box::new(FallbackSource {
+            sources: vec![Box::new(activitywatch), Box::new(builtin)],
+        }),
+    }
+}
+
+pub fn fetch_usage(
+    source: &dyn ActivitySource,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<AppUsage>, Box<dyn Error>> {
+    let events = source.fetch_events(start, end)?;
+    Ok(aggregate_usage(&events, start, end))
+}
+
+pub fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).round() as u64;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::{temp_dir, MockServer};
+
+    fn utc(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    fn event(timestamp: &str, duration: f64, app: &str, title: &str) -> AwEvent {
+        AwEvent {
+            timestamp: timestamp.to_string(),
+            duration,
+            data: AwEventData {
+                app: app.to_string(),
+                title: title.to_string(),
+            },
+        }
+    }
+
+    // Схема peewee из aw-server, только нужные столбцы
+    fn fixture_db(rows: &[(&str, f64, &str)]) -> PathBuf {
+        let path = temp_dir("aw-db").join("peewee-sqlite.v2.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE bucketmodel (key INTEGER PRIMARY KEY, id TEXT, type TEXT);
+             CREATE TABLE eventmodel (id INTEGER PRIMARY KEY, bucket_id INTEGER, timestamp TEXT, duration REAL, datastr TEXT);
+             INSERT INTO bucketmodel VALUES (1, 'aw-watcher-window_host', 'currentwindow');
+             INSERT INTO bucketmodel VALUES (2, 'aw-watcher-afk_host', 'afkstatus');",
+        )
+        .unwrap();
+        for (timestamp, duration, datastr) in rows {
+            conn.execute(
+                "INSERT INTO eventmodel (bucket_id, timestamp, duration, datastr) VALUES (1, ?1, ?2, ?3)",
+                params![timestamp, duration, datastr],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO eventmodel (bucket_id, timestamp, duration, datastr) VALUES (2, '2024-05-01 10:00:00+00:00', 600, '{\"status\":\"afk\"}')",
+            [],
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn client_reads_events_from_window_buckets_only() {
+        let server = MockServer::start(vec![
+            (
+                "/api/0/buckets/",
+                r#"{
+                    "aw-watcher-window_host": {"id": "aw-watcher-window_host", "type": "currentwindow"},
+                    "aw-watcher-afk_host": {"id": "aw-watcher-afk_host", "type": "afkstatus"}
+                }"#
+                .to_string(),
+            ),
+            (
+                "/api/0/buckets/aw-watcher-window_host/events",
+                r#"[
+                    {"id": 2, "timestamp": "2024-05-01T10:05:00+00:00", "duration": 120.5, "data": {"app": "firefox", "title": "Docs"}},
+                    {"id": 1, "timestamp": "2024-05-01T10:00:00+00:00", "duration": 300.0, "data": {"app": "kitty", "title": "vim"}}
+                ]"#
+                .to_string(),
+            ),
+        ]);
+
+        let client = AwClient::new(&format!("{}/", server.base_url));
+        let events = client
+            .fetch_window_events(utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"))
+            .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data.app, "firefox");
+        assert_eq!(events[1].duration, 300.0);
+        assert!(!server.requests().iter().any(|request| request.contains("afk")));
+        assert!(server
+            .requests()
+            .iter()
+            .any(|request| request.contains("/events?") && request.contains("limit=-1")));
+    }
+
+    #[test]
+    fn client_reports_unreachable_server() {
+        let server = MockServer::start(Vec::new());
+        let client = AwClient::new(&server.base_url);
+        assert!(client
+            .fetch_window_events(utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"))
+            .is_err());
+    }
+
+    #[test]
+    fn database_keeps_events_that_run_into_the_range() {
+        let path = fixture_db(&[
+            // 23:50–00:30, в сутки попадают 30 минут
+            ("2024-04-30 23:50:00.000000+00:00", 2400.0, r#"{"app": "kitty", "title": "vim"}"#),
+            ("2024-05-01 09:00:00.123456+00:00", 60.0, r#"{"app": "firefox", "title": "Docs"}"#),
+            // Закончилось до начала суток
+            ("2024-04-30 22:00:00+00:00", 600.0, r#"{"app": "old", "title": ""}"#),
+            // Началось после конца интервала
+            ("2024-05-02 00:00:00+00:00", 60.0, r#"{"app": "late", "title": ""}"#),
+        ]);
+        let (start, end) = (utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+
+        let events = read_window_events_from_db(&path, start, end).unwrap();
+        let mut apps: Vec<&str> = events.iter().map(|event| event.data.app.as_str()).collect();
+        apps.sort();
+        assert_eq!(apps, vec!["firefox", "kitty"]);
+        assert!(events.iter().all(|event| DateTime::parse_from_rfc3339(&event.timestamp).is_ok()));
+
+        let usage = aggregate_usage(&events, start, end);
+        let kitty = usage.iter().find(|usage| usage.app == "kitty").unwrap();
+        assert_eq!(kitty.seconds, 1800.0);
+    }
+
+    #[test]
+    fn aggregation_clips_events_and_groups_titles() {
+        let (start, end) = (utc("2024-05-01T10:00:00Z"), utc("2024-05-01T11:00:00Z"));
+        let events = vec![
+            event("2024-05-01T09:59:00Z", 120.0, "kitty", "vim"),
+            event("2024-05-01T10:10:00Z", 300.0, "kitty", "htop"),
+            event("2024-05-01T10:20:00Z", 60.0, "kitty", "vim"),
+            event("2024-05-01T10:30:00Z", 1800.0, "firefox", "Docs"),
+            event("2024-05-01T10:58:00Z", 600.0, "", "Untitled"),
+            event("2024-05-01T12:00:00Z", 60.0, "late", ""),
+        ];
+
+        let usage = aggregate_usage(&events, start, end);
+
+        let apps: Vec<&str> = usage.iter().map(|usage| usage.app.as_str()).collect();
+        assert_eq!(apps, vec!["firefox", "kitty", "unknown"]);
+        assert_eq!(usage[0].seconds, 1800.0);
+        assert_eq!(usage[1].seconds, 420.0);
+        assert_eq!(usage[1].titles, vec![("htop".to_string(), 300.0), ("vim".to_string(), 120.0)]);
+        assert_eq!(usage[2].seconds, 120.0);
+    }
+
+    #[test]
+    fn duration_format_switches_to_hours() {
+        assert_eq!(format_duration(89.0), "1m");
+        assert_eq!(format_duration(3600.0), "1h 0m");
+        assert_eq!(format_duration(5430.0), "1h 31m");
+    }
+}
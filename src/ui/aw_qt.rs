@@ -0,0 +1,321 @@
+use crate::ui::activitywatch_reader::{
+    create_activity_source, fetch_usage, format_duration, today_range, AppUsage,
+};
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::screen_time::{app_category_color, load_categories, Category};
+use crate::ui::widget_registry::SidebarWidget;
+use egui::epaint::Mesh;
+use egui::{ecolor::Hsva, Color32, Frame, Pos2, Sense, Stroke, Ui, Vec2};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_APPS: usize = 8;
+const MAX_TITLES: usize = 5;
+const CHART_SIZE: f32 = 180.0;
+const WIDE_LAYOUT_THRESHOLD: f32 = 400.0;
+const ARC_STEP: f32 = 0.05; // Шаг дуги в радианах при построении сегментов
+
+#[derive(Default)]
+struct SunburstState {
+    usage: Vec<AppUsage>,
+    error: Option<String>,
+    loading: bool,
+}
+
+// Сегмент диаграммы: углы отсчитываются от 12 часов по часовой стрелке
+struct Segment {
+    label: String,
+    seconds: f64,
+    start: f32,
+    end: f32,
+    color: Color32,
+}
+
+pub struct SunburstWidget {
+    state: Arc<Mutex<SunburstState>>,
+    next_update: Option<Instant>,
+}
+
+impl SunburstWidget {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SunburstState::default())),
+            next_update: None,
+        }
+    }
+
+    fn refresh_if_needed(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        if self.next_update.is_some_and(|next| now < next) {
+            return;
+        }
+        self.next_update = Some(now + REFRESH_INTERVAL);
+
+        if let Ok(mut state) = self.state.lock() {
+            if state.loading {
+                return;
+            }
+            state.loading = true;
+        }
+
+        let state = Arc::clone(&self.state);
+        let ctx = ctx.clone();
+
+        // Запрос к источнику не должен блокировать отрисовку
+        std::thread::spawn(move || {
+            let source = create_activity_source();
+            let (start, end) = today_range();
+            let result = fetch_usage(source.as_ref(), start, end);
+
+            if let Ok(mut state) = state.lock() {
+                match result {
+                    Ok(usage) => {
+                        state.usage = usage;
+                        state.error = None;
+                    }
+                    Err(err) => {
+                        state.error = Some(err.to_string());
+                    }
+                }
+                state.loading = false;
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn show_sunburst_widget(&mut self, ui: &mut Ui) {
+        self.refresh_if_needed(&ui.ctx().clone());
+
+        let (usage, error, loading) = match self.state.lock() {
+            Ok(state) => (state.usage.clone(), state.error.clone(), state.loading),
+            Err(_) => (Vec::new(), None, false),
+        };
+
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("📊 Activity");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("⟳").on_hover_text("Refresh").clicked() {
+                        self.next_update = None;
+                    }
+                    let total: f64 = usage.iter().map(|app| app.seconds).sum();
+                    ui.label(
+                        egui::RichText::new(format!("Today: {}", format_duration(total)))
+                            .size(12.0)
+                            .color(Color32::GRAY),
+                    );
+                });
+            });
+            ui.add_space(5.0);
+
+            if usage.is_empty() {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(10.0);
+                    if loading {
+                        ui.label("Loading activity...");
+                    } else if let Some(error) = &error {
+                        ui.label("Activity data is not available");
+                        ui.label(egui::RichText::new(error).size(11.0).color(Color32::GRAY));
+                    } else {
+                        ui.label("No activity recorded today");
+                    }
+                    ui.add_space(10.0);
+                });
+                return;
+            }
+
+            let (apps, titles) = build_segments(&usage, &load_categories());
+
+            if ui.available_width() >= WIDE_LAYOUT_THRESHOLD {
+                ui.horizontal(|ui| {
+                    render_chart(ui, &apps, &titles);
+                    ui.add_space(10.0);
+                    ui.vertical(|ui| render_legend(ui, &apps));
+                });
+            } else {
+                ui.vertical_centered(|ui| render_chart(ui, &apps, &titles));
+                ui.add_space(10.0);
+                render_legend(ui, &apps);
+            }
+        });
+    }
+}
+
+impl SidebarWidget for SunburstWidget {
+    fn id(&self) -> &'static str {
+        "activity"
+    }
+
+    fn title(&self) -> &'static str {
+        "Activity"
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        self.show_sunburst_widget(ui);
+    }
+
+    fn refresh(&mut self) {
+        self.next_update = None;
+    }
+}
+
+impl Default for SunburstWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn app_color(index: usize) -> Color32 {
+    // Золотое сечение равномерно распределяет оттенки
+    let hue = (index as f32 * 0.618_034) % 1.0;
+    Hsva::new(hue, 0.45, 0.85, 1.0).into()
+}
+
+// Заголовки окон — светлые оттенки цвета приложения
+fn title_color(app_color: Color32, title_index: usize) -> Color32 {
+    let base = Hsva::from(app_color);
+    let value = if title_index % 2 == 0 { 0.95 } else { 0.88 };
+    Hsva::new(base.h, base.s * 0.55, value, 1.0).into()
+}
+
+fn build_segments(usage: &[AppUsage], categories: &[Category]) -> (Vec<Segment>, Vec<Segment>) {
+    let total: f64 = usage.iter().map(|app| app.seconds).sum();
+    if total <= 0.0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut apps = Vec::new();
+    let mut titles = Vec::new();
+    let mut angle = 0.0;
+
+    for (index, app) in usage.iter().take(MAX_APPS).enumerate() {
+        let span = (app.seconds / total) as f32 * TAU;
+        let color = app_category_color(&app.app, categories).unwrap_or_else(|| app_color(index));
+        let mut title_angle = angle;
+
+        for (title_index, (title, seconds)) in app.titles.iter().take(MAX_TITLES).enumerate() {
+            let title_span = (*seconds / total) as f32 * TAU;
+            titles.push(Segment {
+                label: if title.is_empty() { app.app.clone() } else { title.clone() },
+                seconds: *seconds,
+                start: title_angle,
+                end: title_angle + title_span,
+                color: title_color(color, title_index),
+            });
+            title_angle += title_span;
+        }
+
+        apps.push(Segment {
+            label: app.app.clone(),
+            seconds: app.seconds,
+            start: angle,
+            end: angle + span,
+            color,
+        });
+        angle += span;
+    }
+
+    let other: f64 = usage.iter().skip(MAX_APPS).map(|app| app.seconds).sum();
+    if other > 0.0 {
+        apps.push(Segment {
+            label: "Other".to_string(),
+            seconds: other,
+            start: angle,
+            end: TAU,
+            color: Color32::GRAY,
+        });
+    }
+
+    (apps, titles)
+}
+
+fn add_ring_segment(mesh: &mut Mesh, center: Pos2, inner: f32, outer: f32, segment: &Segment) {
+    let steps = ((segment.end - segment.start) / ARC_STEP).ceil().max(1.0) as u32;
+    let base = mesh.vertices.len() as u32;
+
+    for i in 0..=steps {
+        let angle = segment.start + (segment.end - segment.start) * i as f32 / steps as f32 - FRAC_PI_2;
+        let direction = Vec2::angled(angle);
+        mesh.colored_vertex(center + direction * inner, segment.color);
+        mesh.colored_vertex(center + direction * outer, segment.color);
+    }
+
+    for i in 0..steps {
+        let k = base + i * 2;
+        mesh.add_triangle(k, k + 1, k + 2);
+        mesh.add_triangle(k + 1, k + 3, k + 2);
+    }
+}
+
+fn render_chart(ui: &mut Ui, apps: &[Segment], titles: &[Segment]) {
+    let size = ui.available_width().min(CHART_SIZE);
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(size), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let center = rect.center();
+
+    let outer = size / 2.0;
+    let middle = outer * 0.68;
+    let inner = outer * 0.3;
+
+    let mut mesh = Mesh::default();
+    for segment in apps {
+        add_ring_segment(&mut mesh, center, inner, middle, segment);
+    }
+    for segment in titles {
+        add_ring_segment(&mut mesh, center, middle + 1.0, outer, segment);
+    }
+    painter.add(mesh);
+
+    // Разделители между приложениями
+    let separator = Stroke::new(1.5, parse_color_from_ini("frame-background"));
+    for segment in apps {
+        let direction = Vec2::angled(segment.start - FRAC_PI_2);
+        painter.line_segment([center + direction * inner, center + direction * outer], separator);
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let offset = pointer - center;
+        let radius = offset.length();
+        let angle = (offset.y.atan2(offset.x) + FRAC_PI_2).rem_euclid(TAU);
+
+        let ring = if radius >= inner && radius < middle {
+            apps
+        } else if radius >= middle && radius <= outer {
+            titles
+        } else {
+            &[]
+        };
+
+        if let Some(segment) = ring.iter().find(|s| angle >= s.start && angle < s.end) {
+            let text = format!("{}\n{}", segment.label, format_duration(segment.seconds));
+            response.on_hover_text_at_pointer(text);
+        }
+    }
+}
+
+fn render_legend(ui: &mut Ui, apps: &[Segment]) {
+    for segment in apps {
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(Vec2::splat(10.0), Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, segment.color);
+            ui.label(egui::RichText::new(&segment.label).size(12.0));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new(format_duration(segment.seconds))
+                        .size(12.0)
+                        .color(Color32::GRAY),
+                );
+            });
+        });
+    }
+}
@@ -0,0 +1,315 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::data_store::{DataStore, Result};
+use crate::ui::food_diary::{current_day, FoodDiary};
+use crate::ui::health_export::data_version;
+use crate::ui::widget_registry::SidebarWidget;
+use chrono::{Datelike, Duration, NaiveDate};
+use egui::{Color32, Frame, Pos2, Rect, Sense, Stroke, TextEdit, Ui, Vec2};
+
+const HEATMAP_DAYS: i64 = 30;
+const CELL_SIZE: f32 = 16.0;
+const CELL_GAP: f32 = 3.0;
+const NOTE_LIMIT: usize = 140;
+
+// Отметка за день; ключ — та же ISO-дата, что у воды и еды
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckIn {
+    pub date: String, // "%Y-%m-%d"
+    pub sleep_hours: Option<f32>,
+    pub sleep_quality: Option<u8>, // 1–5
+    pub mood: Option<u8>,          // 1–5
+    pub energy: Option<u8>,        // 1–5
+    pub note: String,
+}
+
+// Строка представления daily_health
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyHealth {
+    pub date: String,
+    pub water_ml: u32,
+    pub kcal: i32,
+    pub checkin: Option<CheckIn>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum HeatmapMetric {
+    #[default]
+    Mood,
+    Energy,
+    SleepHours,
+    SleepQuality,
+}
+
+impl HeatmapMetric {
+    const ALL: [HeatmapMetric; 4] = [
+        HeatmapMetric::Mood,
+        HeatmapMetric::Energy,
+        HeatmapMetric::SleepHours,
+        HeatmapMetric::SleepQuality,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            HeatmapMetric::Mood => "Mood",
+            HeatmapMetric::Energy => "Energy",
+            HeatmapMetric::SleepHours => "Sleep",
+            HeatmapMetric::SleepQuality => "Quality",
+        }
+    }
+
+    // Значение, приведённое к 0..1; сон считаем полным от 9 часов
+    fn level(&self, checkin: &CheckIn) -> Option<f32> {
+        let rating = |value: Option<u8>| value.map(|value| (value.clamp(1, 5) - 1) as f32 / 4.0);
+        match self {
+            HeatmapMetric::Mood => rating(checkin.mood),
+            HeatmapMetric::Energy => rating(checkin.energy),
+            HeatmapMetric::SleepQuality => rating(checkin.sleep_quality),
+            HeatmapMetric::SleepHours => checkin.sleep_hours.map(|hours| (hours / 9.0).clamp(0.0, 1.0)),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CheckInWidget {
+    store: Option<DataStore>,
+    loaded: Option<(NaiveDate, u64)>,
+    draft: CheckIn,
+    saved: Option<CheckIn>,
+    days: Vec<DailyHealth>,
+    metric: HeatmapMetric,
+}
+
+impl CheckInWidget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn ensure_loaded(&mut self) -> Result<()> {
+        if self.store.is_none() {
+            self.store = Some(DataStore::open()?);
+        }
+
+        let today = current_day();
+        if self.loaded != Some((today, data_version())) {
+            self.reload(today)?;
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self, today: NaiveDate) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+
+        let today_key = FoodDiary::date_key(today);
+        self.saved = store.checkin(&today_key)?;
+        self.draft = self.saved.clone().unwrap_or(CheckIn {
+            date: today_key.clone(),
+            ..Default::default()
+        });
+
+        let from = FoodDiary::date_key(today - Duration::days(HEATMAP_DAYS - 1));
+        self.days = store.daily_health(&from, &today_key)?;
+
+        self.loaded = Some((today, data_version()));
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save_checkin(&self.draft)?;
+        }
+        self.loaded = None;
+        Ok(())
+    }
+
+    pub fn render(&mut self, ui: &mut Ui) {
+        let load_error = self.ensure_loaded().err();
+
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("🌙 Daily check-in");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let status = if self.saved.is_some() { "✔ saved" } else { "not yet" };
+                    ui.label(egui::RichText::new(status).size(12.0).color(Color32::GRAY));
+                });
+            });
+            ui.add_space(5.0);
+
+            if let Some(e) = load_error {
+                ui.label(egui::RichText::new(format!("Check-in unavailable: {}", e)).color(Color32::from_rgb(200, 60, 60)));
+                return;
+            }
+
+            self.render_form(ui);
+            ui.add_space(10.0);
+            self.render_heatmap(ui);
+        });
+    }
+
+    fn render_form(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sleep:");
+            let mut hours = self.draft.sleep_hours.unwrap_or(0.0);
+            let response = ui.add(
+                egui::DragValue::new(&mut hours)
+                    .speed(0.25)
+                    .clamp_range(0.0..=16.0)
+                    .fixed_decimals(1)
+                    .suffix(" h"),
+            );
+            if response.changed() {
+                self.draft.sleep_hours = Some(hours);
+            }
+        });
+
+        for (label, value) in [
+            ("Sleep quality:", &mut self.draft.sleep_quality),
+            ("Mood:", &mut self.draft.mood),
+            ("Energy:", &mut self.draft.energy),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                rating_picker(ui, value);
+            });
+        }
+
+        ui.add(
+            TextEdit::singleline(&mut self.draft.note)
+                .desired_width(ui.available_width())
+                .char_limit(NOTE_LIMIT)
+                .hint_text("Short note"),
+        );
+        ui.add_space(5.0);
+
+        let changed = self.saved.as_ref() != Some(&self.draft);
+        let label = if self.saved.is_some() { "💾 Update" } else { "💾 Save" };
+        if ui
+            .add_enabled(changed, egui::Button::new(label).fill(parse_color_from_ini("button-color")))
+            .clicked()
+        {
+            let _ = self.save();
+        }
+    }
+
+    // Столбцы — недели с понедельника, как в календаре
+    fn render_heatmap(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            for metric in HeatmapMetric::ALL {
+                ui.selectable_value(&mut self.metric, metric, metric.label());
+            }
+        });
+        ui.add_space(3.0);
+
+        let today = current_day();
+        let start = today - Duration::days(HEATMAP_DAYS - 1);
+        let first_monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+        let weeks = ((today - first_monday).num_days() / 7 + 1) as f32;
+
+        let size = Vec2::new(weeks * (CELL_SIZE + CELL_GAP), 7.0 * (CELL_SIZE + CELL_GAP));
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+
+        let color = parse_color_from_ini("button-color");
+        let empty = parse_color_from_ini("frame-border-color").linear_multiply(0.35);
+        let mut hovered = None;
+
+        for offset in 0..HEATMAP_DAYS {
+            let date = start + Duration::days(offset);
+            let week = (date - first_monday).num_days() / 7;
+            let cell = Rect::from_min_size(
+                Pos2::new(
+                    rect.left() + week as f32 * (CELL_SIZE + CELL_GAP),
+                    rect.top() + date.weekday().num_days_from_monday() as f32 * (CELL_SIZE + CELL_GAP),
+                ),
+                Vec2::splat(CELL_SIZE),
+            );
+
+            let key = FoodDiary::date_key(date);
+            let day = self.days.iter().find(|day| day.date == key);
+            let level = day
+                .and_then(|day| day.checkin.as_ref())
+                .and_then(|checkin| self.metric.level(checkin));
+            let fill = match level {
+                Some(level) => color.linear_multiply(0.25 + 0.75 * level),
+                None => empty,
+            };
+            painter.rect_filled(cell, 3.0, fill);
+            if date == today {
+                painter.rect_stroke(cell, 3.0, Stroke::new(1.0, ui.visuals().text_color().linear_multiply(0.6)));
+            }
+
+            if response.hover_pos().is_some_and(|pointer| cell.contains(pointer)) {
+                hovered = Some((date, day.cloned()));
+            }
+        }
+
+        if let Some((date, day)) = hovered {
+            response.on_hover_text_at_pointer(day_summary(date, day.as_ref()));
+        }
+    }
+}
+
+impl SidebarWidget for CheckInWidget {
+    fn id(&self) -> &'static str {
+        "checkin"
+    }
+
+    fn title(&self) -> &'static str {
+        "Daily check-in"
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        CheckInWidget::render(self, ui);
+    }
+
+    fn refresh(&mut self) {
+        self.loaded = None;
+    }
+}
+
+// 1–5; повторный клик по выбранной оценке сбрасывает её
+fn rating_picker(ui: &mut Ui, value: &mut Option<u8>) {
+    for rating in 1..=5u8 {
+        if ui.selectable_label(*value == Some(rating), rating.to_string()).clicked() {
+            *value = if *value == Some(rating) { None } else { Some(rating) };
+        }
+    }
+}
+
+fn day_summary(date: NaiveDate, day: Option<&DailyHealth>) -> String {
+    let mut lines = vec![date.format("%a %d.%m").to_string()];
+    let Some(day) = day else {
+        lines.push("No data".to_string());
+        return lines.join("\n");
+    };
+
+    match &day.checkin {
+        Some(checkin) => {
+            let rating = |value: Option<u8>| value.map(|value| value.to_string()).unwrap_or_else(|| "–".to_string());
+            let sleep = checkin
+                .sleep_hours
+                .map(|hours| format!("{:.1} h", hours))
+                .unwrap_or_else(|| "–".to_string());
+            lines.push(format!("Sleep {} · quality {}", sleep, rating(checkin.sleep_quality)));
+            lines.push(format!("Mood {} · energy {}", rating(checkin.mood), rating(checkin.energy)));
+            if !checkin.note.is_empty() {
+                lines.push(format!("“{}”", checkin.note));
+            }
+        }
+        None => lines.push("No check-in".to_string()),
+    }
+
+    if day.water_ml > 0 || day.kcal > 0 {
+        lines.push(format!("💧 {} ml · 🍽 {} kcal", day.water_ml, day.kcal));
+    }
+    lines.join("\n")
+}
@@ -1,5 +1,5 @@
+use crate::ui::data_store::config_file;
 use egui::Color32;
-use std::env;
 use std::sync::RwLock;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
@@ -8,6 +8,10 @@ use once_cell::sync::Lazy;
 static COLOR_CACHE: Lazy<RwLock<HashMap<String, Color32>>> = 
     Lazy::new(|| RwLock::new(HashMap::new()));
 
+// Кэш для необязательных ключей (цвета категорий), None — ключа нет в теме
+static OPTIONAL_COLOR_CACHE: Lazy<RwLock<HashMap<String, Option<Color32>>>> = 
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
 pub fn parse_color_from_ini(key: &str) -> Color32 {
     // Проверяем кэш
     if let Ok(cache) = COLOR_CACHE.read() {
@@ -27,17 +31,36 @@ pub fn parse_color_from_ini(key: &str) -> Color32 {
     color
 }
 
-fn load_color_from_file(key: &str) -> Color32 {
-    let home = match env::var("HOME") {
-        Ok(h) => h,
-        Err(_) => return Color32::WHITE,
-    };
+// Цвет категории задаётся в теме ключом вида "category-browser"
+pub fn parse_category_color(category: &str) -> Option<Color32> {
+    let key = format!("category-{}", category.to_lowercase());
+    
+    if let Ok(cache) = OPTIONAL_COLOR_CACHE.read() {
+        if let Some(&color) = cache.get(&key) {
+            return color;
+        }
+    }
+    
+    let color = load_color_string(&key).map(|s| parse_rgb_string(&s));
+    
+    if let Ok(mut cache) = OPTIONAL_COLOR_CACHE.write() {
+        cache.insert(key, color);
+    }
     
-    let theme_path = format!("{}/.config/sidebar/themes.ini", home);
-    let settings_path = format!("{}/.config/sidebar/settings.ini", home);
+    color
+}
+
+fn load_color_from_file(key: &str) -> Color32 {
+    let color_str = load_color_string(key).unwrap_or_else(|| "255, 255, 255".to_string());
+    parse_rgb_string(&color_str)
+}
+
+fn load_color_string(key: &str) -> Option<String> {
+    let theme_path = config_file("themes.ini");
+    let settings_path = config_file("settings.ini");
     
-    let settings = ini!(&settings_path);
-    let theme = ini!(&theme_path);
+    let settings = ini!(&settings_path.to_string_lossy());
+    let theme = ini!(&theme_path.to_string_lossy());
     
     // Исправлено: используем map_or для получения &str
     let current_theme = settings
@@ -48,14 +71,12 @@ fn load_color_from_file(key: &str) -> Color32 {
         .unwrap_or("yellow");
     
     // Исправлено: используем map для получения &str
-    let color_str = theme
+    theme
         .get(current_theme)
         .and_then(|t| t.get(key))
         .and_then(|c| c.as_ref())
-        .map(|s| s.as_str())
-        .unwrap_or("255, 255, 255");
-    
-    parse_rgb_string(color_str)
+        .filter(|s| !s.trim().is_empty())
+        .cloned()
 }
 
 fn parse_rgb_string(s: &str) -> Color32 {
@@ -84,4 +105,7 @@ pub fn invalidate_color_cache() {
     if let Ok(mut cache) = COLOR_CACHE.write() {
         cache.clear();
     }
+    if let Ok(mut cache) = OPTIONAL_COLOR_CACHE.write() {
+        cache.clear();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,771 @@
+use crate::ui::checkin_widget::{CheckIn, DailyHealth};
+use crate::ui::food_diary::FoodEntry;
+use crate::ui::health_widget::WaterIntake;
+use crate::ui::legacy_import::import_legacy_stores;
+use crate::ui::notifications_listener::Notification;
+use crate::ui::pomodoro_widget::PomodoroRecord;
+use crate::ui::weight_tracker::BodyMetrics;
+use once_cell::sync::{Lazy, OnceCell};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub use rusqlite::Result;
+
+// Каждая миграция выполняется один раз, номер хранится в PRAGMA user_version.
+// Новые изменения схемы — только добавлением в конец списка
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE water (
+        date TEXT PRIMARY KEY,
+        ml INTEGER NOT NULL
+    );
+    CREATE TABLE food_entries (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        time TEXT NOT NULL,
+        name TEXT NOT NULL,
+        grams INTEGER NOT NULL,
+        kcal INTEGER NOT NULL,
+        protein REAL,
+        fat REAL,
+        carbs REAL
+    );
+    CREATE INDEX food_entries_date ON food_entries(date);
+    CREATE TABLE documents (
+        collection TEXT NOT NULL,
+        key TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (collection, key)
+    );
+    CREATE TABLE pomodoro_log (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        finished_at TEXT NOT NULL,
+        minutes INTEGER NOT NULL,
+        task TEXT
+    );
+    CREATE INDEX pomodoro_log_date ON pomodoro_log(date);
+    CREATE TABLE notifications (
+        id INTEGER PRIMARY KEY,
+        app_name TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        body TEXT NOT NULL,
+        timestamp TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS spans (
+        id INTEGER PRIMARY KEY,
+        app TEXT NOT NULL,
+        title TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS spans_end ON spans(end);
+    CREATE TABLE legacy_imports (
+        source TEXT PRIMARY KEY,
+        imported_at INTEGER NOT NULL
+    );",
+    // Журнал выпитого; water.ml остаётся итогом дня с учётом коэффициентов
+    "CREATE TABLE water_log (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        time TEXT NOT NULL,
+        beverage TEXT NOT NULL,
+        ml INTEGER NOT NULL,
+        hydration_ml INTEGER NOT NULL
+    );
+    CREATE INDEX water_log_date ON water_log(date);",
+    // Одно взвешивание на день
+    "CREATE TABLE body_metrics (
+        date TEXT PRIMARY KEY,
+        weight_kg REAL NOT NULL,
+        body_fat_pct REAL,
+        waist_cm REAL
+    );",
+    // Ежедневная отметка о сне и самочувствии; оценки 1–5, любое поле может быть пустым.
+    // daily_health сводит её с водой и калориями по той же дате для сравнения
+    "CREATE TABLE checkins (
+        date TEXT PRIMARY KEY,
+        sleep_hours REAL,
+        sleep_quality INTEGER,
+        mood INTEGER,
+        energy INTEGER,
+        note TEXT NOT NULL DEFAULT ''
+    );
+    CREATE VIEW daily_health AS
+    WITH days AS (
+        SELECT date FROM water
+        UNION SELECT date FROM food_entries
+        UNION SELECT date FROM checkins
+    )
+    SELECT
+        days.date AS date,
+        COALESCE(water.ml, 0) AS water_ml,
+        COALESCE((SELECT SUM(kcal) FROM food_entries WHERE food_entries.date = days.date), 0) AS kcal,
+        checkins.date AS checkin_date,
+        checkins.sleep_hours,
+        checkins.sleep_quality,
+        checkins.mood,
+        checkins.energy,
+        checkins.note
+    FROM days
+    LEFT JOIN water ON water.date = days.date
+    LEFT JOIN checkins ON checkins.date = days.date;",
+];
+
+// Настройки и темы остаются в INI, чтобы их можно было править руками.
+// Путь нужен геттерам настроек каждый кадр, поэтому каталог создаём один раз
+pub fn config_dir() -> PathBuf {
+    static CONFIG_DIR: Lazy<PathBuf> = Lazy::new(|| {
+        let path = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join("sidebar");
+        std::fs::create_dir_all(&path).ok();
+        path
+    });
+    CONFIG_DIR.clone()
+}
+
+pub fn config_file(name: &str) -> PathBuf {
+    config_dir().join(name)
+}
+
+pub fn data_dir() -> PathBuf {
+    static DATA_DIR: Lazy<PathBuf> = Lazy::new(|| {
+        let path = dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join("sidebar");
+        std::fs::create_dir_all(&path).ok();
+        path
+    });
+    DATA_DIR.clone()
+}
+
+pub fn database_path() -> PathBuf {
+    data_dir().join("sidebar.db")
+}
+
+// Единое хранилище данных SideBar. Клон разделяет то же соединение
+#[derive(Clone)]
+pub struct DataStore {
+    conn: Arc<Mutex<Connection>>,
+    path: PathBuf,
+}
+
+impl DataStore {
+    // Все виджеты работают с одним экземпляром на процесс
+    pub fn open() -> Result<Self> {
+        static STORE: OnceCell<DataStore> = OnceCell::new();
+        STORE
+            .get_or_try_init(|| {
+                let store = Self::open_at(&database_path())?;
+                import_legacy_stores(&store);
+                Ok(store)
+            })
+            .cloned()
+    }
+
+    // База по произвольному пути, без переноса старых хранилищ
+    pub fn open_at(path: &Path) -> Result<Self> {
+        let mut conn = Self::connect(path)?;
+        // Трекер активности пишет в ту же базу из своего соединения
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            path: path.to_path_buf(),
+        })
+    }
+
+    fn connect(path: &Path) -> Result<Connection> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(conn)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Всё, что change записал через переданное хранилище, фиксируется разом; при ошибке откатывается.
+    // У транзакции своё соединение: записи других потоков через общее ждут её конца
+    // и не попадают в откат. Методы с несколькими запросами используют savepoint, поэтому вкладываются сюда
+    pub fn in_transaction<T, E: From<rusqlite::Error>>(
+        &self,
+        change: impl FnOnce(&Self) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let store = Self {
+            conn: Arc::new(Mutex::new(Self::connect(&self.path)?)),
+            path: self.path.clone(),
+        };
+        store.conn().execute_batch("BEGIN IMMEDIATE")?;
+        match change(&store) {
+            Ok(value) => {
+                store.conn().execute_batch("COMMIT")?;
+                Ok(value)
+            }
+            Err(e) => {
+                let _ = store.conn().execute_batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+
+    // Вода, мл за день "%Y-%m-%d"
+
+    pub fn water(&self, date: &str) -> Result<u32> {
+        Ok(self
+            .conn()
+            .query_row("SELECT ml FROM water WHERE date = ?1", params![date], |row| row.get(0))
+            .optional()?
+            .unwrap_or(0))
+    }
+
+    pub fn has_water(&self, date: &str) -> Result<bool> {
+        Ok(self
+            .conn()
+            .query_row("SELECT 1 FROM water WHERE date = ?1", params![date], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    pub fn set_water(&self, date: &str, ml: u32) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO water (date, ml) VALUES (?1, ?2)
+             ON CONFLICT(date) DO UPDATE SET ml = excluded.ml",
+            params![date, ml],
+        )?;
+        Ok(())
+    }
+
+    // Итог дня без журнала: записи журнала с ним бы разошлись, поэтому удаляются
+    pub fn replace_water_day(&self, date: &str, ml: u32) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        tx.execute("DELETE FROM water_log WHERE date = ?1", params![date])?;
+        tx.execute(
+            "INSERT INTO water (date, ml) VALUES (?1, ?2)
+             ON CONFLICT(date) DO UPDATE SET ml = excluded.ml",
+            params![date, ml],
+        )?;
+        tx.commit()
+    }
+
+    pub fn water_days(&self) -> Result<Vec<(String, u32)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT date, ml FROM water WHERE ml > 0 ORDER BY date")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn water_log(&self, date: &str) -> Result<Vec<WaterIntake>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, time, beverage, ml, hydration_ml FROM water_log WHERE date = ?1 ORDER BY time, id",
+        )?;
+        let rows = stmt.query_map(params![date], |row| {
+            Ok(WaterIntake {
+                id: row.get::<_, i64>(0)? as u64,
+                time: row.get(1)?,
+                beverage: row.get(2)?,
+                ml: row.get(3)?,
+                hydration_ml: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Запись в журнал сразу увеличивает итог дня
+    pub fn add_water_intake(&self, date: &str, intake: &WaterIntake) -> Result<u64> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        tx.execute(
+            "INSERT INTO water_log (date, time, beverage, ml, hydration_ml) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![date, intake.time, intake.beverage, intake.ml, intake.hydration_ml],
+        )?;
+        let id = tx.last_insert_rowid() as u64;
+        tx.execute(
+            "INSERT INTO water (date, ml) VALUES (?1, ?2)
+             ON CONFLICT(date) DO UPDATE SET ml = ml + excluded.ml",
+            params![date, intake.hydration_ml],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn remove_water_intake(&self, date: &str, id: u64) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        let hydration_ml: Option<u32> = tx
+            .query_row(
+                "SELECT hydration_ml FROM water_log WHERE id = ?1 AND date = ?2",
+                params![id as i64, date],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(hydration_ml) = hydration_ml {
+            tx.execute("DELETE FROM water_log WHERE id = ?1", params![id as i64])?;
+            tx.execute(
+                "UPDATE water SET ml = MAX(ml - ?1, 0) WHERE date = ?2",
+                params![hydration_ml, date],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // Вес и замеры тела
+
+    pub fn body_metrics(&self) -> Result<Vec<BodyMetrics>> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT date, weight_kg, body_fat_pct, waist_cm FROM body_metrics ORDER BY date")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(BodyMetrics {
+                date: row.get(0)?,
+                weight_kg: row.get(1)?,
+                body_fat_pct: row.get(2)?,
+                waist_cm: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn set_body_metrics(&self, metrics: &BodyMetrics) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO body_metrics (date, weight_kg, body_fat_pct, waist_cm) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(date) DO UPDATE SET
+                weight_kg = excluded.weight_kg,
+                body_fat_pct = excluded.body_fat_pct,
+                waist_cm = excluded.waist_cm",
+            params![metrics.date, metrics.weight_kg, metrics.body_fat_pct, metrics.waist_cm],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_body_metrics(&self, date: &str) -> Result<()> {
+        self.conn().execute("DELETE FROM body_metrics WHERE date = ?1", params![date])?;
+        Ok(())
+    }
+
+    // Сон и самочувствие
+
+    pub fn checkin(&self, date: &str) -> Result<Option<CheckIn>> {
+        self.conn()
+            .query_row(
+                "SELECT date, sleep_hours, sleep_quality, mood, energy, note FROM checkins WHERE date = ?1",
+                params![date],
+                checkin_from_row,
+            )
+            .optional()
+    }
+
+    pub fn save_checkin(&self, checkin: &CheckIn) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO checkins (date, sleep_hours, sleep_quality, mood, energy, note)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(date) DO UPDATE SET
+                sleep_hours = excluded.sleep_hours,
+                sleep_quality = excluded.sleep_quality,
+                mood = excluded.mood,
+                energy = excluded.energy,
+                note = excluded.note",
+            params![
+                checkin.date,
+                checkin.sleep_hours,
+                checkin.sleep_quality,
+                checkin.mood,
+                checkin.energy,
+                checkin.note
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Вода, калории и отметка по дням из [from, to], только дни с данными
+    pub fn daily_health(&self, from: &str, to: &str) -> Result<Vec<DailyHealth>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT date, water_ml, kcal, checkin_date, sleep_hours, sleep_quality, mood, energy, note
+             FROM daily_health WHERE date BETWEEN ?1 AND ?2 ORDER BY date",
+        )?;
+        let rows = stmt.query_map(params![from, to], |row| {
+            let has_checkin = row.get::<_, Option<String>>(3)?.is_some();
+            Ok(DailyHealth {
+                date: row.get(0)?,
+                water_ml: row.get(1)?,
+                kcal: row.get(2)?,
+                checkin: if has_checkin {
+                    Some(CheckIn {
+                        date: row.get(0)?,
+                        sleep_hours: row.get(4)?,
+                        sleep_quality: row.get(5)?,
+                        mood: row.get(6)?,
+                        energy: row.get(7)?,
+                        note: row.get(8)?,
+                    })
+                } else {
+                    None
+                },
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Дневник питания
+
+    pub fn food_entries(&self, date: &str) -> Result<Vec<FoodEntry>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, time, name, grams, kcal, protein, fat, carbs
+             FROM food_entries WHERE date = ?1 ORDER BY time, id",
+        )?;
+        let rows = stmt.query_map(params![date], food_entry_from_row)?;
+        rows.collect()
+    }
+
+    pub fn food_days(&self) -> Result<Vec<(String, Vec<FoodEntry>)>> {
+        let dates: Vec<String> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare("SELECT DISTINCT date FROM food_entries ORDER BY date")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<Result<_>>()?
+        };
+
+        dates
+            .into_iter()
+            .map(|date| {
+                let entries = self.food_entries(&date)?;
+                Ok((date, entries))
+            })
+            .collect()
+    }
+
+    pub fn add_food_entry(&self, date: &str, entry: &FoodEntry) -> Result<u64> {
+        let conn = self.conn();
+        insert_food_entry(&conn, date, entry)?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    pub fn update_food_entry(&self, date: &str, entry: &FoodEntry) -> Result<()> {
+        self.conn().execute(
+            "UPDATE food_entries
+             SET time = ?1, name = ?2, grams = ?3, kcal = ?4, protein = ?5, fat = ?6, carbs = ?7
+             WHERE id = ?8 AND date = ?9",
+            params![
+                entry.time,
+                entry.name,
+                entry.grams,
+                entry.kcal,
+                entry.protein,
+                entry.fat,
+                entry.carbs,
+                entry.id as i64,
+                date
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_food_entry(&self, date: &str, id: u64) -> Result<()> {
+        self.conn()
+            .execute("DELETE FROM food_entries WHERE id = ?1 AND date = ?2", params![id as i64, date])?;
+        Ok(())
+    }
+
+    // Записи получают новые id
+    pub fn replace_food_day(&self, date: &str, entries: &[FoodEntry]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        tx.execute("DELETE FROM food_entries WHERE date = ?1", params![date])?;
+        for entry in entries {
+            insert_food_entry(&tx, date, entry)?;
+        }
+        tx.commit()
+    }
+
+    // JSON-документы: продукты, рецепты, кэш погоды и прочее без своей таблицы
+
+    pub fn document<T: DeserializeOwned>(&self, collection: &str, key: &str) -> Result<Option<T>> {
+        let data: Option<String> = self
+            .conn()
+            .query_row(
+                "SELECT data FROM documents WHERE collection = ?1 AND key = ?2",
+                params![collection, key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    pub fn documents<T: DeserializeOwned>(&self, collection: &str) -> Result<Vec<T>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT data FROM documents WHERE collection = ?1 ORDER BY key")?;
+        let rows = stmt.query_map(params![collection], |row| row.get::<_, String>(0))?;
+
+        let mut documents = Vec::new();
+        for data in rows {
+            // Запись в устаревшем формате пропускаем, а не роняем весь список
+            if let Ok(document) = serde_json::from_str(&data?) {
+                documents.push(document);
+            }
+        }
+        Ok(documents)
+    }
+
+    pub fn save_document<T: Serialize>(&self, collection: &str, key: &str, value: &T) -> Result<()> {
+        let data = serde_json::to_string(value)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn().execute(
+            "INSERT INTO documents (collection, key, data) VALUES (?1, ?2, ?3)
+             ON CONFLICT(collection, key) DO UPDATE SET data = excluded.data",
+            params![collection, key, data],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_document(&self, collection: &str, key: &str) -> Result<()> {
+        self.conn().execute(
+            "DELETE FROM documents WHERE collection = ?1 AND key = ?2",
+            params![collection, key],
+        )?;
+        Ok(())
+    }
+
+    // Помодоро
+
+    pub fn pomodoro_log(&self, date: &str) -> Result<Vec<PomodoroRecord>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT finished_at, minutes, task FROM pomodoro_log WHERE date = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![date], |row| {
+            Ok(PomodoroRecord {
+                finished_at: row.get(0)?,
+                minutes: row.get(1)?,
+                task: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn add_pomodoro(&self, date: &str, record: &PomodoroRecord) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO pomodoro_log (date, finished_at, minutes, task) VALUES (?1, ?2, ?3, ?4)",
+            params![date, record.finished_at, record.minutes, record.task],
+        )?;
+        Ok(())
+    }
+
+    // Уведомления: список целиком, как его держит слушатель
+
+    pub fn notifications(&self) -> Result<Vec<Notification>> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT id, app_name, summary, body, timestamp FROM notifications ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Notification {
+                id: row.get::<_, i64>(0)? as u64,
+                app_name: row.get(1)?,
+                summary: row.get(2)?,
+                body: row.get(3)?,
+                timestamp: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn replace_notifications(&self, notifications: &[Notification]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        tx.execute("DELETE FROM notifications", [])?;
+        for notification in notifications {
+            tx.execute(
+                "INSERT INTO notifications (id, app_name, summary, body, timestamp) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    notification.id as i64,
+                    notification.app_name,
+                    notification.summary,
+                    notification.body,
+                    notification.timestamp
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // Перенос старых хранилищ
+
+    pub fn legacy_imported(&self, source: &str) -> Result<bool> {
+        Ok(self
+            .conn()
+            .query_row("SELECT 1 FROM legacy_imports WHERE source = ?1", params![source], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    pub fn mark_legacy_imported(&self, source: &str) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO legacy_imports (source, imported_at) VALUES (?1, ?2)",
+            params![source, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    // Копирует таблицу spans из отдельной базы трекера активности.
+    // Без ATTACH: внутри транзакции его выполнить нельзя
+    pub fn import_spans(&self, legacy_path: &Path) -> Result<()> {
+        let legacy = Connection::open_with_flags(legacy_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut stmt = legacy.prepare("SELECT app, title, start, end FROM spans ORDER BY start")?;
+        let spans = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut conn = self.conn();
+        let tx = conn.savepoint()?;
+        for (app, title, start, end) in spans {
+            tx.execute(
+                "INSERT INTO spans (app, title, start, end) VALUES (?1, ?2, ?3, ?4)",
+                params![app, title, start, end],
+            )?;
+        }
+        tx.commit()
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn insert_food_entry(conn: &Connection, date: &str, entry: &FoodEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO food_entries (date, time, name, grams, kcal, protein, fat, carbs)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![date, entry.time, entry.name, entry.grams, entry.kcal, entry.protein, entry.fat, entry.carbs],
+    )?;
+    Ok(())
+}
+
+fn food_entry_from_row(row: &rusqlite::Row) -> Result<FoodEntry> {
+    Ok(FoodEntry {
+        id: row.get::<_, i64>(0)? as u64,
+        time: row.get(1)?,
+        name: row.get(2)?,
+        grams: row.get(3)?,
+        kcal: row.get(4)?,
+        protein: row.get(5)?,
+        fat: row.get(6)?,
+        carbs: row.get(7)?,
+    })
+}
+
+fn checkin_from_row(row: &rusqlite::Row) -> Result<CheckIn> {
+    Ok(CheckIn {
+        date: row.get(0)?,
+        sleep_hours: row.get(1)?,
+        sleep_quality: row.get(2)?,
+        mood: row.get(3)?,
+        energy: row.get(4)?,
+        note: row.get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::temp_dir;
+
+    fn user_version(path: &Path) -> usize {
+        Connection::open(path)
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn fresh_database_gets_every_migration_once() {
+        let path = temp_dir("migrate-fresh").join("sidebar.db");
+        let store = DataStore::open_at(&path).unwrap();
+        store.set_water("2024-05-01", 500).unwrap();
+        drop(store);
+        assert_eq!(user_version(&path), MIGRATIONS.len());
+
+        // Повторное открытие ничего не пересоздаёт
+        let store = DataStore::open_at(&path).unwrap();
+        assert_eq!(user_version(&path), MIGRATIONS.len());
+        assert_eq!(store.water("2024-05-01").unwrap(), 500);
+    }
+
+    #[test]
+    fn old_schema_is_upgraded_with_data_kept() {
+        let path = temp_dir("migrate-old").join("sidebar.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            conn.execute("INSERT INTO water (date, ml) VALUES ('2024-05-01', 750)", []).unwrap();
+        }
+
+        let store = DataStore::open_at(&path).unwrap();
+        assert_eq!(user_version(&path), MIGRATIONS.len());
+        assert_eq!(store.water("2024-05-01").unwrap(), 750);
+        // Таблицы из следующих миграций уже есть
+        assert!(store.water_log("2024-05-01").unwrap().is_empty());
+        assert!(store.body_metrics().unwrap().is_empty());
+        assert_eq!(store.daily_health("2024-05-01", "2024-05-01").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn transaction_rolls_back_every_change_on_error() {
+        let store = DataStore::open_at(&temp_dir("transaction").join("sidebar.db")).unwrap();
+
+        let result: Result<()> = store.in_transaction(|store| {
+            store.set_water("2024-05-01", 300)?;
+            store.mark_legacy_imported("water_db")?;
+            Err(rusqlite::Error::InvalidQuery)
+        });
+        assert!(result.is_err());
+        assert!(!store.has_water("2024-05-01").unwrap());
+        assert!(!store.legacy_imported("water_db").unwrap());
+
+        // Вложенный savepoint фиксируется вместе с внешней транзакцией
+        store
+            .in_transaction(|store| {
+                store.replace_food_day("2024-05-01", &[])?;
+                store.set_water("2024-05-01", 300)
+            })
+            .unwrap();
+        assert_eq!(store.water("2024-05-01").unwrap(), 300);
+    }
+
+    #[test]
+    fn writes_from_other_threads_survive_a_rollback() {
+        let store = DataStore::open_at(&temp_dir("transaction-threads").join("sidebar.db")).unwrap();
+
+        let mut writer = None;
+        let result: Result<()> = store.in_transaction(|tx| {
+            tx.set_water("2024-05-01", 300)?;
+            let other = store.clone();
+            writer = Some(std::thread::spawn(move || other.set_water("2024-05-02", 500)));
+            // Даём второму потоку дойти до записи, пока транзакция открыта
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            Err(rusqlite::Error::InvalidQuery)
+        });
+        assert!(result.is_err());
+
+        writer.unwrap().join().unwrap().unwrap();
+        assert!(!store.has_water("2024-05-01").unwrap());
+        assert_eq!(store.water("2024-05-02").unwrap(), 500);
+    }
+}
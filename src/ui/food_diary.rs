@@ -0,0 +1,450 @@
+use crate::ui::data_store::{DataStore, Result};
+use crate::ui::settings::get_day_start_hour;
+use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+const SEARCH_LIMIT: usize = 10;
+pub const MIGRATED_ENTRY_NAME: &str = "Daily total (migrated)";
+
+static DAY_START_CACHE: Lazy<RwLock<Option<u32>>> = Lazy::new(|| RwLock::new(None));
+
+fn day_start_hour() -> u32 {
+    if let Ok(cache) = DAY_START_CACHE.read() {
+        if let Some(hour) = *cache {
+            return hour;
+        }
+    }
+
+    let hour = get_day_start_hour();
+    if let Ok(mut cache) = DAY_START_CACHE.write() {
+        *cache = Some(hour);
+    }
+    hour
+}
+
+pub fn invalidate_day_start_cache() {
+    if let Ok(mut cache) = DAY_START_CACHE.write() {
+        *cache = None;
+    }
+}
+
+// День дневников начинается в day_start_hour: до этого часа продолжается вчерашний
+pub fn current_day() -> NaiveDate {
+    (Local::now().naive_local() - Duration::hours(day_start_hour() as i64)).date()
+}
+
+// Сколько осталось до начала следующего дня дневников
+pub fn until_next_day() -> std::time::Duration {
+    let next_start = (current_day() + Duration::days(1))
+        .and_time(NaiveTime::from_hms_opt(day_start_hour(), 0, 0).unwrap_or_default());
+    (next_start - Local::now().naive_local())
+        .to_std()
+        .unwrap_or_default()
+}
+
+// Одна запись дневника питания; макронутриенты в граммах, если известны
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FoodEntry {
+    pub id: u64,
+    pub time: String, // "%H:%M"
+    pub name: String,
+    pub grams: u32,
+    pub kcal: i32,
+    pub protein: Option<f32>,
+    pub fat: Option<f32>,
+    pub carbs: Option<f32>,
+}
+
+// Продукт из локальной базы: значения на 100 г
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FoodItem {
+    pub name: String,
+    pub kcal_per_100g: i32,
+    pub protein: Option<f32>,
+    pub fat: Option<f32>,
+    pub carbs: Option<f32>,
+    pub favorite: bool,
+    pub last_used: i64,  // Метка времени последнего добавления, 0 — ещё не ели
+    pub last_grams: u32, // Порция для быстрого добавления
+}
+
+impl FoodItem {
+    pub fn macros(&self) -> [Option<f32>; 3] {
+        [self.protein, self.fat, self.carbs]
+    }
+
+    // Запись дневника на указанную порцию
+    pub fn to_entry(&self, grams: u32) -> FoodEntry {
+        let scale = |per_100g: f32| per_100g * grams as f32 / 100.0;
+        FoodEntry {
+            id: 0,
+            time: Local::now().format("%H:%M").to_string(),
+            name: self.name.clone(),
+            grams,
+            kcal: self.kcal_per_100g * grams as i32 / 100,
+            protein: self.protein.map(scale),
+            fat: self.fat.map(scale),
+            carbs: self.carbs.map(scale),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ingredient {
+    pub food: String, // Имя продукта из локальной базы
+    pub grams: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Recipe {
+    pub name: String,
+    pub ingredients: Vec<Ingredient>,
+    pub portions: u32,
+}
+
+// Пищевая ценность рецепта, посчитанная по локальной базе
+#[derive(Debug, Clone, Default)]
+pub struct RecipeNutrition {
+    pub grams: u32,
+    pub total: DailyTotals,
+    pub missing: Vec<String>, // Ингредиенты, которых нет в базе
+}
+
+impl RecipeNutrition {
+    pub fn per_portion(&self, portions: u32) -> (u32, DailyTotals) {
+        let portions = portions.max(1);
+        let divide = |value: f32| value / portions as f32;
+        (
+            self.grams / portions,
+            DailyTotals {
+                kcal: self.total.kcal / portions as i32,
+                protein: divide(self.total.protein),
+                fat: divide(self.total.fat),
+                carbs: divide(self.total.carbs),
+            },
+        )
+    }
+}
+
+// Шаблон приёма пищи хранит готовые записи и добавляет их разом
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MealTemplate {
+    pub name: String,
+    pub items: Vec<FoodEntry>,
+}
+
+pub const FOODS_COLLECTION: &str = "foods";
+pub const RECIPES_COLLECTION: &str = "recipes";
+pub const MEALS_COLLECTION: &str = "meals";
+
+// Записи привязаны к дню "%Y-%m-%d", продукты, рецепты и шаблоны
+// хранятся документами под именем в нижнем регистре
+#[derive(Clone)]
+pub struct FoodDiary {
+    store: DataStore,
+}
+
+impl FoodDiary {
+    pub fn open() -> Result<Self> {
+        Ok(Self {
+            store: DataStore::open()?,
+        })
+    }
+
+    pub fn date_key(date: NaiveDate) -> String {
+        date.format("%Y-%m-%d").to_string()
+    }
+
+    pub fn today_key() -> String {
+        Self::date_key(current_day())
+    }
+
+    pub fn load_day(&self, date: &str) -> Result<Vec<FoodEntry>> {
+        self.store.food_entries(date)
+    }
+
+    pub fn day_totals(&self, date: NaiveDate) -> Result<DailyTotals> {
+        Ok(DailyTotals::from_entries(&self.load_day(&Self::date_key(date))?))
+    }
+
+    // Все дни с записями по возрастанию даты
+    pub fn all_days(&self) -> Result<Vec<(String, Vec<FoodEntry>)>> {
+        self.store.food_days()
+    }
+
+    pub fn add(&self, date: &str, mut entry: FoodEntry) -> Result<FoodEntry> {
+        entry.id = self.store.add_food_entry(date, &entry)?;
+        Ok(entry)
+    }
+
+    pub fn update(&self, date: &str, entry: FoodEntry) -> Result<()> {
+        self.store.update_food_entry(date, &entry)
+    }
+
+    pub fn remove(&self, date: &str, id: u64) -> Result<()> {
+        self.store.remove_food_entry(date, id)
+    }
+
+    fn food_key(name: &str) -> String {
+        name.trim().to_lowercase()
+    }
+
+    pub fn get_food(&self, name: &str) -> Result<Option<FoodItem>> {
+        self.store.document(FOODS_COLLECTION, &Self::food_key(name))
+    }
+
+    pub fn save_food(&self, item: &FoodItem) -> Result<()> {
+        self.save_named(FOODS_COLLECTION, &item.name, item)
+    }
+
+    pub fn remove_food(&self, name: &str) -> Result<()> {
+        self.store.remove_document(FOODS_COLLECTION, &Self::food_key(name))
+    }
+
+    // Обновляет значения продукта, сохраняя избранное
+    pub fn remember_food(&self, mut item: FoodItem) -> Result<()> {
+        if let Some(existing) = self.get_food(&item.name)? {
+            item.favorite = item.favorite || existing.favorite;
+            if item.last_used == 0 {
+                item.last_used = existing.last_used;
+                item.last_grams = existing.last_grams;
+            }
+        }
+        self.save_food(&item)
+    }
+
+    pub fn set_favorite(&self, name: &str, favorite: bool) -> Result<()> {
+        if let Some(mut item) = self.get_food(name)? {
+            item.favorite = favorite;
+            self.save_food(&item)?;
+        }
+        Ok(())
+    }
+
+    pub fn all_foods(&self) -> Vec<FoodItem> {
+        self.store.documents(FOODS_COLLECTION).unwrap_or_default()
+    }
+
+    pub fn favorites(&self) -> Vec<FoodItem> {
+        let mut foods: Vec<FoodItem> = self.all_foods().into_iter().filter(|item| item.favorite).collect();
+        foods.sort_by(|a, b| a.name.cmp(&b.name));
+        foods
+    }
+
+    pub fn recent(&self, limit: usize) -> Vec<FoodItem> {
+        let mut foods: Vec<FoodItem> = self.all_foods().into_iter().filter(|item| item.last_used > 0).collect();
+        foods.sort_by_key(|item| std::cmp::Reverse(item.last_used));
+        foods.truncate(limit);
+        foods
+    }
+
+    pub fn search_foods(&self, query: &str) -> Vec<FoodItem> {
+        let mut matches: Vec<(i32, FoodItem)> = self
+            .all_foods()
+            .into_iter()
+            .filter_map(|item| fuzzy_score(query, &item.name).map(|score| (score, item)))
+            .collect();
+
+        // При равной оценке выше избранные и недавно использованные
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then(b.favorite.cmp(&a.favorite))
+                .then(b.last_used.cmp(&a.last_used))
+        });
+        matches.into_iter().take(SEARCH_LIMIT).map(|(_, item)| item).collect()
+    }
+
+    fn save_named<T: Serialize>(&self, collection: &str, name: &str, value: &T) -> Result<()> {
+        let key = Self::food_key(name);
+        if key.is_empty() {
+            return Ok(());
+        }
+        self.store.save_document(collection, &key, value)
+    }
+
+    pub fn recipes(&self) -> Vec<Recipe> {
+        let mut recipes: Vec<Recipe> = self.store.documents(RECIPES_COLLECTION).unwrap_or_default();
+        recipes.sort_by(|a, b| a.name.cmp(&b.name));
+        recipes
+    }
+
+    pub fn save_recipe(&self, recipe: &Recipe) -> Result<()> {
+        self.save_named(RECIPES_COLLECTION, &recipe.name, recipe)
+    }
+
+    pub fn remove_recipe(&self, name: &str) -> Result<()> {
+        self.store.remove_document(RECIPES_COLLECTION, &Self::food_key(name))
+    }
+
+    pub fn recipe_nutrition(&self, recipe: &Recipe) -> Result<RecipeNutrition> {
+        let mut nutrition = RecipeNutrition::default();
+
+        for ingredient in &recipe.ingredients {
+            nutrition.grams += ingredient.grams;
+            match self.get_food(&ingredient.food)? {
+                Some(item) => {
+                    let entry = item.to_entry(ingredient.grams);
+                    nutrition.total.kcal += entry.kcal;
+                    nutrition.total.protein += entry.protein.unwrap_or(0.0);
+                    nutrition.total.fat += entry.fat.unwrap_or(0.0);
+                    nutrition.total.carbs += entry.carbs.unwrap_or(0.0);
+                }
+                None => nutrition.missing.push(ingredient.food.clone()),
+            }
+        }
+        Ok(nutrition)
+    }
+
+    // Запись "1 порция" рецепта
+    pub fn recipe_portion_entry(&self, recipe: &Recipe, portions: f32) -> Result<FoodEntry> {
+        let nutrition = self.recipe_nutrition(recipe)?;
+        let (grams, per_portion) = nutrition.per_portion(recipe.portions);
+        let scale = |value: f32| value * portions;
+
+        Ok(FoodEntry {
+            id: 0,
+            time: Local::now().format("%H:%M").to_string(),
+            name: format!(
+                "{} ({} {})",
+                recipe.name,
+                portions,
+                if portions == 1.0 { "portion" } else { "portions" }
+            ),
+            grams: (grams as f32 * portions).round() as u32,
+            kcal: (per_portion.kcal as f32 * portions).round() as i32,
+            protein: Some(scale(per_portion.protein)),
+            fat: Some(scale(per_portion.fat)),
+            carbs: Some(scale(per_portion.carbs)),
+        })
+    }
+
+    pub fn meal_templates(&self) -> Vec<MealTemplate> {
+        let mut meals: Vec<MealTemplate> = self.store.documents(MEALS_COLLECTION).unwrap_or_default();
+        meals.sort_by(|a, b| a.name.cmp(&b.name));
+        meals
+    }
+
+    pub fn save_meal_template(&self, meal: &MealTemplate) -> Result<()> {
+        self.save_named(MEALS_COLLECTION, &meal.name, meal)
+    }
+
+    pub fn remove_meal_template(&self, name: &str) -> Result<()> {
+        self.store.remove_document(MEALS_COLLECTION, &Self::food_key(name))
+    }
+
+    pub fn apply_meal_template(&self, date: &str, meal: &MealTemplate) -> Result<()> {
+        let time = Local::now().format("%H:%M").to_string();
+        for item in &meal.items {
+            self.add(date, FoodEntry { time: time.clone(), ..item.clone() })?;
+        }
+        Ok(())
+    }
+}
+
+// Нечёткое сравнение: подстрока, затем все слова запроса, затем буквы по порядку
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let query = query.trim().to_lowercase();
+    let name = name.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    if let Some(position) = name.find(&query) {
+        let prefix_bonus = if position == 0 { 50 } else { 0 };
+        return Some(200 + prefix_bonus - position.min(50) as i32);
+    }
+
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let found = words.iter().filter(|word| name.contains(*word)).count();
+    if found == words.len() {
+        return Some(150);
+    }
+
+    // Буквы запроса встречаются в названии в том же порядке ("grch" → "grechka")
+    let mut name_chars = name.chars();
+    let mut gaps = 0;
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let mut skipped = 0;
+        loop {
+            match name_chars.next() {
+                Some(c) if c == query_char => break,
+                Some(_) => skipped += 1,
+                None => return (found > 0).then_some((50 * found as i32).min(100)),
+            }
+        }
+        gaps += skipped;
+    }
+    // Слишком разреженное совпадение считаем случайным
+    let score = 100 - gaps * 5;
+    if score > 20 {
+        Some(score)
+    } else {
+        (found > 0).then_some((50 * found as i32).min(100))
+    }
+}
+
+// Итоги дня выводятся из записей, отдельно не хранятся
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DailyTotals {
+    pub kcal: i32,
+    pub protein: f32,
+    pub fat: f32,
+    pub carbs: f32,
+}
+
+impl DailyTotals {
+    pub fn from_entries(entries: &[FoodEntry]) -> Self {
+        entries.iter().fold(Self::default(), |totals, entry| Self {
+            kcal: totals.kcal + entry.kcal,
+            protein: totals.protein + entry.protein.unwrap_or(0.0),
+            fat: totals.fat + entry.fat.unwrap_or(0.0),
+            carbs: totals.carbs + entry.carbs.unwrap_or(0.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_matches_rank_by_position() {
+        assert_eq!(fuzzy_score("grech", "Grechka"), Some(250));
+        assert_eq!(fuzzy_score("chka", "grechka"), Some(197));
+        assert!(fuzzy_score("rice", "Rice pudding") > fuzzy_score("rice", "Fried rice"));
+    }
+
+    #[test]
+    fn all_query_words_match_in_any_order() {
+        assert_eq!(fuzzy_score("rice chicken", "Chicken with rice"), Some(150));
+        // Совпало только одно слово
+        assert_eq!(fuzzy_score("apple pie", "Apple juice"), Some(50));
+    }
+
+    #[test]
+    fn letters_in_order_match_abbreviations() {
+        assert_eq!(fuzzy_score("grch", "grechka"), Some(95));
+        assert_eq!(fuzzy_score("gk", "grechka"), Some(80));
+        assert!(fuzzy_score("grch", "grechka") > fuzzy_score("gk", "grechka"));
+    }
+
+    #[test]
+    fn unrelated_and_sparse_matches_are_rejected() {
+        assert_eq!(fuzzy_score("", "grechka"), None);
+        assert_eq!(fuzzy_score("   ", "grechka"), None);
+        assert_eq!(fuzzy_score("xyz", "grechka"), None);
+        assert_eq!(fuzzy_score("ga", "g________________a"), None);
+    }
+
+    #[test]
+    fn substring_beats_word_and_letter_matches() {
+        let substring = fuzzy_score("oat", "Oatmeal with milk");
+        let words = fuzzy_score("milk oat", "Oatmeal with milk");
+        let letters = fuzzy_score("otml", "Oatmeal with milk");
+        assert!(substring > words);
+        assert!(words > letters);
+    }
+}
@@ -0,0 +1,473 @@
+use crate::ui::data_store::{DataStore, Result as StoreResult};
+use crate::ui::food_diary::{FoodDiary, FoodEntry};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const CSV_HEADER: &str = "date,kind,time,name,grams,kcal,protein,fat,carbs,water_ml";
+
+// Растёт после каждого импорта, виджеты по нему перечитывают базы
+static DATA_VERSION: AtomicU64 = AtomicU64::new(0);
+
+pub fn data_version() -> u64 {
+    DATA_VERSION.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    // Добавляет только отсутствующие дни воды и новые записи еды
+    Merge,
+    // Дни из файла полностью заменяют локальные
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase()) {
+            Some(ext) if ext == "csv" => Ok(Format::Csv),
+            Some(ext) if ext == "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown file type: {} (expected .csv or .json)", path.display())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct WaterDay {
+    date: String,
+    water_ml: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FoodDay {
+    date: String,
+    entries: Vec<FoodEntry>,
+}
+
+// Даты везде в ISO, "%Y-%m-%d". Вода — только итоги дней, без журнала напитков;
+// вес и отметки о самочувствии в обмен не входят
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct HealthData {
+    water: Vec<WaterDay>,
+    food: Vec<FoodDay>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub water_days: usize,
+    pub food_entries: usize,
+}
+
+// Раскрывает "~/" в начале пути
+pub fn expand_path(path: &str) -> PathBuf {
+    let path = path.trim();
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn export_to(path: &Path) -> Result<usize, String> {
+    let format = Format::from_path(path)?;
+    let data = collect()?;
+    let records = data.water.len() + data.food.iter().map(|day| day.entries.len()).sum::<usize>();
+
+    let content = match format {
+        Format::Csv => to_csv(&data),
+        Format::Json => serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?,
+    };
+    fs::write(path, content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(records)
+}
+
+pub fn import_from(path: &Path, mode: ImportMode) -> Result<ImportSummary, String> {
+    let format = Format::from_path(path)?;
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let data = match format {
+        Format::Csv => from_csv(&content)?,
+        Format::Json => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+    };
+    validate_dates(&data)?;
+
+    // Ошибка на середине файла не должна оставлять базу наполовину импортированной
+    let store = DataStore::open().map_err(|e| e.to_string())?;
+    let summary = store
+        .in_transaction(|store| apply(store, data, mode))
+        .map_err(|e| e.to_string())?;
+    DATA_VERSION.fetch_add(1, Ordering::Relaxed);
+    Ok(summary)
+}
+
+fn collect() -> Result<HealthData, String> {
+    let store = DataStore::open().map_err(|e| e.to_string())?;
+    let diary = FoodDiary::open().map_err(|e| e.to_string())?;
+
+    let water = store
+        .water_days()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(date, water_ml)| WaterDay { date, water_ml })
+        .collect();
+
+    let food = diary
+        .all_days()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(date, entries)| FoodDay { date, entries })
+        .collect();
+
+    Ok(HealthData { water, food })
+}
+
+fn apply(store: &DataStore, data: HealthData, mode: ImportMode) -> StoreResult<ImportSummary> {
+    let mut summary = ImportSummary::default();
+
+    for day in data.water {
+        if mode == ImportMode::Merge && store.has_water(&day.date)? {
+            continue;
+        }
+        store.replace_water_day(&day.date, day.water_ml)?;
+        summary.water_days += 1;
+    }
+
+    for day in data.food {
+        let mut entries = match mode {
+            ImportMode::Merge => store.food_entries(&day.date)?,
+            ImportMode::Overwrite => Vec::new(),
+        };
+        for entry in day.entries {
+            // Повторный импорт того же файла не должен дублировать записи
+            let duplicate = entries.iter().any(|existing| {
+                existing.time == entry.time
+                    && existing.name == entry.name
+                    && existing.grams == entry.grams
+                    && existing.kcal == entry.kcal
+            });
+            if !duplicate {
+                entries.push(entry);
+                summary.food_entries += 1;
+            }
+        }
+        store.replace_food_day(&day.date, &entries)?;
+    }
+
+    Ok(summary)
+}
+
+fn validate_dates(data: &HealthData) -> Result<(), String> {
+    let dates = data
+        .water
+        .iter()
+        .map(|day| &day.date)
+        .chain(data.food.iter().map(|day| &day.date));
+    for date in dates {
+        if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(format!("Invalid date '{}', expected YYYY-MM-DD", date));
+        }
+    }
+    Ok(())
+}
+
+fn to_csv(data: &HealthData) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+
+    for day in &data.water {
+        lines.push(format!("{},water,,,,,,,,{}", day.date, day.water_ml));
+    }
+    for day in &data.food {
+        for entry in &day.entries {
+            let optional = |value: Option<f32>| value.map(|value| value.to_string()).unwrap_or_default();
+            lines.push(format!(
+                "{},food,{},{},{},{},{},{},{},",
+                day.date,
+                entry.time,
+                csv_escape(&entry.name),
+                entry.grams,
+                entry.kcal,
+                optional(entry.protein),
+                optional(entry.fat),
+                optional(entry.carbs),
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn from_csv(content: &str) -> Result<HealthData, String> {
+    let mut water = Vec::new();
+    let mut food: BTreeMap<String, Vec<FoodEntry>> = BTreeMap::new();
+
+    for (index, (line_number, fields)) in csv_records(content).into_iter().enumerate() {
+        let is_header = index == 0 && fields.join(",").trim() == CSV_HEADER;
+        if is_header || fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let field = |i: usize| fields.get(i).map(|value| value.trim()).unwrap_or("");
+        let number_error = |name: &str| format!("Line {}: invalid {}", line_number, name);
+
+        match field(1) {
+            "water" => water.push(WaterDay {
+                date: field(0).to_string(),
+                water_ml: field(9).parse().map_err(|_| number_error("water_ml"))?,
+            }),
+            "food" => {
+                let optional = |i: usize, name: &str| -> Result<Option<f32>, String> {
+                    match field(i) {
+                        "" => Ok(None),
+                        value => value.parse().map(Some).map_err(|_| number_error(name)),
+                    }
+                };
+                food.entry(field(0).to_string()).or_default().push(FoodEntry {
+                    id: 0,
+                    time: field(2).to_string(),
+                    name: field(3).to_string(),
+                    grams: field(4).parse().map_err(|_| number_error("grams"))?,
+                    kcal: field(5).parse().map_err(|_| number_error("kcal"))?,
+                    protein: optional(6, "protein")?,
+                    fat: optional(7, "fat")?,
+                    carbs: optional(8, "carbs")?,
+                });
+            }
+            other => return Err(format!("Line {}: unknown kind '{}'", line_number, other)),
+        }
+    }
+
+    Ok(HealthData {
+        water,
+        food: food.into_iter().map(|(date, entries)| FoodDay { date, entries }).collect(),
+    })
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Записи с номером строки, на которой они начинаются. Поле в кавычках
+// может содержать перевод строки, поэтому делить файл на строки заранее нельзя
+fn csv_records(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut line_number = 1;
+    let mut record_start = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut current));
+                records.push((record_start, std::mem::take(&mut fields)));
+                line_number += 1;
+                record_start = line_number;
+            }
+            _ => {
+                if c == '\n' {
+                    line_number += 1;
+                }
+                current.push(c);
+            }
+        }
+    }
+    if !current.is_empty() || !fields.is_empty() {
+        fields.push(current);
+        records.push((record_start, fields));
+    }
+    records
+}
+
+const CLI_USAGE: &str = "Usage:
+  SideBar health export <file.csv|file.json>
+  SideBar health import <file.csv|file.json> [--overwrite]";
+
+// Точка входа для `SideBar health ...`, возвращает код выхода
+pub fn run_cli(args: &[String]) -> i32 {
+    let command = args.first().map(String::as_str);
+    let Some(path) = args.get(1).map(|path| expand_path(path)) else {
+        eprintln!("{}", CLI_USAGE);
+        return 2;
+    };
+
+    let result = match command {
+        Some("export") => export_to(&path).map(|records| format!("Exported {} records to {}", records, path.display())),
+        Some("import") => {
+            let mode = if args.iter().any(|arg| arg == "--overwrite") {
+                ImportMode::Overwrite
+            } else {
+                ImportMode::Merge
+            };
+            import_from(&path, mode).map(|summary| {
+                format!(
+                    "Imported {} water days and {} food entries",
+                    summary.water_days, summary.food_entries
+                )
+            })
+        }
+        _ => {
+            eprintln!("{}", CLI_USAGE);
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::health_widget::WaterIntake;
+    use crate::ui::test_support::temp_dir;
+
+    fn entry(time: &str, name: &str, grams: u32, kcal: i32, macros: [Option<f32>; 3]) -> FoodEntry {
+        FoodEntry {
+            id: 0,
+            time: time.to_string(),
+            name: name.to_string(),
+            grams,
+            kcal,
+            protein: macros[0],
+            fat: macros[1],
+            carbs: macros[2],
+        }
+    }
+
+    fn sample() -> HealthData {
+        HealthData {
+            water: vec![
+                WaterDay { date: "2024-05-01".to_string(), water_ml: 1750 },
+                WaterDay { date: "2024-05-02".to_string(), water_ml: 0 },
+            ],
+            food: vec![
+                FoodDay {
+                    date: "2024-05-01".to_string(),
+                    entries: vec![
+                        entry("08:15", "Oatmeal", 250, 310, [Some(11.5), Some(6.0), Some(52.25)]),
+                        entry("13:00", "Soup, \"Borscht\"", 300, 180, [None, None, None]),
+                        entry("19:30", "Salad\nwith feta", 150, 210, [Some(6.0), None, Some(8.0)]),
+                    ],
+                },
+                FoodDay {
+                    date: "2024-05-02".to_string(),
+                    entries: vec![entry("00:00", "Migrated, multiline\r\nname", 0, 1200, [None, None, None])],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_every_field() {
+        let data = sample();
+        let csv = to_csv(&data);
+        assert_eq!(from_csv(&csv).unwrap(), data);
+    }
+
+    #[test]
+    fn csv_round_trip_survives_windows_line_endings() {
+        let data = sample();
+        let csv = to_csv(&data).replace('\n', "\r\n");
+        let parsed = from_csv(&csv).unwrap();
+        assert_eq!(parsed.water, data.water);
+        assert_eq!(parsed.food[0].entries[0], data.food[0].entries[0]);
+        assert_eq!(parsed.food[0].entries.len(), 3);
+    }
+
+    #[test]
+    fn csv_errors_point_to_the_starting_line() {
+        let csv = format!("{}\n2024-05-01,food,12:00,\"Two\nlines\",100,50,,,,\n2024-05-01,food,13:00,Bad,x,50,,,,\n", CSV_HEADER);
+        assert_eq!(from_csv(&csv).unwrap_err(), "Line 4: invalid grams");
+
+        let csv = "2024-05-01,snack,,,,,,,,\n";
+        assert_eq!(from_csv(csv).unwrap_err(), "Line 1: unknown kind 'snack'");
+    }
+
+    #[test]
+    fn csv_skips_header_and_blank_lines() {
+        let csv = format!("{}\n\n2024-05-01,water,,,,,,,,500\n\n", CSV_HEADER);
+        let data = from_csv(&csv).unwrap();
+        assert_eq!(data.water, vec![WaterDay { date: "2024-05-01".to_string(), water_ml: 500 }]);
+        assert!(data.food.is_empty());
+    }
+
+    fn store_with_local_day(name: &str) -> DataStore {
+        let store = DataStore::open_at(&temp_dir(name).join("sidebar.db")).unwrap();
+        let intake = WaterIntake {
+            id: 0,
+            time: "09:00".to_string(),
+            beverage: "Water".to_string(),
+            ml: 400,
+            hydration_ml: 400,
+        };
+        store.add_water_intake("2024-05-01", &intake).unwrap();
+        store
+            .replace_food_day("2024-05-01", &[entry("07:00", "Toast", 50, 130, [None, None, None])])
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn merge_keeps_local_days_and_skips_duplicates() {
+        let store = store_with_local_day("import-merge");
+
+        let summary = apply(&store, sample(), ImportMode::Merge).unwrap();
+        assert_eq!(summary.water_days, 1);
+        assert_eq!(summary.food_entries, 4);
+        assert_eq!(store.water("2024-05-01").unwrap(), 400);
+        assert_eq!(store.water_log("2024-05-01").unwrap().len(), 1);
+        assert!(store.has_water("2024-05-02").unwrap());
+        assert_eq!(store.food_entries("2024-05-01").unwrap().len(), 4);
+
+        // Повторный импорт ничего не добавляет
+        let summary = apply(&store, sample(), ImportMode::Merge).unwrap();
+        assert_eq!((summary.water_days, summary.food_entries), (0, 0));
+        assert_eq!(store.food_entries("2024-05-01").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn overwrite_replaces_days_and_clears_their_water_log() {
+        let store = store_with_local_day("import-overwrite");
+
+        let summary = apply(&store, sample(), ImportMode::Overwrite).unwrap();
+        assert_eq!(summary.water_days, 2);
+        assert_eq!(summary.food_entries, 4);
+        assert_eq!(store.water("2024-05-01").unwrap(), 1750);
+        assert!(store.water_log("2024-05-01").unwrap().is_empty());
+        let names: Vec<String> = store
+            .food_entries("2024-05-01")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, ["Oatmeal", "Soup, \"Borscht\"", "Salad\nwith feta"]);
+    }
+}
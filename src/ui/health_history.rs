@@ -0,0 +1,412 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::data_store::Result;
+use crate::ui::food_diary::current_day;
+use crate::ui::health_export::data_version;
+use crate::ui::health_widget::{FoodWidget, WaterWidget};
+use crate::ui::settings::{get_daily_calorie_goal, get_daily_water_goal};
+use chrono::{Datelike, Duration, NaiveDate};
+use egui::{Color32, Frame, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2, Window};
+
+const CHART_HEIGHT: f32 = 90.0;
+const DATE_LABEL_HEIGHT: f32 = 14.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HistoryRange {
+    #[default]
+    Week,
+    Month,
+    Quarter,
+}
+
+impl HistoryRange {
+    const ALL: [HistoryRange; 3] = [HistoryRange::Week, HistoryRange::Month, HistoryRange::Quarter];
+
+    fn days(&self) -> i64 {
+        match self {
+            HistoryRange::Week => 7,
+            HistoryRange::Month => 30,
+            HistoryRange::Quarter => 90,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            HistoryRange::Week => "7 days",
+            HistoryRange::Month => "30 days",
+            HistoryRange::Quarter => "90 days",
+        }
+    }
+}
+
+// Воду нужно добрать до цели, калории — не превысить
+#[derive(Clone, Copy, PartialEq)]
+enum GoalKind {
+    AtLeast,
+    AtMost,
+}
+
+struct Series {
+    title: &'static str,
+    unit: &'static str,
+    goal: f32,
+    kind: GoalKind,
+    color: Color32,
+    days: Vec<(NaiveDate, f32)>,
+}
+
+impl Series {
+    fn met(&self, value: f32) -> bool {
+        match self.kind {
+            GoalKind::AtLeast => self.goal > 0.0 && value >= self.goal,
+            GoalKind::AtMost => value > 0.0 && value <= self.goal,
+        }
+    }
+
+    // Незакрытый сегодняшний день серию не обрывает
+    fn current_streak(&self) -> usize {
+        let mut days = self.days.iter().rev().peekable();
+        if days.peek().is_some_and(|(_, value)| !self.met(*value)) {
+            days.next();
+        }
+        days.take_while(|(_, value)| self.met(*value)).count()
+    }
+
+    fn best_streak(&self) -> usize {
+        let mut best = 0;
+        let mut current = 0;
+        for (_, value) in &self.days {
+            current = if self.met(*value) { current + 1 } else { 0 };
+            best = best.max(current);
+        }
+        best
+    }
+
+    // Среднее по неделям с понедельника, только по дням с данными
+    fn weekly_averages(&self) -> Vec<(NaiveDate, f32)> {
+        let mut weeks: Vec<(NaiveDate, f32, u32)> = Vec::new();
+        for (date, value) in &self.days {
+            let week_start = *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            match weeks.last_mut() {
+                Some((start, sum, count)) if *start == week_start => {
+                    if *value > 0.0 {
+                        *sum += value;
+                        *count += 1;
+                    }
+                }
+                _ => weeks.push((week_start, *value, (*value > 0.0) as u32)),
+            }
+        }
+
+        weeks
+            .into_iter()
+            .filter(|(_, _, count)| *count > 0)
+            .map(|(start, sum, count)| (start, sum / count as f32))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct HealthHistory {
+    pub open: bool,
+    range: HistoryRange,
+    loaded: Option<(HistoryRange, NaiveDate, u64)>,
+    past: Vec<(NaiveDate, f32, f32)>, // Дата, вода в мл, калории; без сегодняшнего дня
+}
+
+impl HealthHistory {
+    pub fn refresh(&mut self) {
+        self.loaded = None;
+    }
+
+    // Прошедшие дни меняет только импорт, поэтому читаются из базы один раз на диапазон
+    fn load(&mut self, food: &FoodWidget, water: &WaterWidget) -> Result<()> {
+        let today = current_day();
+        if self.loaded == Some((self.range, today, data_version())) {
+            return Ok(());
+        }
+
+        self.past.clear();
+        for offset in (1..self.range.days()).rev() {
+            let date = today - Duration::days(offset);
+            let water_amount = water.amount_on(date)? as f32;
+            let kcal = food.totals_on(date)?.kcal as f32;
+            self.past.push((date, water_amount, kcal));
+        }
+        self.loaded = Some((self.range, today, data_version()));
+        Ok(())
+    }
+
+    fn series(&self, food: &FoodWidget, water: &WaterWidget) -> [Series; 2] {
+        let today = current_day();
+
+        let mut water_days: Vec<(NaiveDate, f32)> = self.past.iter().map(|(date, water, _)| (*date, *water)).collect();
+        water_days.push((today, water.today_amount() as f32));
+
+        let mut kcal_days: Vec<(NaiveDate, f32)> = self.past.iter().map(|(date, _, kcal)| (*date, *kcal)).collect();
+        kcal_days.push((today, food.today_totals().kcal as f32));
+
+        [
+            Series {
+                title: "💧 Water",
+                unit: "ml",
+                goal: get_daily_water_goal() as f32,
+                kind: GoalKind::AtLeast,
+                color: Color32::from_rgb(90, 150, 220),
+                days: water_days,
+            },
+            Series {
+                title: "🍽 Calories",
+                unit: "kcal",
+                goal: get_daily_calorie_goal() as f32,
+                kind: GoalKind::AtMost,
+                color: parse_color_from_ini("button-color"),
+                days: kcal_days,
+            },
+        ]
+    }
+
+    pub fn render_inline(&mut self, ui: &mut Ui, food: &FoodWidget, water: &WaterWidget) {
+        self.render_contents(ui, food, water);
+    }
+
+    // В узком окне графикам не хватает места внутри рамки
+    pub fn render_window(&mut self, ctx: &egui::Context, food: &FoodWidget, water: &WaterWidget) {
+        if !self.open {
+            return;
+        }
+
+        let bg_color = parse_color_from_ini("background-color");
+        let text_color = parse_color_from_ini("text-color");
+        let popup_width = (ctx.screen_rect().width() * 0.9).min(420.0).max(260.0);
+
+        let mut open = self.open;
+        Window::new("📊 Health history")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(popup_width)
+            .frame(Frame::window(&ctx.style()).fill(bg_color))
+            .show(ctx, |ui| {
+                ui.style_mut().visuals.override_text_color = Some(text_color);
+                egui::ScrollArea::vertical()
+                    .id_source("health_history_scroll")
+                    .max_height(480.0)
+                    .show(ui, |ui| self.render_contents(ui, food, water));
+            });
+        self.open = open;
+    }
+
+    fn render_contents(&mut self, ui: &mut Ui, food: &FoodWidget, water: &WaterWidget) {
+        ui.horizontal(|ui| {
+            for range in HistoryRange::ALL {
+                ui.selectable_value(&mut self.range, range, range.label());
+            }
+        });
+        ui.add_space(5.0);
+
+        if let Err(e) = self.load(food, water) {
+            ui.label(egui::RichText::new(format!("History unavailable: {}", e)).color(Color32::from_rgb(200, 60, 60)));
+            return;
+        }
+
+        for series in self.series(food, water) {
+            render_series(ui, &series);
+            ui.add_space(10.0);
+        }
+    }
+}
+
+fn render_series(ui: &mut Ui, series: &Series) {
+    let streak = series.current_streak();
+    let best = series.best_streak();
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(series.title).strong());
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(
+                egui::RichText::new(format!("🔥 {} · best {}", streak, best))
+                    .size(11.0)
+                    .color(Color32::GRAY),
+            )
+            .on_hover_text("Days in a row with the goal met");
+        });
+    });
+
+    render_chart(ui, series);
+
+    let averages = series.weekly_averages();
+    if averages.is_empty() {
+        return;
+    }
+    egui::CollapsingHeader::new(egui::RichText::new("Weekly averages").size(11.0))
+        .id_source(("weekly_averages", series.title))
+        .show(ui, |ui| {
+            for (week_start, average) in averages.iter().rev() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("Week of {}", week_start.format("%d.%m"))).size(11.0));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(
+                            egui::RichText::new(format!("{:.0} / {:.0} {}", average, series.goal, series.unit))
+                                .size(11.0),
+                        );
+                    });
+                });
+            }
+        });
+}
+
+fn render_chart(ui: &mut Ui, series: &Series) {
+    if series.days.is_empty() {
+        return;
+    }
+
+    let width = ui.available_width();
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(width, CHART_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let plot = Rect::from_min_max(
+        Pos2::new(rect.left() + 2.0, rect.top() + 4.0),
+        Pos2::new(rect.right() - 2.0, rect.bottom() - DATE_LABEL_HEIGHT),
+    );
+
+    let max_value = series
+        .days
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(series.goal * 1.15, f32::max)
+        .max(1.0);
+    let y_of = |value: f32| plot.bottom() - plot.height() * (value / max_value);
+
+    let grid = Stroke::new(1.0, parse_color_from_ini("frame-border-color").linear_multiply(0.5));
+    painter.line_segment([plot.left_bottom(), plot.right_bottom()], grid);
+
+    let step = plot.width() / series.days.len() as f32;
+    let gap = if step > 4.0 { (step * 0.2).min(4.0) } else { 0.0 };
+    let over_color = Color32::from_rgb(200, 60, 60);
+
+    for (index, (_, value)) in series.days.iter().enumerate() {
+        if *value <= 0.0 {
+            continue;
+        }
+        let left = plot.left() + step * index as f32 + gap / 2.0;
+        let bar = Rect::from_min_max(Pos2::new(left, y_of(*value)), Pos2::new(left + step - gap, plot.bottom()));
+        let color = if series.met(*value) {
+            series.color
+        } else if series.kind == GoalKind::AtMost {
+            over_color
+        } else {
+            series.color.linear_multiply(0.45)
+        };
+        painter.rect_filled(bar, 1.5, color);
+    }
+
+    // Линия цели пунктиром
+    if series.goal > 0.0 {
+        let y = y_of(series.goal);
+        painter.extend(Shape::dashed_line(
+            &[Pos2::new(plot.left(), y), Pos2::new(plot.right(), y)],
+            Stroke::new(1.0, ui.visuals().text_color().linear_multiply(0.6)),
+            4.0,
+            3.0,
+        ));
+    }
+
+    // Подписи дат не чаще, чем раз в 45 пикселей, последняя — всегда сегодня
+    let font = egui::FontId::proportional(10.0);
+    let label_every = ((45.0 / step).ceil() as usize).max(1);
+    let last = series.days.len() - 1;
+    for (index, (date, _)) in series.days.iter().enumerate() {
+        if (last - index) % label_every != 0 {
+            continue;
+        }
+        painter.text(
+            Pos2::new(plot.left() + step * (index as f32 + 0.5), rect.bottom()),
+            egui::Align2::CENTER_BOTTOM,
+            date.format("%d.%m").to_string(),
+            font.clone(),
+            Color32::GRAY,
+        );
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let index = (((pointer.x - plot.left()) / step).floor().max(0.0) as usize).min(last);
+        let (date, value) = series.days[index];
+        let column = Rect::from_min_max(
+            Pos2::new(plot.left() + step * index as f32, plot.top()),
+            Pos2::new(plot.left() + step * (index + 1) as f32, plot.bottom()),
+        );
+        painter.rect_stroke(column, 1.5, grid);
+
+        let mark = if series.met(value) { " ✔" } else { "" };
+        response.on_hover_text_at_pointer(format!(
+            "{}\n{:.0} / {:.0} {}{}",
+            date.format("%a %d.%m"),
+            value,
+            series.goal,
+            series.unit,
+            mark
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Дни подряд, начиная с понедельника 1 января 2024 года
+    fn series(kind: GoalKind, values: &[f32]) -> Series {
+        let first = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        Series {
+            title: "Water",
+            unit: "ml",
+            goal: 2000.0,
+            kind,
+            color: Color32::WHITE,
+            days: values
+                .iter()
+                .enumerate()
+                .map(|(offset, value)| (first + Duration::days(offset as i64), *value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn unfinished_today_keeps_the_streak() {
+        let water = series(GoalKind::AtLeast, &[2000.0, 2500.0, 2100.0, 500.0]);
+        assert_eq!(water.current_streak(), 3);
+
+        let water = series(GoalKind::AtLeast, &[2000.0, 2500.0, 2100.0, 2000.0]);
+        assert_eq!(water.current_streak(), 4);
+    }
+
+    #[test]
+    fn missed_yesterday_breaks_the_streak() {
+        let water = series(GoalKind::AtLeast, &[2000.0, 2500.0, 0.0, 500.0]);
+        assert_eq!(water.current_streak(), 0);
+
+        let water = series(GoalKind::AtLeast, &[2000.0, 2500.0, 0.0, 2200.0]);
+        assert_eq!(water.current_streak(), 1);
+        assert_eq!(water.best_streak(), 2);
+    }
+
+    #[test]
+    fn empty_days_do_not_meet_an_upper_limit() {
+        let calories = series(GoalKind::AtMost, &[1800.0, 0.0, 1900.0, 1950.0, 2500.0]);
+        assert_eq!(calories.best_streak(), 2);
+        assert_eq!(calories.current_streak(), 2);
+    }
+
+    #[test]
+    fn weekly_averages_skip_missing_days_and_weeks() {
+        let mut values = vec![1000.0, 0.0, 2000.0, 0.0, 0.0, 0.0, 0.0];
+        values.extend([0.0; 7]);
+        values.extend([0.0, 3000.0]);
+        let water = series(GoalKind::AtLeast, &values);
+
+        let first = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(
+            water.weekly_averages(),
+            vec![(first, 1500.0), (first + Duration::days(14), 3000.0)]
+        );
+    }
+}
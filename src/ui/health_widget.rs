@@ -1,17 +1,28 @@
 use crate::ui::color_parser::parse_color_from_ini;
-use crate::ui::settings::{get_daily_water_goal, get_water_increment, get_daily_calorie_goal};
-use bincode;
+use crate::ui::data_store::{DataStore, Result};
+use crate::ui::food_diary::{current_day, until_next_day, DailyTotals, FoodDiary, FoodEntry, FoodItem, MIGRATED_ENTRY_NAME};
+use crate::ui::health_export::data_version;
+use crate::ui::health_history::HealthHistory;
+use crate::ui::notifications_listener::NotificationSender;
+use crate::ui::recipes_popup::RecipesPopup;
+use crate::ui::weight_tracker::WeightWidget;
+use crate::ui::widget_registry::{SidebarWidget, WidgetLayout};
+use crate::ui::widget_settings::HealthSettings;
+use crate::ui::settings::{
+    get_daily_calorie_goal, get_daily_carbs_goal, get_daily_fat_goal, get_daily_protein_goal,
+    get_beverages, get_daily_water_goal, get_water_increment, get_water_presets, get_water_reminder_enabled,
+    get_water_reminder_hours, get_water_reminder_interval_minutes,
+};
 use calory_fetch::{fetch_calory_of_certain_food, fetch_data};
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, Timelike};
 use egui::{Frame, TextEdit, Ui, Vec2, Window};
-use sled::{Db, Result};
-use std::env;
-use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 
 
 const GRAMS_PER_100G: i32 = 100;
-const NARROW_WINDOW_THRESHOLD: f32 = 600.0; // Порог для переключения на вертикальную верстку
+const QUICK_ADD_LIMIT: usize = 8;
+pub const NARROW_WINDOW_THRESHOLD: f32 = 600.0; // Порог для переключения на вертикальную верстку
 
 
 #[derive(Default)]
@@ -19,10 +30,19 @@ pub struct FoodWidget {
     query: String,
     dish_name: String,
     results: Vec<(String, String)>,
-    dish_calory: i32,
+    local_results: Vec<FoodItem>,
+    online_searched: bool,
+    dish_calory: String, // ккал на 100 г, строкой для ручного ввода
+    dish_macros: [String; 3], // Белки, жиры, углеводы на 100 г; пусто — неизвестно
     selected_food: Option<(String, String)>,
-    db: Option<Db>,
-    calory: i32,
+    diary: Option<FoodDiary>,
+    date: String,
+    entries: Vec<FoodEntry>,
+    editing: Option<FoodEntry>, // None — во всплывающем окне новая запись
+    popup_favorite: bool,
+    quick_add: Vec<FoodItem>,
+    recipes_popup: RecipesPopup,
+    data_version: u64,
     pub calory_popup: bool,
     food_amount: String,
     runtime: Option<Runtime>,
@@ -36,49 +56,202 @@ impl FoodWidget {
         }
     }
 
-    fn get_db_path() -> PathBuf {
-        let home = env::var("HOME").expect("Could not determine HOME directory");
-        PathBuf::from(home).join(".local/share/SideBarFoodDb")
+    fn ensure_db(&mut self) -> Result<()> {
+        if self.diary.is_none() {
+            self.diary = Some(FoodDiary::open()?);
+        }
+
+        // После полуночи или импорта перечитываем записи
+        let today = FoodDiary::today_key();
+        if self.date != today || self.data_version != data_version() {
+            self.date = today;
+            self.data_version = data_version();
+            self.reload_entries()?;
+        }
+        Ok(())
     }
 
-    fn ensure_db(&mut self) -> Result<()> {
-        if self.db.is_none() {
-            let db = sled::open(Self::get_db_path())?;
-            self.db = Some(db);
-            self.init_today_record()?;
+    fn reload_entries(&mut self) -> Result<()> {
+        if let Some(diary) = &self.diary {
+            self.entries = diary.load_day(&self.date)?;
+            self.quick_add = quick_add_items(diary);
         }
         Ok(())
     }
 
+    pub fn today_totals(&self) -> DailyTotals {
+        DailyTotals::from_entries(&self.entries)
+    }
+
+    pub fn totals_on(&self, date: NaiveDate) -> Result<DailyTotals> {
+        match &self.diary {
+            Some(diary) => diary.day_totals(date),
+            None => Ok(DailyTotals::default()),
+        }
+    }
+
+    fn update_local_results(&mut self) {
+        self.local_results = match &self.diary {
+            Some(diary) => diary.search_foods(&self.query),
+            None => Vec::new(),
+        };
+    }
+
+    fn search_online(&mut self) {
+        if let Some(rt) = &self.runtime {
+            let results = rt.block_on(fetch_data(&self.query));
+            self.results = results
+                .into_iter()
+                .map(|item| (item.value, item.url))
+                .collect();
+            self.online_searched = true;
+        }
+    }
+
     fn get_date() -> String {
-        Local::now().format("%d.%m").to_string()
+        current_day().format("%d.%m").to_string()
     }
 
-    fn init_today_record(&mut self) -> Result<()> {
-        if let Some(db) = &self.db {
-            let date = Self::get_date();
-            
-            match db.get(date.as_bytes())? {
-                Some(stored_value) => {
-                    self.calory = bincode::deserialize(&stored_value)
-                        .unwrap_or(0);
-                }
-                None => {
-                    self.calory = 0;
-                    db.insert(date.as_bytes(), bincode::serialize(&self.calory).unwrap())?;
-                    db.flush()?;
-                }
+    fn open_popup(
+        &mut self,
+        name: String,
+        kcal_per_100g: i32,
+        macros_per_100g: [Option<f32>; 3],
+        grams: u32,
+        editing: Option<FoodEntry>,
+    ) {
+        self.dish_name = name;
+        self.dish_calory = kcal_per_100g.to_string();
+        self.dish_macros = macros_per_100g.map(|value| value.map(format_grams).unwrap_or_default());
+        self.food_amount = grams.to_string();
+        self.editing = editing;
+        self.popup_favorite = self
+            .diary
+            .as_ref()
+            .and_then(|diary| diary.get_food(&self.dish_name).ok().flatten())
+            .is_some_and(|item| item.favorite);
+        self.calory_popup = true;
+    }
+
+    fn open_item_popup(&mut self, item: &FoodItem) {
+        let grams = if item.last_grams > 0 { item.last_grams } else { GRAMS_PER_100G as u32 };
+        self.open_popup(item.name.clone(), item.kcal_per_100g, item.macros(), grams, None);
+    }
+
+    fn quick_add_item(&mut self, item: &FoodItem) -> Result<()> {
+        let grams = if item.last_grams > 0 { item.last_grams } else { GRAMS_PER_100G as u32 };
+        if let Some(diary) = &self.diary {
+            diary.add(&self.date, item.to_entry(grams))?;
+            diary.remember_food(FoodItem {
+                last_used: Local::now().timestamp(),
+                last_grams: grams,
+                ..item.clone()
+            })?;
+        }
+        self.reload_entries()
+    }
+
+    fn toggle_favorite(&mut self, item: &FoodItem) -> Result<()> {
+        if let Some(diary) = &self.diary {
+            diary.set_favorite(&item.name, !item.favorite)?;
+        }
+        self.update_local_results();
+        self.reload_entries()
+    }
+
+    fn open_edit_popup(&mut self, entry: FoodEntry) {
+        // У перенесённых итогов дня нет граммовки: считаем их порцией в 100 г
+        let (factor, grams) = if entry.grams > 0 {
+            (GRAMS_PER_100G as f32 / entry.grams as f32, entry.grams)
+        } else {
+            (1.0, GRAMS_PER_100G as u32)
+        };
+        let per_100g = (entry.kcal as f32 * factor).round() as i32;
+        let macros = [entry.protein, entry.fat, entry.carbs].map(|value| value.map(|value| value * factor));
+        self.open_popup(entry.name.clone(), per_100g, macros, grams, Some(entry));
+    }
+
+    fn close_popup(&mut self) {
+        self.calory_popup = false;
+        self.editing = None;
+        self.food_amount.clear();
+    }
+
+    fn save_popup_entry(&mut self, grams: u32, kcal: i32) -> Result<()> {
+        let Some(diary) = &self.diary else {
+            return Ok(());
+        };
+
+        // Макронутриенты на порцию из значений на 100 г
+        let [protein, fat, carbs] = self
+            .dish_macros
+            .clone()
+            .map(|value| parse_grams(&value).map(|per_100g| per_100g * grams as f32 / GRAMS_PER_100G as f32));
+
+        // Каждый сохранённый продукт попадает в локальную базу для офлайн-поиска
+        let name = self.dish_name.trim().to_string();
+        if grams > 0 && name != MIGRATED_ENTRY_NAME {
+            let per_100g = self.dish_calory.trim().parse::<i32>().unwrap_or(0);
+            let [protein_100g, fat_100g, carbs_100g] = self.dish_macros.clone().map(|value| parse_grams(&value));
+            diary.remember_food(FoodItem {
+                name: name.clone(),
+                kcal_per_100g: per_100g,
+                protein: protein_100g,
+                fat: fat_100g,
+                carbs: carbs_100g,
+                favorite: false,
+                last_used: Local::now().timestamp(),
+                last_grams: grams,
+            })?;
+            diary.set_favorite(&name, self.popup_favorite)?;
+        }
+
+        match self.editing.take() {
+            Some(mut entry) => {
+                entry.name = self.dish_name.clone();
+                entry.grams = grams;
+                entry.kcal = kcal;
+                entry.protein = protein;
+                entry.fat = fat;
+                entry.carbs = carbs;
+                diary.update(&self.date, entry)?;
+            }
+            None => {
+                diary.add(
+                    &self.date,
+                    FoodEntry {
+                        id: 0,
+                        time: Local::now().format("%H:%M").to_string(),
+                        name: self.dish_name.clone(),
+                        grams,
+                        kcal,
+                        protein,
+                        fat,
+                        carbs,
+                    },
+                )?;
             }
         }
-        Ok(())
+
+        // Сохранённый продукт должен сразу появиться в локальном поиске
+        self.update_local_results();
+        self.reload_entries()
+    }
+
+    fn delete_entry(&mut self, id: u64) -> Result<()> {
+        if let Some(diary) = &self.diary {
+            diary.remove(&self.date, id)?;
+        }
+        self.reload_entries()
     }
 
-    fn update_calories(&mut self, additional: i32) -> Result<()> {
-        if let Some(db) = &self.db {
-            let date = Self::get_date();
-            self.calory += additional;
-            db.insert(date.as_bytes(), bincode::serialize(&self.calory).unwrap())?;
-            db.flush()?;
+    pub fn render_recipes_popup(&mut self, ctx: &egui::Context) -> Result<()> {
+        let logged = match &self.diary {
+            Some(diary) => self.recipes_popup.render(ctx, diary, &self.date, &self.entries),
+            None => false,
+        };
+        if logged {
+            self.reload_entries()?;
         }
         Ok(())
     }
@@ -94,9 +267,11 @@ impl FoodWidget {
 
         let screen_size = ctx.screen_rect().size();
         let popup_width = (screen_size.x * 0.9).min(350.0).max(280.0);
-        let popup_height = 300.0;
+        let popup_height = 360.0;
+
+        let title = if self.editing.is_some() { "Edit Food" } else { "Add Food" };
 
-        Window::new("Add Food")
+        Window::new(title)
             .title_bar(true)
             .collapsible(false)
             .resizable(false)
@@ -108,11 +283,28 @@ impl FoodWidget {
                 ui.vertical_centered(|ui| {
                     ui.add_space(10.0);
                     
-                    ui.heading(&self.dish_name);
+                    ui.add(
+                        TextEdit::singleline(&mut self.dish_name)
+                            .hint_text("Dish name...")
+                            .desired_width(ui.available_width() * 0.8)
+                    );
+                    ui.add_space(5.0);
+                    
+                    ui.label("kcal per 100g:");
+                    ui.add(
+                        TextEdit::singleline(&mut self.dish_calory)
+                            .desired_width(ui.available_width() * 0.8)
+                    );
                     ui.add_space(5.0);
                     
-                    ui.label(format!("{} kcal per 100g", self.dish_calory));
-                    ui.add_space(15.0);
+                    ui.label("Protein / fat / carbs per 100g:");
+                    ui.horizontal(|ui| {
+                        let field_width = (ui.available_width() - 2.0 * ui.spacing().item_spacing.x) / 3.0;
+                        for (value, hint) in self.dish_macros.iter_mut().zip(["P", "F", "C"]) {
+                            ui.add(TextEdit::singleline(value).hint_text(hint).desired_width(field_width));
+                        }
+                    });
+                    ui.add_space(5.0);
                     
                     ui.label("Amount (grams):");
                     ui.add(
@@ -120,25 +312,31 @@ impl FoodWidget {
                             .hint_text("Enter amount...")
                             .desired_width(ui.available_width() * 0.8)
                     );
-                    ui.add_space(10.0);
+                    ui.add_space(5.0);
+                    
+                    ui.checkbox(&mut self.popup_favorite, "★ Favorite");
+                    ui.add_space(5.0);
 
-                    if let Ok(amount) = self.food_amount.parse::<i32>() {
-                        let total_calories = (amount * self.dish_calory) / GRAMS_PER_100G;
+                    let amount = self.food_amount.trim().parse::<u32>();
+                    let per_100g = self.dish_calory.trim().parse::<i32>();
+
+                    if let (Ok(amount), Ok(per_100g)) = (amount, per_100g) {
+                        let total_calories = (amount as i32 * per_100g) / GRAMS_PER_100G;
                         
                         ui.label(format!("Total: {} kcal", total_calories));
                         ui.add_space(15.0);
 
                         let button_width = (ui.available_width() * 0.45).min(120.0);
+                        let confirm = if self.editing.is_some() { "✓ Save" } else { "✓ Add" };
                         
                         ui.horizontal(|ui| {
                             if ui.add(
-                                egui::Button::new("✓ Add")
+                                egui::Button::new(confirm)
                                     .min_size(Vec2::new(button_width, 35.0))
                                     .fill(button_color)
-                            ).clicked() {
-                                let _ = self.update_calories(total_calories);
-                                self.calory_popup = false;
-                                self.food_amount.clear();
+                            ).clicked() && !self.dish_name.trim().is_empty() {
+                                let _ = self.save_popup_entry(amount, total_calories);
+                                self.close_popup();
                             }
 
                             if ui.add(
@@ -146,8 +344,7 @@ impl FoodWidget {
                                     .min_size(Vec2::new(button_width, 35.0))
                                     .fill(button_color.linear_multiply(0.7))
                             ).clicked() {
-                                self.calory_popup = false;
-                                self.food_amount.clear();
+                                self.close_popup();
                             }
                         });
                     } else {
@@ -164,50 +361,42 @@ impl FoodWidget {
 
         if is_narrow {
             ui.vertical(|ui| {
-                ui.add(
+                if ui.add(
                     TextEdit::singleline(&mut self.query)
                         .hint_text("Search food...")
                         .desired_width(ui.available_width())
-                );
+                ).changed() {
+                    self.update_local_results();
+                }
                 
                 ui.add_space(5.0);
 
                 if ui.add(
-                    egui::Button::new("🔍 Search")
+                    egui::Button::new("🔍 Search online")
                         .min_size(Vec2::new(ui.available_width(), 25.0))
                         .fill(button_color)
                 ).clicked() {
-                    if let Some(rt) = &self.runtime {
-                        let results = rt.block_on(fetch_data(&self.query));
-                        self.results = results
-                            .into_iter()
-                            .map(|item| (item.value, item.url))
-                            .collect();
-                    }
+                    self.search_online();
                 }
             });
         } else {
             ui.horizontal(|ui| {
                 let search_width = (available_width * 0.65).max(150.0);
                 
-                ui.add(
+                if ui.add(
                     TextEdit::singleline(&mut self.query)
                         .hint_text("Search food...")
                         .desired_width(search_width)
-                );
+                ).changed() {
+                    self.update_local_results();
+                }
 
                 if ui.add(
-                    egui::Button::new("🔍 Search")
+                    egui::Button::new("🔍 Online")
                         .min_size(Vec2::new(80.0, 25.0))
                         .fill(button_color)
                 ).clicked() {
-                    if let Some(rt) = &self.runtime {
-                        let results = rt.block_on(fetch_data(&self.query));
-                        self.results = results
-                            .into_iter()
-                            .map(|item| (item.value, item.url))
-                            .collect();
-                    }
+                    self.search_online();
                 }
             });
         }
@@ -232,9 +421,9 @@ impl FoodWidget {
                         if let Some(rt) = &self.runtime {
                             let calory_data = rt.block_on(fetch_calory_of_certain_food(url));
                             
-                            self.dish_name = calory_data.get(1).cloned().unwrap_or_default();
-                            self.dish_calory = calory_data
-                                .get(0)
+                            let dish_name = calory_data.get(1).cloned().unwrap_or_default();
+                            let dish_calory = calory_data
+                                .first()
                                 .and_then(|s| s.chars()
                                     .filter(|c| c.is_ascii_digit())
                                     .collect::<String>()
@@ -242,16 +431,167 @@ impl FoodWidget {
                                     .ok())
                                 .unwrap_or(0);
                             
-                            self.food_amount = String::from("100");
-                            self.calory_popup = true;
+                            // calory_fetch отдаёт только калории и название, БЖУ можно ввести в окне
+                            let macros = [None; 3];
+                            
+                            if let Some(diary) = &self.diary {
+                                let _ = diary.remember_food(FoodItem {
+                                    name: dish_name.clone(),
+                                    kcal_per_100g: dish_calory,
+                                    protein: macros[0],
+                                    fat: macros[1],
+                                    carbs: macros[2],
+                                    favorite: false,
+                                    last_used: 0,
+                                    last_grams: 0,
+                                });
+                            }
+                            self.update_local_results();
+                            
+                            self.open_popup(dish_name, dish_calory, macros, GRAMS_PER_100G as u32, None);
+                        }
+                    }
+                }
+            });
+    }
+
+    fn render_local_results(&mut self, ui: &mut Ui) {
+        let button_color = parse_color_from_ini("button-color");
+        let mut picked = None;
+        let mut forget = None;
+
+        egui::ScrollArea::vertical()
+            .id_source("food_local_results")
+            .max_height(100.0)
+            .show(ui, |ui| {
+                for item in &self.local_results {
+                    let star = if item.favorite { "★ " } else { "" };
+                    let text = format!("{}{} · {} kcal/100g", star, item.name, item.kcal_per_100g);
+                    let response = ui.add(
+                        egui::Button::new(text)
+                            .min_size(Vec2::new(ui.available_width(), 25.0))
+                            .fill(button_color.linear_multiply(0.6))
+                            .wrap(true)
+                    );
+                    if response.clicked() {
+                        picked = Some(item.clone());
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("🗑 Remove from local database").clicked() {
+                            forget = Some(item.name.clone());
+                            ui.close_menu();
                         }
+                    });
+                }
+            });
+
+        if let Some(item) = picked {
+            self.open_item_popup(&item);
+        }
+        if let Some(name) = forget {
+            if let Some(diary) = &self.diary {
+                let _ = diary.remove_food(&name);
+            }
+            self.update_local_results();
+            let _ = self.reload_entries();
+        }
+    }
+
+    // Клик добавляет последнюю порцию, правый клик — меню
+    fn render_quick_add(&mut self, ui: &mut Ui) {
+        if self.quick_add.is_empty() {
+            return;
+        }
+
+        let button_color = parse_color_from_ini("button-color");
+        let mut add = None;
+        let mut edit = None;
+        let mut toggle = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for item in &self.quick_add {
+                let icon = if item.favorite { "★" } else { "🕘" };
+                let grams = if item.last_grams > 0 { item.last_grams } else { GRAMS_PER_100G as u32 };
+                let response = ui
+                    .add(egui::Button::new(format!("{} {}", icon, item.name)).fill(button_color.linear_multiply(0.5)))
+                    .on_hover_text(format!("Add {} g", grams));
+
+                if response.clicked() {
+                    add = Some(item.clone());
+                }
+                response.context_menu(|ui| {
+                    if ui.button("Edit amount…").clicked() {
+                        edit = Some(item.clone());
+                        ui.close_menu();
                     }
+                    let label = if item.favorite { "☆ Remove from favorites" } else { "★ Add to favorites" };
+                    if ui.button(label).clicked() {
+                        toggle = Some(item.clone());
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if let Some(item) = add {
+            let _ = self.quick_add_item(&item);
+        }
+        if let Some(item) = edit {
+            self.open_item_popup(&item);
+        }
+        if let Some(item) = toggle {
+            let _ = self.toggle_favorite(&item);
+        }
+    }
+
+    fn render_entries(&mut self, ui: &mut Ui) {
+        if self.entries.is_empty() {
+            ui.label(egui::RichText::new("Nothing logged today").size(12.0).color(egui::Color32::GRAY));
+            return;
+        }
+
+        let mut edit = None;
+        let mut delete = None;
+
+        egui::ScrollArea::vertical()
+            .id_source("food_entries")
+            .max_height(140.0)
+            .show(ui, |ui| {
+                for entry in &self.entries {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&entry.time).size(12.0).color(egui::Color32::GRAY));
+                        ui.label(egui::RichText::new(&entry.name).size(12.0));
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                delete = Some(entry.id);
+                            }
+                            if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                                edit = Some(entry.clone());
+                            }
+
+                            let amount = if entry.grams > 0 {
+                                format!("{} g · {} kcal", entry.grams, entry.kcal)
+                            } else {
+                                format!("{} kcal", entry.kcal)
+                            };
+                            ui.label(egui::RichText::new(amount).size(12.0).color(egui::Color32::GRAY));
+                        });
+                    });
                 }
             });
+
+        if let Some(entry) = edit {
+            self.open_edit_popup(entry);
+        }
+        if let Some(id) = delete {
+            let _ = self.delete_entry(id);
+        }
     }
 
     pub fn render(&mut self, ui: &mut Ui) -> Result<()> {
         let _ = self.ensure_db();
+        let totals = DailyTotals::from_entries(&self.entries);
 
         ui.vertical(|ui| {
             ui.heading("🍽 Food Tracker");
@@ -262,30 +602,56 @@ impl FoodWidget {
 
             if is_very_narrow {
                 ui.label(format!("Date: {}", Self::get_date()));
-                ui.label(format!("Cal: {} / {}", self.calory, get_daily_calorie_goal()));
             } else {
                 ui.horizontal(|ui| {
                     ui.label("Date:");
                     ui.label(Self::get_date());
                 });
-
-                ui.horizontal(|ui| {
-                    ui.label("Calories:");
-                    ui.label(format!("{} / {} kcal", self.calory, get_daily_calorie_goal()));
-                });
             }
 
+            ui.add_space(5.0);
+            render_nutrition_bars(ui, &totals);
+
+            ui.add_space(5.0);
+            self.render_entries(ui);
+
             ui.add_space(10.0);
             ui.separator();
             ui.add_space(10.0);
 
+            self.render_quick_add(ui);
+            ui.add_space(5.0);
+
             // Search
             self.render_search(ui);
             ui.add_space(5.0);
 
+            ui.horizontal(|ui| {
+                if ui.small_button("✏ Manual entry").clicked() {
+                    let name = self.query.trim().to_string();
+                    self.open_popup(name, 0, [None; 3], GRAMS_PER_100G as u32, None);
+                }
+                if ui.small_button("📖 Recipes").clicked() {
+                    self.recipes_popup.open = true;
+                }
+            });
+            ui.add_space(5.0);
+
+            // Local results first, they work offline
+            if !self.local_results.is_empty() {
+                self.render_local_results(ui);
+                ui.add_space(5.0);
+            }
+
             // Results
             if !self.results.is_empty() {
                 self.render_results(ui);
+            } else if self.online_searched && self.local_results.is_empty() {
+                ui.label(
+                    egui::RichText::new("Nothing found online. Add it manually to keep it offline.")
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                );
             }
         });
 
@@ -294,68 +660,237 @@ impl FoodWidget {
 }
 
 
-#[derive(Default)]
+// Сначала избранное, затем недавнее без повторов
+fn quick_add_items(diary: &FoodDiary) -> Vec<FoodItem> {
+    let mut items = diary.favorites();
+    for item in diary.recent(QUICK_ADD_LIMIT) {
+        if !items.iter().any(|existing| existing.name == item.name) {
+            items.push(item);
+        }
+    }
+    items.truncate(QUICK_ADD_LIMIT);
+    items
+}
+
+// Граммы с точностью до десятых, без лишнего ".0"
+fn format_grams(value: f32) -> String {
+    let rounded = (value * 10.0).round() / 10.0;
+    if rounded.fract() == 0.0 {
+        format!("{}", rounded as i32)
+    } else {
+        format!("{:.1}", rounded)
+    }
+}
+
+// Допускает "12,5", "12.5 г" и подобные строки
+fn parse_grams(value: &str) -> Option<f32> {
+    let number: String = value
+        .trim()
+        .replace(',', ".")
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.parse().ok()
+}
+
+// Калории и БЖУ — полосы прогресса одна под другой
+fn render_nutrition_bars(ui: &mut Ui, totals: &DailyTotals) {
+    let bars = [
+        ("Calories", totals.kcal as f32, get_daily_calorie_goal() as f32, "kcal", parse_color_from_ini("button-color")),
+        ("Protein", totals.protein, get_daily_protein_goal() as f32, "g", egui::Color32::from_rgb(90, 150, 220)),
+        ("Fat", totals.fat, get_daily_fat_goal() as f32, "g", egui::Color32::from_rgb(230, 180, 60)),
+        ("Carbs", totals.carbs, get_daily_carbs_goal() as f32, "g", egui::Color32::from_rgb(120, 190, 110)),
+    ];
+
+    for (label, value, goal, unit, color) in bars {
+        let progress = if goal > 0.0 { (value / goal).min(1.0) } else { 0.0 };
+        let fill = if goal > 0.0 && value > goal {
+            egui::Color32::from_rgb(200, 60, 60)
+        } else {
+            color
+        };
+
+        ui.add(
+            egui::ProgressBar::new(progress)
+                .fill(fill)
+                .desired_height(14.0)
+                .text(
+                    egui::RichText::new(format!("{}: {} / {} {}", label, format_grams(value), goal as i32, unit))
+                        .size(11.0),
+                ),
+        );
+        ui.add_space(2.0);
+    }
+}
+
+// Одна запись журнала воды; hydration_ml — объём с учётом коэффициента напитка
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterIntake {
+    pub id: u64,
+    pub time: String, // "%H:%M"
+    pub beverage: String,
+    pub ml: u32,
+    pub hydration_ml: u32,
+}
+
 pub struct WaterWidget {
     water_amount: u32,
-    db: Option<Db>,
-    initialized: bool,
+    log: Vec<WaterIntake>,
+    store: Option<DataStore>,
+    date: String,
+    data_version: u64,
+    beverage: String,
+    custom_amount: String,
+    notifier: NotificationSender,
+    last_reminder_check: Option<Instant>,
+    last_reminder: Option<Instant>,
+    reminder_enabled: bool,
 }
 
 impl WaterWidget {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(notifier: NotificationSender) -> Self {
+        Self {
+            water_amount: 0,
+            log: Vec::new(),
+            store: None,
+            date: String::new(),
+            data_version: 0,
+            beverage: "water".to_string(),
+            custom_amount: get_water_increment().to_string(),
+            notifier,
+            last_reminder_check: None,
+            last_reminder: None,
+            reminder_enabled: false,
+        }
     }
 
-    fn get_db_path() -> PathBuf {
-        let home = env::var("HOME").expect("Could not determine HOME directory");
-        PathBuf::from(home).join(".local/share/SideBarWaterDb")
+    fn get_date() -> String {
+        current_day().format("%d.%m").to_string()
     }
 
-    fn get_date() -> String {
-        Local::now().format("%d.%m").to_string()
+    pub fn today_amount(&self) -> u32 {
+        self.water_amount
+    }
+
+    pub fn amount_on(&self, date: NaiveDate) -> Result<u32> {
+        match &self.store {
+            Some(store) => store.water(&FoodDiary::date_key(date)),
+            None => Ok(0),
+        }
     }
 
     fn ensure_db(&mut self) -> Result<()> {
-        if self.db.is_none() {
-            let db = sled::open(Self::get_db_path())?;
-            self.db = Some(db);
+        if self.store.is_none() {
+            self.store = Some(DataStore::open()?);
         }
 
-        if !self.initialized {
-            self.init_today_record()?;
-            self.initialized = true;
+        // После полуночи или импорта перечитываем количество
+        let today = FoodDiary::today_key();
+        if self.date != today || self.data_version != data_version() {
+            self.date = today;
+            self.data_version = data_version();
+            self.reload()?;
         }
 
         Ok(())
     }
 
-    fn init_today_record(&mut self) -> Result<()> {
-        if let Some(db) = &self.db {
-            let date = Self::get_date();
-
-            match db.get(date.as_bytes())? {
-                Some(stored_value) => {
-                    self.water_amount = bincode::deserialize(&stored_value)
-                        .unwrap_or(0);
-                }
-                None => {
-                    self.water_amount = 0;
-                    db.insert(date.as_bytes(), bincode::serialize(&self.water_amount).unwrap())?;
-                    db.flush()?;
-                }
-            }
+    fn reload(&mut self) -> Result<()> {
+        if let Some(store) = &self.store {
+            self.water_amount = store.water(&self.date)?;
+            self.log = store.water_log(&self.date)?;
         }
         Ok(())
     }
 
-    fn update_water(&mut self, new_amount: u32) -> Result<()> {
-        if let Some(db) = &self.db {
-            let date = Self::get_date();
-            self.water_amount = new_amount;
-            db.insert(date.as_bytes(), bincode::serialize(&self.water_amount).unwrap())?;
-            db.flush()?;
+    fn add_intake(&mut self, ml: u32) -> Result<()> {
+        let factor = get_beverages()
+            .into_iter()
+            .find(|(name, _)| *name == self.beverage)
+            .map(|(_, factor)| factor)
+            .unwrap_or(1.0);
+
+        let intake = WaterIntake {
+            id: 0,
+            time: Local::now().format("%H:%M").to_string(),
+            beverage: self.beverage.clone(),
+            ml,
+            hydration_ml: (ml as f32 * factor).round() as u32,
+        };
+
+        if let Some(store) = &self.store {
+            store.add_water_intake(&self.date, &intake)?;
+        }
+        self.reload()
+    }
+
+    // Поправка итога дня без записи в журнал: дни из старых хранилищ журнала не имеют
+    fn subtract(&mut self, ml: u32) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.set_water(&self.date, self.water_amount.saturating_sub(ml))?;
+        }
+        self.reload()
+    }
+
+    fn remove_intake(&mut self, id: u64) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.remove_water_intake(&self.date, id)?;
+        }
+        self.reload()
+    }
+
+    // Напоминает, если выпито заметно меньше, чем положено к этому часу
+    fn check_reminder(&mut self, goal: u32) {
+        // Настройки читаются с диска, поэтому проверяем не чаще раза в минуту
+        if self.last_reminder_check.is_some_and(|at| at.elapsed() < Duration::from_secs(60)) {
+            return;
+        }
+        self.last_reminder_check = Some(Instant::now());
+        self.reminder_enabled = get_water_reminder_enabled();
+
+        if !self.reminder_enabled || self.water_amount >= goal {
+            return;
+        }
+        let interval = Duration::from_secs(get_water_reminder_interval_minutes() * 60);
+        if self.last_reminder.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+
+        let (start, end) = get_water_reminder_hours();
+        let now = Local::now();
+        let hour = now.hour() as f32 + now.minute() as f32 / 60.0;
+        if hour < start as f32 || hour >= end as f32 {
+            return;
+        }
+
+        // Цель набирается равномерно между start и end
+        let expected = goal as f32 * (hour - start as f32) / (end - start) as f32;
+        let behind = expected - self.water_amount as f32;
+        let smallest_portion = get_water_presets().first().map(|(_, ml)| *ml).unwrap_or(250);
+        if behind < smallest_portion as f32 {
+            return;
+        }
+
+        self.notifier.send(
+            "Water",
+            "💧 Time for a drink",
+            &format!(
+                "{} of {} ml so far, about {} ml behind your pace",
+                self.water_amount,
+                goal,
+                behind.round() as u32
+            ),
+        );
+        self.last_reminder = Some(Instant::now());
+    }
+
+    fn tick(&mut self, ctx: &egui::Context) {
+        let _ = self.ensure_db();
+        self.check_reminder(get_daily_water_goal());
+        // Без ввода кадров нет, а напоминание должно прийти и в простаивающую панель
+        if self.reminder_enabled {
+            ctx.request_repaint_after(Duration::from_secs(60));
         }
-        Ok(())
     }
 
     pub fn render(&mut self, ui: &mut Ui) -> Result<()> {
@@ -363,10 +898,8 @@ impl WaterWidget {
         let button_color = parse_color_from_ini("button-color");
         let available_width = ui.available_width();
         let is_very_narrow = available_width < 200.0;
-        
 
         let daily_water_goal = get_daily_water_goal();
-        let water_increment = get_water_increment();
 
         ui.vertical(|ui| {
             ui.heading("💧 Water Tracker");
@@ -389,80 +922,141 @@ impl WaterWidget {
 
             ui.add_space(10.0);
 
-
             let progress = (self.water_amount as f32) / (daily_water_goal as f32);
             ui.add(egui::ProgressBar::new(progress).show_percentage());
 
             ui.add_space(10.0);
 
-            let is_narrow = available_width < 250.0;
-            
-            if is_narrow {
-                if ui.add(
-                    egui::Button::new(format!("+ {} ml", water_increment))
-                        .min_size(Vec2::new(ui.available_width(), 30.0))
-                        .fill(button_color)
-                ).clicked() {
-                    let _ = self.update_water(self.water_amount + water_increment);
-                }
-
-                ui.add_space(5.0);
+            let beverages = get_beverages();
+            ui.horizontal(|ui| {
+                ui.label("Drink:");
+                egui::ComboBox::from_id_source("water_beverage")
+                    .selected_text(capitalize(&self.beverage))
+                    .show_ui(ui, |ui| {
+                        for (name, factor) in &beverages {
+                            let label = if *factor == 1.0 {
+                                capitalize(name)
+                            } else {
+                                format!("{} (×{:.2})", capitalize(name), factor)
+                            };
+                            ui.selectable_value(&mut self.beverage, name.clone(), label);
+                        }
+                    });
+            });
+            ui.add_space(5.0);
 
-                if ui.add(
-                    egui::Button::new(format!("- {} ml", water_increment))
-                        .min_size(Vec2::new(ui.available_width(), 30.0))
-                        .fill(button_color.linear_multiply(0.7))
-                ).clicked() {
-                    let _ = self.update_water(self.water_amount.saturating_sub(water_increment));
-                }
-            } else {
-                ui.horizontal(|ui| {
-                    let button_width = (ui.available_width() * 0.48).min(100.0);
-                    
+            let mut add = None;
+            let mut subtract = None;
+            ui.horizontal_wrapped(|ui| {
+                for (name, ml) in get_water_presets() {
                     if ui.add(
-                        egui::Button::new(format!("+ {} ml", water_increment))
-                            .min_size(Vec2::new(button_width, 30.0))
+                        egui::Button::new(format!("{} {} ml", capitalize(&name), ml))
+                            .min_size(Vec2::new(0.0, 30.0))
                             .fill(button_color)
                     ).clicked() {
-                        let _ = self.update_water(self.water_amount + water_increment);
+                        add = Some(ml);
                     }
+                }
+            });
+            ui.add_space(3.0);
 
-                    if ui.add(
-                        egui::Button::new(format!("- {} ml", water_increment))
-                            .min_size(Vec2::new(button_width, 30.0))
-                            .fill(button_color.linear_multiply(0.7))
-                    ).clicked() {
-                        let _ = self.update_water(self.water_amount.saturating_sub(water_increment));
-                    }
-                });
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.custom_amount).desired_width(60.0));
+                ui.label("ml");
+                if ui.add(egui::Button::new("+ Add").fill(button_color.linear_multiply(0.7))).clicked() {
+                    add = self.custom_amount.trim().parse().ok().filter(|ml| *ml > 0);
+                }
+                if ui
+                    .add(egui::Button::new("− ml").fill(button_color.linear_multiply(0.5)))
+                    .on_hover_text("Lower today's total without a log entry")
+                    .clicked()
+                {
+                    subtract = self.custom_amount.trim().parse().ok().filter(|ml| *ml > 0);
+                }
+            });
+
+            if let Some(ml) = add {
+                let _ = self.add_intake(ml);
             }
+            if let Some(ml) = subtract {
+                let _ = self.subtract(ml);
+            }
+
+            ui.add_space(5.0);
+            self.render_log(ui);
         });
 
         Ok(())
     }
+
+    fn render_log(&mut self, ui: &mut Ui) {
+        if self.log.is_empty() {
+            return;
+        }
+
+        let mut remove = None;
+        egui::CollapsingHeader::new(format!("Today's log ({})", self.log.len()))
+            .id_source("water_log")
+            .show(ui, |ui| {
+                for intake in &self.log {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&intake.time).size(11.0).color(egui::Color32::GRAY));
+                        ui.label(format!("{} {} ml", capitalize(&intake.beverage), intake.ml));
+                        if intake.hydration_ml != intake.ml {
+                            ui.label(
+                                egui::RichText::new(format!("→ {} ml", intake.hydration_ml))
+                                    .size(11.0)
+                                    .color(egui::Color32::GRAY),
+                            );
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("✕").on_hover_text("Remove").clicked() {
+                                remove = Some(intake.id);
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(id) = remove {
+            let _ = self.remove_intake(id);
+        }
+    }
+}
+
+// Имена из settings.ini приходят в нижнем регистре
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 pub struct HealthWidget {
     pub food_widget: FoodWidget,
     pub water_widget: WaterWidget,
+    pub weight_widget: WeightWidget,
+    history: HealthHistory,
+    history_in_window: bool,
+    settings: Option<HealthSettings>,
 }
 
-impl Default for HealthWidget {
-    fn default() -> Self {
+impl HealthWidget {
+    pub fn new(notifier: NotificationSender) -> Self {
         Self {
             food_widget: FoodWidget::new(),
-            water_widget: WaterWidget::new(),
+            water_widget: WaterWidget::new(notifier),
+            weight_widget: WeightWidget::new(),
+            history: HealthHistory::default(),
+            history_in_window: false,
+            settings: None,
         }
     }
-}
-
-impl HealthWidget {
-    pub fn new() -> Self {
-        Self::default()
-    }
 
     pub fn render(&mut self, ui: &mut Ui, ctx: &egui::Context) {
-        self.food_widget.render_popup(ctx);
+        // Без ввода egui не перерисовывается, а счётчики должны смениться в начале дня
+        ctx.request_repaint_after(until_next_day());
 
         let frame = Frame {
             fill: parse_color_from_ini("frame-background"),
@@ -490,8 +1084,78 @@ impl HealthWidget {
                 
                 let _ = self.water_widget.render(ui);
             }
+
+            ui.add_space(15.0);
+            ui.separator();
+            ui.add_space(15.0);
+
+            if let Err(e) = self.weight_widget.render(ui) {
+                ui.label(egui::RichText::new(format!("Weight unavailable: {}", e)).color(egui::Color32::from_rgb(200, 60, 60)));
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+
+            self.history_in_window = available_width < NARROW_WINDOW_THRESHOLD;
+            let toggle = if self.history.open { "📊 Hide history" } else { "📊 History" };
+            if ui.small_button(toggle).clicked() {
+                self.history.open = !self.history.open;
+            }
+            if self.history.open && !self.history_in_window {
+                ui.add_space(5.0);
+                self.history.render_inline(ui, &self.food_widget, &self.water_widget);
+            }
         });
     }
+
+    pub fn render_popups(&mut self, ctx: &egui::Context) {
+        self.food_widget.render_popup(ctx);
+        let _ = self.food_widget.render_recipes_popup(ctx);
+
+        if self.history_in_window {
+            self.history.render_window(ctx, &self.food_widget, &self.water_widget);
+        }
+    }
+}
+
+impl SidebarWidget for HealthWidget {
+    fn id(&self) -> &'static str {
+        "health"
+    }
+
+    fn title(&self) -> &'static str {
+        "Health"
+    }
+
+    fn render(&mut self, ui: &mut Ui, ctx: &egui::Context) {
+        HealthWidget::render(self, ui, ctx);
+    }
+
+    fn render_popups(&mut self, ctx: &egui::Context) {
+        HealthWidget::render_popups(self, ctx);
+    }
+
+    fn tick(&mut self, ctx: &egui::Context) {
+        if WidgetLayout::current().is_enabled(self.id()) {
+            self.water_widget.tick(ctx);
+        }
+    }
+
+    fn has_settings(&self) -> bool {
+        true
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) {
+        self.settings.get_or_insert_with(HealthSettings::load).ui(ui);
+    }
+
+    // Пустая дата заставляет трекеры перечитать день из базы
+    fn refresh(&mut self) {
+        self.food_widget.date.clear();
+        self.water_widget.date.clear();
+        self.weight_widget.refresh();
+        self.history.refresh();
+    }
 }
 
 
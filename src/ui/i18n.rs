@@ -0,0 +1,140 @@
+use crate::ui::settings::get_language_setting;
+use chrono::Weekday;
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    English,
+    Russian,
+    Ukrainian,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::Russian, Language::Ukrainian];
+
+    pub fn from_code(code: &str) -> Self {
+        match code.trim().to_lowercase().as_str() {
+            "ru" => Language::Russian,
+            "uk" | "ua" => Language::Ukrainian,
+            _ => Language::English,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+            Language::Ukrainian => "uk",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+            Language::Ukrainian => "Українська",
+        }
+    }
+}
+
+// Язык читается из settings.ini один раз, до смены в настройках
+static LANGUAGE_CACHE: Lazy<RwLock<Option<Language>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn current_language() -> Language {
+    if let Ok(cache) = LANGUAGE_CACHE.read() {
+        if let Some(language) = *cache {
+            return language;
+        }
+    }
+
+    let language = Language::from_code(&get_language_setting());
+
+    if let Ok(mut cache) = LANGUAGE_CACHE.write() {
+        *cache = Some(language);
+    }
+
+    language
+}
+
+pub fn invalidate_language_cache() {
+    if let Ok(mut cache) = LANGUAGE_CACHE.write() {
+        *cache = None;
+    }
+}
+
+// Английский текст служит ключом: (en, ru, uk)
+const TRANSLATIONS: &[(&str, &str, &str)] = &[
+    // Погода
+    ("Weather", "Погода", "Погода"),
+    ("Loading weather...", "Загрузка погоды...", "Завантаження погоди..."),
+    ("Weather unavailable", "Погода недоступна", "Погода недоступна"),
+    ("Updated", "Обновлено", "Оновлено"),
+    ("Offline, data from", "Нет сети, данные", "Немає мережі, дані"),
+    ("just now", "только что", "щойно"),
+    ("min ago", "мин назад", "хв тому"),
+    ("h ago", "ч назад", "год тому"),
+    ("d ago", "дн назад", "дн тому"),
+    ("from", "с", "з"),
+    ("until", "до", "до"),
+    ("Hourly", "По часам", "Погодинно"),
+    ("Precipitation", "Осадки", "Опади"),
+    // Описания погоды
+    ("clear sky", "ясно", "ясно"),
+    ("few clouds", "малооблачно", "малохмарно"),
+    ("scattered clouds", "переменная облачность", "мінлива хмарність"),
+    ("broken clouds", "облачно с прояснениями", "хмарно з проясненнями"),
+    ("overcast clouds", "пасмурно", "похмуро"),
+    ("light rain", "небольшой дождь", "невеликий дощ"),
+    ("shower rain", "ливень", "злива"),
+    ("rain", "дождь", "дощ"),
+    ("thunderstorm", "гроза", "гроза"),
+    ("snow", "снег", "сніг"),
+    ("mist", "дымка", "серпанок"),
+    ("fog", "туман", "туман"),
+    ("smoke", "дым", "дим"),
+    ("dust", "пыль", "пил"),
+    ("drizzle", "морось", "мряка"),
+    ("freezing rain", "ледяной дождь", "крижаний дощ"),
+    ("sleet", "мокрый снег", "мокрий сніг"),
+    ("hail", "град", "град"),
+    ("tornado", "смерч", "смерч"),
+    ("unknown", "неизвестно", "невідомо"),
+    // Единицы
+    ("m/s", "м/с", "м/с"),
+    ("km/h", "км/ч", "км/год"),
+    ("mph", "миль/ч", "миль/год"),
+    ("hPa", "гПа", "гПа"),
+    ("mmHg", "мм рт. ст.", "мм рт. ст."),
+    ("inHg", "дюйм рт. ст.", "дюйм рт. ст."),
+];
+
+// Перевод строки на текущий язык; без перевода возвращается исходный текст
+pub fn tr(text: &str) -> String {
+    translate(text, current_language())
+}
+
+pub fn translate(text: &str, language: Language) -> String {
+    if language == Language::English {
+        return text.to_string();
+    }
+
+    TRANSLATIONS
+        .iter()
+        .find(|(en, _, _)| en.eq_ignore_ascii_case(text))
+        .map(|(_, ru, uk)| match language {
+            Language::Russian => *ru,
+            _ => *uk,
+        })
+        .unwrap_or(text)
+        .to_string()
+}
+
+pub fn weekday_short(weekday: Weekday) -> &'static str {
+    let index = weekday.num_days_from_monday() as usize;
+    match current_language() {
+        Language::English => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][index],
+        Language::Russian => ["Пн", "Вт", "Ср", "Чт", "Пт", "Сб", "Вс"][index],
+        Language::Ukrainian => ["Пн", "Вт", "Ср", "Чт", "Пт", "Сб", "Нд"][index],
+    }
+}
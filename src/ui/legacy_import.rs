@@ -0,0 +1,308 @@
+use crate::ui::data_store::{config_dir, DataStore};
+use crate::ui::food_diary::{
+    FoodDiary, FoodEntry, FoodItem, MealTemplate, Recipe, FOODS_COLLECTION, MEALS_COLLECTION,
+    MIGRATED_ENTRY_NAME, RECIPES_COLLECTION,
+};
+use crate::ui::notifications_listener::Notification;
+use crate::ui::pomodoro_widget::PomodoroRecord;
+use crate::ui::widgets::weather_cache::{CachedForecast, WEATHER_CACHE_KEY};
+use crate::ui::widgets::weather_locations::{LocationStore, LOCATIONS_KEY};
+use crate::ui::widgets::weather_plugin::WEATHER_COLLECTION;
+use chrono::{Datelike, Local, NaiveDate};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+// Хранилища до перехода на единую базу. Файлы не удаляются,
+// а в legacy_imports отмечается, что источник уже перенесён
+type Importer = fn(&DataStore, &Path) -> Result<(), Box<dyn Error>>;
+
+pub fn import_legacy_stores(store: &DataStore) {
+    let data_dir = dirs::home_dir().unwrap_or_default().join(".local/share");
+    import_from_dirs(store, &data_dir, &config_dir());
+}
+
+// data_dir — где лежали базы sled и трекера, config_dir — JSON-файлы рядом с настройками
+fn import_from_dirs(store: &DataStore, data_dir: &Path, config_dir: &Path) {
+    let sources: [(&str, PathBuf, Importer); 7] = [
+        ("food_db", data_dir.join("SideBarFoodDb"), import_food),
+        ("water_db", data_dir.join("SideBarWaterDb"), import_water),
+        ("pomodoro_db", data_dir.join("SideBarPomodoroDb"), import_pomodoro),
+        ("activity_db", data_dir.join("SideBarActivity.db"), import_activity),
+        ("notifications_json", config_dir.join("notifications.json"), import_notifications),
+        ("weather_cache_json", config_dir.join("weather_cache.json"), import_weather_cache),
+        ("weather_locations_json", config_dir.join("weather_locations.json"), import_weather_locations),
+    ];
+
+    for (source, path, import) in sources {
+        if store.legacy_imported(source).unwrap_or(true) {
+            continue;
+        }
+
+        // Данные и отметка фиксируются вместе: после сбоя повтор начнётся с чистого листа
+        let result = store.in_transaction(|store| -> Result<(), Box<dyn Error>> {
+            if path.exists() {
+                import(store, &path)?;
+            }
+            store.mark_legacy_imported(source)?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => {
+                if path.exists() {
+                    println!("Imported legacy {} from {}", source, path.display());
+                }
+            }
+            // Не отмечаем, чтобы повторить при следующем запуске
+            Err(e) => eprintln!("Failed to import legacy {}: {}", source, e),
+        }
+    }
+}
+
+// Старые ключи "%d.%m" без года относим к последней прошедшей такой дате
+pub fn legacy_key_to_date(key: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (day, month) = key.split_once('.')?;
+    let day: u32 = day.parse().ok()?;
+    let month: u32 = month.parse().ok()?;
+
+    // 29.02 ищем в ближайшем високосном году
+    (0..8)
+        .filter_map(|years_back| NaiveDate::from_ymd_opt(today.year() - years_back, month, day))
+        .find(|date| *date <= today)
+}
+
+fn sled_values<T: DeserializeOwned>(tree: &sled::Tree) -> Vec<(String, T)> {
+    tree.iter()
+        .filter_map(|item| item.ok())
+        .filter_map(|(key, value)| {
+            let key = String::from_utf8(key.to_vec()).ok()?;
+            let value = bincode::deserialize(&value).ok()?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn import_food(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let db = sled::open(path)?;
+    let tree = |name: &str| db.open_tree(name);
+    let today = Local::now().date_naive();
+
+    let mut days: Vec<(String, Vec<FoodEntry>)> = sled_values(&tree("entries")?);
+
+    // Самый старый формат: одно число калорий на день в корневом дереве
+    for (key, kcal) in sled_values::<i32>(&db) {
+        let Some(date) = legacy_key_to_date(&key, today) else {
+            continue;
+        };
+        if kcal == 0 {
+            continue;
+        }
+        let entry = FoodEntry {
+            id: 0,
+            time: "00:00".to_string(),
+            name: MIGRATED_ENTRY_NAME.to_string(),
+            grams: 0,
+            kcal,
+            protein: None,
+            fat: None,
+            carbs: None,
+        };
+        let date = FoodDiary::date_key(date);
+        match days.iter_mut().find(|(existing, _)| *existing == date) {
+            Some((_, entries)) => entries.push(entry),
+            None => days.push((date, vec![entry])),
+        }
+    }
+
+    for (date, entries) in days {
+        store.replace_food_day(&date, &entries)?;
+    }
+
+    for (key, item) in sled_values::<FoodItem>(&tree("foods")?) {
+        store.save_document(FOODS_COLLECTION, &key, &item)?;
+    }
+    for (key, recipe) in sled_values::<Recipe>(&tree("recipes")?) {
+        store.save_document(RECIPES_COLLECTION, &key, &recipe)?;
+    }
+    for (key, meal) in sled_values::<MealTemplate>(&tree("meals")?) {
+        store.save_document(MEALS_COLLECTION, &key, &meal)?;
+    }
+    Ok(())
+}
+
+fn import_water(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let db = sled::open(path)?;
+    let today = Local::now().date_naive();
+
+    for (key, ml) in sled_values::<u32>(&db) {
+        if let Some(date) = legacy_key_to_date(&key, today) {
+            store.set_water(&FoodDiary::date_key(date), ml)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_pomodoro(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let db = sled::open(path)?;
+
+    for (date, records) in sled_values::<Vec<PomodoroRecord>>(&db) {
+        for record in records {
+            store.add_pomodoro(&date, &record)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_activity(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    Ok(store.import_spans(path)?)
+}
+
+fn import_notifications(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let notifications: Vec<Notification> = read_json(path)?;
+    Ok(store.replace_notifications(&notifications)?)
+}
+
+fn import_weather_cache(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let cached: CachedForecast = read_json(path)?;
+    Ok(store.save_document(WEATHER_COLLECTION, WEATHER_CACHE_KEY, &cached)?)
+}
+
+fn import_weather_locations(store: &DataStore, path: &Path) -> Result<(), Box<dyn Error>> {
+    let locations: LocationStore = read_json(path)?;
+    Ok(store.save_document(WEATHER_COLLECTION, LOCATIONS_KEY, &locations)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn legacy_keys_map_to_the_latest_past_date() {
+        let today = date(2024, 5, 1);
+        assert_eq!(legacy_key_to_date("01.05", today), Some(today));
+        assert_eq!(legacy_key_to_date("30.04", today), Some(date(2024, 4, 30)));
+        // Дата позже сегодняшней — это прошлый год
+        assert_eq!(legacy_key_to_date("02.05", today), Some(date(2023, 5, 2)));
+        assert_eq!(legacy_key_to_date("15.12", today), Some(date(2023, 12, 15)));
+    }
+
+    #[test]
+    fn legacy_keys_cross_the_year_boundary() {
+        assert_eq!(legacy_key_to_date("31.12", date(2025, 1, 2)), Some(date(2024, 12, 31)));
+        assert_eq!(legacy_key_to_date("01.01", date(2025, 1, 1)), Some(date(2025, 1, 1)));
+        assert_eq!(legacy_key_to_date("01.01", date(2024, 12, 31)), Some(date(2024, 1, 1)));
+    }
+
+    #[test]
+    fn february_29_goes_to_the_last_leap_year() {
+        assert_eq!(legacy_key_to_date("29.02", date(2024, 2, 29)), Some(date(2024, 2, 29)));
+        assert_eq!(legacy_key_to_date("29.02", date(2025, 3, 1)), Some(date(2024, 2, 29)));
+        assert_eq!(legacy_key_to_date("29.02", date(2024, 2, 28)), Some(date(2020, 2, 29)));
+    }
+
+    #[test]
+    fn malformed_legacy_keys_are_skipped() {
+        let today = date(2024, 5, 1);
+        for key in ["", "abc", "1.5.2024", "32.01", "31.04", "00.05", "05"] {
+            assert_eq!(legacy_key_to_date(key, today), None, "key {:?}", key);
+        }
+    }
+
+    // Старые sled-базы хранили значения в bincode
+    fn write_sled<T: serde::Serialize>(path: &Path, values: &[(&str, T)]) {
+        let db = sled::open(path).unwrap();
+        for (key, value) in values {
+            db.insert(key.as_bytes(), bincode::serialize(value).unwrap()).unwrap();
+        }
+        db.flush().unwrap();
+    }
+
+    fn fixture_dirs(name: &str) -> (DataStore, PathBuf, PathBuf) {
+        let root = crate::ui::test_support::temp_dir(name);
+        let data_dir = root.join("share");
+        let config_dir = root.join("config");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let store = DataStore::open_at(&root.join("sidebar.db")).unwrap();
+        (store, data_dir, config_dir)
+    }
+
+    #[test]
+    fn sled_stores_are_imported_once() {
+        let (store, data_dir, config_dir) = fixture_dirs("import-sled");
+        let record = PomodoroRecord {
+            finished_at: "10:25".to_string(),
+            minutes: 25,
+            task: Some("Отчёт".to_string()),
+        };
+        write_sled(&data_dir.join("SideBarPomodoroDb"), &[("2024-05-01", vec![record])]);
+        write_sled(&data_dir.join("SideBarWaterDb"), &[("01.01", 1200u32)]);
+        write_sled(&data_dir.join("SideBarFoodDb"), &[("01.01", 1800i32)]);
+
+        import_from_dirs(&store, &data_dir, &config_dir);
+        import_from_dirs(&store, &data_dir, &config_dir);
+
+        let pomodoros = store.pomodoro_log("2024-05-01").unwrap();
+        assert_eq!(pomodoros.len(), 1);
+        assert_eq!(pomodoros[0].minutes, 25);
+        assert_eq!(pomodoros[0].task.as_deref(), Some("Отчёт"));
+
+        let new_year = legacy_key_to_date("01.01", Local::now().date_naive()).unwrap();
+        let date = FoodDiary::date_key(new_year);
+        assert_eq!(store.water(&date).unwrap(), 1200);
+        let food = store.food_entries(&date).unwrap();
+        assert_eq!(food.len(), 1);
+        assert_eq!(food[0].kcal, 1800);
+        assert_eq!(food[0].name, MIGRATED_ENTRY_NAME);
+
+        // Отсутствующие файлы тоже отмечаются, чтобы их не искать при каждом запуске
+        assert!(store.legacy_imported("pomodoro_db").unwrap());
+        assert!(store.legacy_imported("activity_db").unwrap());
+    }
+
+    #[test]
+    fn json_notifications_are_imported() {
+        let (store, data_dir, config_dir) = fixture_dirs("import-json");
+        std::fs::write(
+            config_dir.join("notifications.json"),
+            r#"[{"app_name":"mail","summary":"Новое письмо","body":"","timestamp":"10:00","id":7}]"#,
+        )
+        .unwrap();
+
+        import_from_dirs(&store, &data_dir, &config_dir);
+
+        let notifications = store.notifications().unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].id, 7);
+        assert_eq!(notifications[0].summary, "Новое письмо");
+    }
+
+    #[test]
+    fn failed_import_is_rolled_back_and_retried() {
+        let (store, data_dir, config_dir) = fixture_dirs("import-failed");
+        // Журнал трекера без таблицы spans: чтение падает
+        rusqlite::Connection::open(data_dir.join("SideBarActivity.db"))
+            .unwrap()
+            .execute_batch("CREATE TABLE other (id INTEGER);")
+            .unwrap();
+        std::fs::write(config_dir.join("notifications.json"), "не json").unwrap();
+
+        import_from_dirs(&store, &data_dir, &config_dir);
+        assert!(!store.legacy_imported("activity_db").unwrap());
+        assert!(!store.legacy_imported("notifications_json").unwrap());
+        assert!(store.notifications().unwrap().is_empty());
+
+        std::fs::write(config_dir.join("notifications.json"), "[]").unwrap();
+        import_from_dirs(&store, &data_dir, &config_dir);
+        assert!(store.legacy_imported("notifications_json").unwrap());
+    }
+}
@@ -1,13 +1,30 @@
 pub mod sidebar;
 pub mod widgets;
 
+pub mod checkin_widget;
 pub mod color_parser;
+pub mod data_store;
+pub mod legacy_import;
+pub mod i18n;
+pub mod food_diary;
 pub mod health_widget;
+pub mod health_export;
+pub mod health_history;
+pub mod recipes_popup;
+pub mod weight_tracker;
 pub mod reminders_manager;
 pub mod settings;
 pub mod task_manager;
 pub mod weather_widget;
+pub mod widget_registry;
+pub mod widget_settings;
 pub mod custom_vidgets;
 pub mod notifications_listener;
+pub mod pomodoro_widget;
 pub mod aw_qt;
-pub mod activitywatch_reader;
\ No newline at end of file
+pub mod activitywatch_reader;
+pub mod activity_tracker;
+pub mod screen_time;
+#[cfg(test)]
+pub mod test_support;
+pub mod script_widget;
@@ -1,8 +1,7 @@
 use std::sync::{Arc, Mutex};
 use std::process::{Command, Stdio};
-use std::io::{BufRead, BufReader, Write};
-use std::fs::File;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader};
+use crate::ui::data_store::DataStore;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -14,94 +13,108 @@ pub struct Notification {
     pub id: u64, // Уникальный ID для удаления
 }
 
+// Позволяет виджетам добавлять собственные уведомления в список SideBar
+#[derive(Clone)]
+pub struct NotificationSender {
+    notifications: Arc<Mutex<Vec<Notification>>>,
+    store: Option<DataStore>,
+    next_id: Arc<Mutex<u64>>,
+    ctx: egui::Context,
+}
+
+impl NotificationSender {
+    pub fn send(&self, app_name: &str, summary: &str, body: &str) {
+        NotificationsListener::push_notification(
+            &self.notifications,
+            &self.store,
+            &self.next_id,
+            app_name,
+            summary,
+            body,
+        );
+
+        self.ctx.request_repaint();
+    }
+}
+
 pub struct NotificationsListener {
     notifications: Arc<Mutex<Vec<Notification>>>,
-    storage_path: PathBuf,
+    store: Option<DataStore>,
     next_id: Arc<Mutex<u64>>,
 }
 
 impl NotificationsListener {
     pub fn new() -> Self {
-        let storage_path = Self::get_storage_path();
-        let (notifications, max_id) = Self::load_from_file(&storage_path);
+        let store = match DataStore::open() {
+            Ok(store) => Some(store),
+            Err(e) => {
+                eprintln!("Failed to open data store, notifications won't be saved: {}", e);
+                None
+            }
+        };
+        let (notifications, max_id) = Self::load(&store);
         
         Self {
             notifications: Arc::new(Mutex::new(notifications)),
-            storage_path,
+            store,
             next_id: Arc::new(Mutex::new(max_id + 1)),
         }
     }
 
-    fn get_storage_path() -> PathBuf {
-        let mut path = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("/tmp"));
-        path.push("sidebar");
-        std::fs::create_dir_all(&path).ok();
-        path.push("notifications.json");
-        path
-    }
-
-    fn load_from_file(path: &PathBuf) -> (Vec<Notification>, u64) {
-        match std::fs::read_to_string(path) {
-            Ok(content) => {
-                match serde_json::from_str::<Vec<Notification>>(&content) {
-                    Ok(notifs) => {
-                        let max_id = notifs.iter().map(|n| n.id).max().unwrap_or(0);
-                        println!("Loaded {} notifications from file", notifs.len());
-                        (notifs, max_id)
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to parse notifications: {}", e);
-                        (Vec::new(), 0)
-                    }
-                }
+    fn load(store: &Option<DataStore>) -> (Vec<Notification>, u64) {
+        let Some(store) = store else {
+            return (Vec::new(), 0);
+        };
+        match store.notifications() {
+            Ok(notifs) => {
+                let max_id = notifs.iter().map(|n| n.id).max().unwrap_or(0);
+                println!("Loaded {} notifications", notifs.len());
+                (notifs, max_id)
             }
-            Err(_) => {
-                println!("No existing notifications file, starting fresh");
+            Err(e) => {
+                eprintln!("Failed to load notifications: {}", e);
                 (Vec::new(), 0)
             }
         }
     }
 
-    fn save_to_file(&self) {
-        if let Ok(notifs) = self.notifications.lock() {
-            match serde_json::to_string_pretty(&*notifs) {
-                Ok(json) => {
-                    if let Ok(mut file) = File::create(&self.storage_path) {
-                        let _ = file.write_all(json.as_bytes());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to serialize notifications: {}", e);
-                }
-            }
-        }
+    fn save(&self) {
+        Self::save_notifications(&self.notifications, &self.store);
     }
 
     pub fn get_notifications(&self) -> Arc<Mutex<Vec<Notification>>> {
         Arc::clone(&self.notifications)
     }
 
+    pub fn sender(&self, ctx: &egui::Context) -> NotificationSender {
+        NotificationSender {
+            notifications: Arc::clone(&self.notifications),
+            store: self.store.clone(),
+            next_id: Arc::clone(&self.next_id),
+            ctx: ctx.clone(),
+        }
+    }
+
     pub fn start_listening(&self, ctx: egui::Context) {
         let notifications = Arc::clone(&self.notifications);
-        let storage_path = self.storage_path.clone();
+        let store = self.store.clone();
         let next_id = Arc::clone(&self.next_id);
         
         std::thread::spawn(move || {
-            Self::listen_loop(notifications, ctx, storage_path, next_id);
+            Self::listen_loop(notifications, ctx, store, next_id);
         });
     }
 
     fn listen_loop(
         notifications: Arc<Mutex<Vec<Notification>>>,
         ctx: egui::Context,
-        storage_path: PathBuf,
+        store: Option<DataStore>,
         next_id: Arc<Mutex<u64>>,
     ) {
         println!("Starting notification listener...");
         
         loop {
-            match Self::run_dbus_monitor(&notifications, &ctx, &storage_path, &next_id) {
+            match Self::run_dbus_monitor(&notifications, &ctx, &store, &next_id) {
                 Ok(_) => {
                     eprintln!("dbus-monitor exited unexpectedly, restarting...");
                 }
@@ -116,7 +129,7 @@ impl NotificationsListener {
     fn run_dbus_monitor(
         notifications: &Arc<Mutex<Vec<Notification>>>,
         ctx: &egui::Context,
-        storage_path: &PathBuf,
+        store: &Option<DataStore>,
         next_id: &Arc<Mutex<u64>>,
     ) -> Result<(), String> {
         let mut child = Command::new("dbus-monitor")
@@ -197,30 +210,14 @@ impl NotificationsListener {
                     println!("  Body: '{}'", body);
                     println!("===========================\n");
                     
-                    let id = {
-                        let mut id_lock = next_id.lock().unwrap();
-                        let current_id = *id_lock;
-                        *id_lock += 1;
-                        current_id
-                    };
-                    
-                    let notification = Notification {
-                        app_name: app_name.clone(),
-                        summary: summary.clone(),
-                        body: body.clone(),
-                        timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-                        id,
-                    };
-                    
-                    if let Ok(mut notifs) = notifications.lock() {
-                        notifs.push(notification);
-                        if notifs.len() > 100 {
-                            notifs.remove(0);
-                        }
-                        
-                        drop(notifs);
-                        Self::save_notifications_to_file(notifications, storage_path);
-                    }
+                    Self::push_notification(
+                        notifications,
+                        store,
+                        next_id,
+                        &app_name,
+                        &summary,
+                        &body,
+                    );
                     
                     ctx.request_repaint();
                     in_method_call = false;
@@ -233,20 +230,50 @@ impl NotificationsListener {
         Ok(())
     }
 
-    fn save_notifications_to_file(
+    fn push_notification(
+        notifications: &Arc<Mutex<Vec<Notification>>>,
+        store: &Option<DataStore>,
+        next_id: &Arc<Mutex<u64>>,
+        app_name: &str,
+        summary: &str,
+        body: &str,
+    ) {
+        let id = {
+            let mut id_lock = next_id.lock().unwrap();
+            let current_id = *id_lock;
+            *id_lock += 1;
+            current_id
+        };
+        
+        let notification = Notification {
+            app_name: app_name.to_string(),
+            summary: summary.to_string(),
+            body: body.to_string(),
+            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+            id,
+        };
+        
+        if let Ok(mut notifs) = notifications.lock() {
+            notifs.push(notification);
+            if notifs.len() > 100 {
+                notifs.remove(0);
+            }
+            
+            drop(notifs);
+            Self::save_notifications(notifications, store);
+        }
+    }
+
+    fn save_notifications(
         notifications: &Arc<Mutex<Vec<Notification>>>,
-        storage_path: &PathBuf,
+        store: &Option<DataStore>,
     ) {
+        let Some(store) = store else {
+            return;
+        };
         if let Ok(notifs) = notifications.lock() {
-            match serde_json::to_string_pretty(&*notifs) {
-                Ok(json) => {
-                    if let Ok(mut file) = File::create(storage_path) {
-                        let _ = file.write_all(json.as_bytes());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to serialize notifications: {}", e);
-                }
+            if let Err(e) = store.replace_notifications(&notifs) {
+                eprintln!("Failed to save notifications: {}", e);
             }
         }
     }
@@ -255,14 +282,14 @@ impl NotificationsListener {
         if let Ok(mut notifs) = self.notifications.lock() {
             notifs.retain(|n| n.id != id);
         }
-        self.save_to_file();
+        self.save();
     }
 
     pub fn clear_all(&self) {
         if let Ok(mut notifs) = self.notifications.lock() {
             notifs.clear();
         }
-        self.save_to_file();
+        self.save();
     }
 
     pub fn get_count(&self) -> usize {
@@ -0,0 +1,478 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::data_store::{DataStore, Result};
+use crate::ui::settings::{
+    get_pomodoro_auto_dnd, get_pomodoro_long_break_minutes, get_pomodoro_sessions,
+    get_pomodoro_short_break_minutes, get_pomodoro_work_minutes,
+};
+use crate::ui::widgets::todo_widget::{get_active_task, Task};
+use crate::ui::widget_registry::SidebarWidget;
+use crate::ui::widget_settings::PomodoroSettings;
+use chrono::Local;
+use egui::{Align2, FontId, Frame, Pos2, Sense, Shape, Stroke, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const RING_SIZE: f32 = 140.0;
+const RING_WIDTH: f32 = 8.0;
+const RING_SEGMENTS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PomodoroPhase {
+    #[default]
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Work => "Focus",
+            Self::ShortBreak => "Short break",
+            Self::LongBreak => "Long break",
+        }
+    }
+
+    fn minutes(&self) -> u32 {
+        match self {
+            Self::Work => get_pomodoro_work_minutes(),
+            Self::ShortBreak => get_pomodoro_short_break_minutes(),
+            Self::LongBreak => get_pomodoro_long_break_minutes(),
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs(self.minutes() as u64 * 60)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PomodoroRecord {
+    pub finished_at: String,
+    pub minutes: u32,
+    pub task: Option<String>,
+}
+
+#[derive(Default)]
+pub struct PomodoroWidget {
+    phase: PomodoroPhase,
+    running: bool,
+    started_at: Option<Instant>,
+    // Время, накопленное до последней паузы
+    elapsed_before_pause: Duration,
+    completed_sessions: u32,
+    bind_to_task: bool,
+    bound_task: Option<Task>,
+    today_log: Vec<PomodoroRecord>,
+    store: Option<DataStore>,
+    dnd_active: bool,
+    settings: Option<PomodoroSettings>,
+}
+
+impl PomodoroWidget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_date() -> String {
+        Local::now().format("%Y-%m-%d").to_string()
+    }
+
+    fn ensure_db(&mut self) -> Result<()> {
+        if self.store.is_none() {
+            self.store = Some(DataStore::open()?);
+            self.load_today_log()?;
+        }
+        Ok(())
+    }
+
+    fn load_today_log(&mut self) -> Result<()> {
+        if let Some(store) = &self.store {
+            self.today_log = store.pomodoro_log(&Self::get_date())?;
+        }
+        Ok(())
+    }
+
+    fn log_pomodoro(&mut self) -> Result<()> {
+        let record = PomodoroRecord {
+            finished_at: Local::now().format("%H:%M").to_string(),
+            minutes: PomodoroPhase::Work.minutes(),
+            task: self.bound_task.as_ref().map(|task| task.description.clone()),
+        };
+
+        if let Some(store) = &self.store {
+            store.add_pomodoro(&Self::get_date(), &record)?;
+            // Перечитываем лог, чтобы не потерять записи при смене дня
+            self.today_log = store.pomodoro_log(&Self::get_date())?;
+        }
+        Ok(())
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed_before_pause + self.started_at.map(|s| s.elapsed()).unwrap_or_default()
+    }
+
+    fn remaining(&self) -> Duration {
+        self.phase.duration().saturating_sub(self.elapsed())
+    }
+
+    fn progress(&self) -> f32 {
+        let total = self.phase.duration().as_secs_f32();
+        if total > 0.0 {
+            (self.elapsed().as_secs_f32() / total).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    fn start(&mut self) {
+        if self.phase == PomodoroPhase::Work && self.bind_to_task && self.bound_task.is_none() {
+            self.bound_task = get_active_task();
+        }
+        self.started_at = Some(Instant::now());
+        self.running = true;
+        self.sync_dnd();
+    }
+
+    fn pause(&mut self) {
+        self.elapsed_before_pause = self.elapsed();
+        self.started_at = None;
+        self.running = false;
+        self.sync_dnd();
+    }
+
+    fn reset(&mut self) {
+        self.switch_phase(PomodoroPhase::Work, false);
+        self.completed_sessions = 0;
+    }
+
+    fn next_phase(&self) -> PomodoroPhase {
+        match self.phase {
+            PomodoroPhase::Work => {
+                let sessions = get_pomodoro_sessions().max(1);
+                if self.completed_sessions > 0 && self.completed_sessions % sessions == 0 {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                }
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Work,
+        }
+    }
+
+    fn switch_phase(&mut self, phase: PomodoroPhase, running: bool) {
+        self.phase = phase;
+        self.elapsed_before_pause = Duration::ZERO;
+        self.started_at = None;
+        self.running = false;
+        if phase == PomodoroPhase::Work {
+            self.bound_task = None;
+        }
+
+        if running {
+            self.start();
+        } else {
+            self.sync_dnd();
+        }
+    }
+
+    fn finish_phase(&mut self) {
+        let finished = self.phase;
+        if finished == PomodoroPhase::Work {
+            self.completed_sessions += 1;
+            let _ = self.log_pomodoro();
+        }
+
+        let next = self.next_phase();
+        let body = format!("{} finished, {} for {} min", finished.title(), next.title().to_lowercase(), next.minutes());
+
+        // Уведомление о начале работы шлём до включения DND, о перерыве — после выключения
+        if next == PomodoroPhase::Work {
+            send_notification("🍅 Back to work", &body);
+            self.switch_phase(next, true);
+        } else {
+            self.switch_phase(next, true);
+            send_notification("☕ Time for a break", &body);
+        }
+    }
+
+    fn skip_phase(&mut self) {
+        let next = self.next_phase();
+        let running = self.running;
+        self.switch_phase(next, running);
+    }
+
+    fn sync_dnd(&mut self) {
+        let wanted = self.running && self.phase == PomodoroPhase::Work && get_pomodoro_auto_dnd();
+
+        if wanted != self.dnd_active {
+            set_dnd(wanted);
+            self.dnd_active = wanted;
+        }
+    }
+
+    fn tick(&mut self, ctx: &egui::Context) {
+        if !self.running {
+            return;
+        }
+        let _ = self.ensure_db();
+
+        if self.remaining().is_zero() {
+            self.finish_phase();
+        }
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+
+    pub fn render(&mut self, ui: &mut Ui) {
+        let _ = self.ensure_db();
+
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("🍅 Pomodoro");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} / {}",
+                            self.completed_sessions % get_pomodoro_sessions().max(1),
+                            get_pomodoro_sessions()
+                        ))
+                        .size(12.0)
+                        .color(egui::Color32::GRAY),
+                    );
+                });
+            });
+            ui.add_space(5.0);
+
+            ui.vertical_centered(|ui| {
+                self.render_ring(ui);
+                ui.add_space(5.0);
+                ui.label(self.phase.title());
+            });
+
+            ui.add_space(10.0);
+            self.render_controls(ui);
+            ui.add_space(5.0);
+            self.render_task_binding(ui);
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(5.0);
+            self.render_today_log(ui);
+        });
+    }
+
+    fn render_ring(&self, ui: &mut Ui) {
+        let size = ui.available_width().min(RING_SIZE);
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(size), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let center = rect.center();
+        let radius = size / 2.0 - RING_WIDTH;
+
+        painter.circle_stroke(
+            center,
+            radius,
+            Stroke::new(RING_WIDTH, parse_color_from_ini("button-color").linear_multiply(0.6)),
+        );
+
+        let progress = self.progress();
+        if progress > 0.0 {
+            let points: Vec<Pos2> = (0..=RING_SEGMENTS)
+                .map(|i| {
+                    let angle = -FRAC_PI_2 + TAU * progress * i as f32 / RING_SEGMENTS as f32;
+                    center + radius * Vec2::angled(angle)
+                })
+                .collect();
+            painter.add(Shape::line(
+                points,
+                Stroke::new(RING_WIDTH, parse_color_from_ini("frame-border-color")),
+            ));
+        }
+
+        let remaining = self.remaining().as_secs();
+        painter.text(
+            center,
+            Align2::CENTER_CENTER,
+            format!("{:02}:{:02}", remaining / 60, remaining % 60),
+            FontId::proportional(22.0),
+            ui.visuals().text_color(),
+        );
+    }
+
+    fn render_controls(&mut self, ui: &mut Ui) {
+        let button_color = parse_color_from_ini("button-color");
+
+        ui.horizontal(|ui| {
+            let button_width = ((ui.available_width() - 10.0) / 3.0).min(100.0);
+            let start_label = if self.running { "⏸ Pause" } else { "▶ Start" };
+
+            if ui.add(
+                egui::Button::new(start_label)
+                    .min_size(Vec2::new(button_width, 30.0))
+                    .fill(button_color)
+            ).clicked() {
+                if self.running {
+                    self.pause();
+                } else {
+                    self.start();
+                }
+            }
+
+            if ui.add(
+                egui::Button::new("⏭ Skip")
+                    .min_size(Vec2::new(button_width, 30.0))
+                    .fill(button_color.linear_multiply(0.7))
+            ).on_hover_text("Skip to the next phase").clicked() {
+                self.skip_phase();
+            }
+
+            if ui.add(
+                egui::Button::new("⟲ Reset")
+                    .min_size(Vec2::new(button_width, 30.0))
+                    .fill(button_color.linear_multiply(0.7))
+            ).clicked() {
+                self.reset();
+            }
+        });
+    }
+
+    fn render_task_binding(&mut self, ui: &mut Ui) {
+        if ui
+            .checkbox(&mut self.bind_to_task, "Bind to started task")
+            .on_hover_text("Attach focus sessions to the task started with `task <id> start`")
+            .changed()
+        {
+            self.bound_task = if self.bind_to_task && self.running {
+                get_active_task()
+            } else {
+                None
+            };
+        }
+
+        if self.bind_to_task {
+            match &self.bound_task {
+                Some(task) => {
+                    ui.label(
+                        egui::RichText::new(format!("📌 {}", task.description))
+                            .size(12.0),
+                    )
+                    .on_hover_text(&task.project);
+                }
+                None => {
+                    ui.label(
+                        egui::RichText::new("No started task")
+                            .size(12.0)
+                            .color(egui::Color32::GRAY),
+                    );
+                }
+            }
+        }
+    }
+
+    fn render_today_log(&self, ui: &mut Ui) {
+        let total_minutes: u32 = self.today_log.iter().map(|r| r.minutes).sum();
+        ui.label(format!(
+            "Today: {} pomodoros ({} min)",
+            self.today_log.len(),
+            total_minutes
+        ));
+
+        if !self.today_log.is_empty() {
+            egui::CollapsingHeader::new("Today's log")
+                .id_source("pomodoro_today_log")
+                .show(ui, |ui| {
+                    for record in self.today_log.iter().rev() {
+                        let task = record.task.as_deref().unwrap_or("—");
+                        ui.label(
+                            egui::RichText::new(format!("{}  {} min  {}", record.finished_at, record.minutes, task))
+                                .size(12.0),
+                        );
+                    }
+                });
+        }
+    }
+}
+
+impl SidebarWidget for PomodoroWidget {
+    fn id(&self) -> &'static str {
+        "pomodoro"
+    }
+
+    fn title(&self) -> &'static str {
+        "Pomodoro"
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        PomodoroWidget::render(self, ui);
+    }
+
+    // Выключенный виджет тоже доводит фазу до конца и снимает DND
+    fn tick(&mut self, ctx: &egui::Context) {
+        PomodoroWidget::tick(self, ctx);
+    }
+
+    fn has_settings(&self) -> bool {
+        true
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) {
+        self.settings.get_or_insert_with(PomodoroSettings::load).ui(ui);
+    }
+
+    fn refresh(&mut self) {
+        let _ = self.load_today_log();
+    }
+}
+
+impl Drop for PomodoroWidget {
+    fn drop(&mut self) {
+        // Не оставляем DND включённым после закрытия SideBar
+        // Здесь ждём команду: поток из set_dnd не успеет отработать до выхода
+        if self.dnd_active {
+            apply_dnd(false);
+        }
+    }
+}
+
+fn send_notification(summary: &str, body: &str) {
+    let _ = Command::new("notify-send")
+        .args(["-u", "normal", "-a", "SideBar", summary, body])
+        .spawn();
+}
+
+// Команды демонов могут подвисать, поэтому не блокируем отрисовку
+fn set_dnd(enabled: bool) {
+    std::thread::spawn(move || {
+        apply_dnd(enabled)
+    });
+}
+
+// Пробуем известные демоны уведомлений по очереди, пока один не ответит
+fn apply_dnd(enabled: bool) {
+    let dunst = Command::new("dunstctl")
+        .args(["set-paused", if enabled { "true" } else { "false" }])
+        .status();
+    if matches!(dunst, Ok(status) if status.success()) {
+        return;
+    }
+
+    let mako = Command::new("makoctl")
+        .args(["mode", if enabled { "-a" } else { "-r" }, "do-not-disturb"])
+        .status();
+    if matches!(mako, Ok(status) if status.success()) {
+        return;
+    }
+
+    let _ = Command::new("swaync-client")
+        .arg(if enabled { "--dnd-on" } else { "--dnd-off" })
+        .status();
+}
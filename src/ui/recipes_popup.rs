@@ -0,0 +1,383 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::data_store::Result;
+use crate::ui::food_diary::{FoodDiary, FoodEntry, Ingredient, MealTemplate, Recipe};
+use egui::{Color32, Frame, TextEdit, Ui, Vec2, Window};
+use std::collections::HashSet;
+
+#[derive(Default, PartialEq, Clone, Copy)]
+enum RecipesTab {
+    #[default]
+    Recipes,
+    Meals,
+}
+
+// Редактируемый рецепт; числа строками, как в остальных полях ввода
+struct RecipeDraft {
+    original_name: Option<String>,
+    name: String,
+    portions: String,
+    ingredients: Vec<(String, String)>,
+}
+
+impl RecipeDraft {
+    fn new() -> Self {
+        Self {
+            original_name: None,
+            name: String::new(),
+            portions: "1".to_string(),
+            ingredients: vec![(String::new(), "100".to_string())],
+        }
+    }
+
+    fn from_recipe(recipe: &Recipe) -> Self {
+        Self {
+            original_name: Some(recipe.name.clone()),
+            name: recipe.name.clone(),
+            portions: recipe.portions.to_string(),
+            ingredients: recipe
+                .ingredients
+                .iter()
+                .map(|ingredient| (ingredient.food.clone(), ingredient.grams.to_string()))
+                .collect(),
+        }
+    }
+
+    fn to_recipe(&self) -> Recipe {
+        Recipe {
+            name: self.name.trim().to_string(),
+            portions: self.portions.trim().parse().unwrap_or(1).max(1),
+            ingredients: self
+                .ingredients
+                .iter()
+                .filter(|(food, _)| !food.trim().is_empty())
+                .filter_map(|(food, grams)| {
+                    grams.trim().parse().ok().map(|grams| Ingredient {
+                        food: food.trim().to_string(),
+                        grams,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RecipesPopup {
+    pub open: bool,
+    tab: RecipesTab,
+    draft: Option<RecipeDraft>,
+    meal_name: String,
+    meal_selection: HashSet<u64>,
+    error: Option<String>,
+}
+
+impl RecipesPopup {
+    // Возвращает true, если в дневник добавлены записи
+    pub fn render(&mut self, ctx: &egui::Context, diary: &FoodDiary, date: &str, today: &[FoodEntry]) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let bg_color = parse_color_from_ini("background-color");
+        let text_color = parse_color_from_ini("text-color");
+        let screen_size = ctx.screen_rect().size();
+        let popup_width = (screen_size.x * 0.9).min(420.0).max(280.0);
+
+        let mut open = self.open;
+        let mut logged = false;
+
+        Window::new("📖 Recipes & Meals")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(popup_width)
+            .frame(Frame::window(&ctx.style()).fill(bg_color))
+            .show(ctx, |ui| {
+                ui.style_mut().visuals.override_text_color = Some(text_color);
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, RecipesTab::Recipes, "Recipes");
+                    ui.selectable_value(&mut self.tab, RecipesTab::Meals, "Meal templates");
+                });
+                ui.separator();
+
+                if let Some(error) = &self.error {
+                    ui.label(egui::RichText::new(error).size(11.0).color(Color32::from_rgb(200, 60, 60)));
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_source("recipes_scroll")
+                    .max_height(420.0)
+                    .show(ui, |ui| {
+                        let result = match self.tab {
+                            RecipesTab::Recipes => self.render_recipes(ui, diary, date),
+                            RecipesTab::Meals => self.render_meals(ui, diary, date, today),
+                        };
+                        match result {
+                            Ok(added) => logged |= added,
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    });
+            });
+
+        self.open = open;
+        logged
+    }
+
+    fn render_recipes(&mut self, ui: &mut Ui, diary: &FoodDiary, date: &str) -> Result<bool> {
+        if self.draft.is_some() {
+            return self.render_recipe_editor(ui, diary).map(|_| false);
+        }
+
+        let button_color = parse_color_from_ini("button-color");
+        let mut logged = false;
+        let mut edit = None;
+        let mut delete = None;
+
+        let recipes = diary.recipes();
+        if recipes.is_empty() {
+            ui.label(egui::RichText::new("No recipes yet").size(12.0).color(Color32::GRAY));
+        }
+
+        for recipe in &recipes {
+            let nutrition = diary.recipe_nutrition(recipe)?;
+            let (grams, per_portion) = nutrition.per_portion(recipe.portions);
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(&recipe.name).strong());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        delete = Some(recipe.name.clone());
+                    }
+                    if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                        edit = Some(recipe.clone());
+                    }
+                    if ui
+                        .add(egui::Button::new("+ 1 portion").fill(button_color))
+                        .clicked()
+                    {
+                        match diary.recipe_portion_entry(recipe, 1.0).and_then(|entry| diary.add(date, entry)) {
+                            Ok(_) => logged = true,
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                });
+            });
+
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} kcal · {} g per portion · P {:.0} / F {:.0} / C {:.0} · {} portions",
+                    per_portion.kcal, grams, per_portion.protein, per_portion.fat, per_portion.carbs, recipe.portions
+                ))
+                .size(11.0)
+                .color(Color32::GRAY),
+            );
+            render_missing(ui, &nutrition.missing);
+            ui.add_space(5.0);
+        }
+
+        if let Some(recipe) = edit {
+            self.draft = Some(RecipeDraft::from_recipe(&recipe));
+        }
+        if let Some(name) = delete {
+            diary.remove_recipe(&name)?;
+        }
+
+        ui.add_space(5.0);
+        if ui
+            .add(egui::Button::new("+ New recipe").min_size(Vec2::new(ui.available_width(), 28.0)).fill(button_color))
+            .clicked()
+        {
+            self.draft = Some(RecipeDraft::new());
+        }
+
+        Ok(logged)
+    }
+
+    fn render_recipe_editor(&mut self, ui: &mut Ui, diary: &FoodDiary) -> Result<()> {
+        let button_color = parse_color_from_ini("button-color");
+        let Some(draft) = &mut self.draft else {
+            return Ok(());
+        };
+
+        ui.add(TextEdit::singleline(&mut draft.name).hint_text("Recipe name...").desired_width(ui.available_width()));
+        ui.horizontal(|ui| {
+            ui.label("Portions:");
+            ui.add(TextEdit::singleline(&mut draft.portions).desired_width(50.0));
+        });
+        ui.add_space(5.0);
+        ui.label("Ingredients (grams):");
+
+        let mut remove = None;
+        for (index, (food, grams)) in draft.ingredients.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let name_width = (ui.available_width() - 110.0).max(100.0);
+                ui.add(TextEdit::singleline(food).hint_text("Food...").desired_width(name_width));
+                ui.add(TextEdit::singleline(grams).desired_width(50.0));
+                if ui.small_button("✕").clicked() {
+                    remove = Some(index);
+                }
+            });
+
+            // Подсказка из локальной базы, если имя не совпадает точно
+            if !food.trim().is_empty() && diary.get_food(food)?.is_none() {
+                if let Some(suggestion) = diary.search_foods(food).into_iter().next() {
+                    if ui.small_button(format!("→ {}", suggestion.name)).clicked() {
+                        *food = suggestion.name;
+                    }
+                }
+            }
+        }
+        if let Some(index) = remove {
+            draft.ingredients.remove(index);
+        }
+
+        if ui.small_button("+ Ingredient").clicked() {
+            draft.ingredients.push((String::new(), "100".to_string()));
+        }
+
+        let recipe = draft.to_recipe();
+        let nutrition = diary.recipe_nutrition(&recipe)?;
+        let (grams, per_portion) = nutrition.per_portion(recipe.portions);
+
+        ui.add_space(5.0);
+        ui.label(format!("Total: {} kcal · {} g", nutrition.total.kcal, nutrition.grams));
+        ui.label(format!(
+            "Per portion: {} kcal · {} g · P {:.0} / F {:.0} / C {:.0}",
+            per_portion.kcal, grams, per_portion.protein, per_portion.fat, per_portion.carbs
+        ));
+        render_missing(ui, &nutrition.missing);
+        ui.add_space(10.0);
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            let can_save = !recipe.name.is_empty() && !recipe.ingredients.is_empty();
+            if ui
+                .add_enabled(can_save, egui::Button::new("✓ Save").min_size(Vec2::new(100.0, 30.0)).fill(button_color))
+                .clicked()
+            {
+                close = true;
+            }
+            if ui
+                .add(egui::Button::new("✖ Cancel").min_size(Vec2::new(100.0, 30.0)).fill(button_color.linear_multiply(0.7)))
+                .clicked()
+            {
+                self.draft = None;
+            }
+        });
+
+        if close {
+            if let Some(original) = self.draft.take().and_then(|draft| draft.original_name) {
+                if original.to_lowercase() != recipe.name.to_lowercase() {
+                    diary.remove_recipe(&original)?;
+                }
+            }
+            diary.save_recipe(&recipe)?;
+        }
+
+        Ok(())
+    }
+
+    fn render_meals(&mut self, ui: &mut Ui, diary: &FoodDiary, date: &str, today: &[FoodEntry]) -> Result<bool> {
+        let button_color = parse_color_from_ini("button-color");
+        let mut logged = false;
+        let mut delete = None;
+
+        let meals = diary.meal_templates();
+        if meals.is_empty() {
+            ui.label(egui::RichText::new("No meal templates yet").size(12.0).color(Color32::GRAY));
+        }
+
+        for meal in &meals {
+            let kcal: i32 = meal.items.iter().map(|item| item.kcal).sum();
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(&meal.name).strong());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        delete = Some(meal.name.clone());
+                    }
+                    if ui.add(egui::Button::new("+ Add").fill(button_color)).clicked() {
+                        match diary.apply_meal_template(date, meal) {
+                            Ok(()) => logged = true,
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                });
+            });
+
+            let names: Vec<&str> = meal.items.iter().map(|item| item.name.as_str()).collect();
+            ui.label(
+                egui::RichText::new(format!("{} kcal · {}", kcal, names.join(", ")))
+                    .size(11.0)
+                    .color(Color32::GRAY),
+            );
+            ui.add_space(5.0);
+        }
+
+        if let Some(name) = delete {
+            diary.remove_meal_template(&name)?;
+        }
+
+        ui.add_space(5.0);
+        ui.separator();
+        ui.label("Save today's entries as a template:");
+
+        if today.is_empty() {
+            ui.label(egui::RichText::new("Log some food first").size(11.0).color(Color32::GRAY));
+            return Ok(logged);
+        }
+
+        for entry in today {
+            let mut selected = self.meal_selection.contains(&entry.id);
+            if ui
+                .checkbox(&mut selected, format!("{} {} · {} kcal", entry.time, entry.name, entry.kcal))
+                .changed()
+            {
+                if selected {
+                    self.meal_selection.insert(entry.id);
+                } else {
+                    self.meal_selection.remove(&entry.id);
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.meal_name).hint_text("Usual breakfast...").desired_width(180.0));
+
+            let can_save = !self.meal_name.trim().is_empty() && !self.meal_selection.is_empty();
+            if ui.add_enabled(can_save, egui::Button::new("💾 Save").fill(button_color)).clicked() {
+                let items = today
+                    .iter()
+                    .filter(|entry| self.meal_selection.contains(&entry.id))
+                    .map(|entry| FoodEntry { id: 0, ..entry.clone() })
+                    .collect();
+                let meal = MealTemplate {
+                    name: self.meal_name.trim().to_string(),
+                    items,
+                };
+
+                match diary.save_meal_template(&meal) {
+                    Ok(()) => {
+                        self.meal_name.clear();
+                        self.meal_selection.clear();
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        });
+
+        Ok(logged)
+    }
+}
+
+fn render_missing(ui: &mut Ui, missing: &[String]) {
+    if missing.is_empty() {
+        return;
+    }
+    ui.label(
+        egui::RichText::new(format!("⚠ Not in local database: {}", missing.join(", ")))
+            .size(11.0)
+            .color(Color32::from_rgb(220, 150, 60)),
+    );
+}
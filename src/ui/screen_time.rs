@@ -0,0 +1,458 @@
+use crate::ui::activity_tracker::idle_unavailable;
+use crate::ui::activitywatch_reader::{
+    aggregate_usage, create_activity_source, fetch_usage, format_duration, today_range, AppUsage, AwEvent,
+};
+use crate::ui::color_parser::{parse_category_color, parse_color_from_ini};
+use crate::ui::notifications_listener::NotificationSender;
+use crate::ui::settings::get_section_entries;
+use crate::ui::widget_registry::{SidebarWidget, WidgetLayout};
+use chrono::{DateTime, Local, Utc};
+use egui::{Color32, Frame, Sense, Stroke, Ui, Vec2};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const TOP_APPS: usize = 5;
+const AVERAGE_DAYS: i64 = 7;
+
+// Категория из секции [categories]: browser = firefox, chromium
+#[derive(Debug, Clone)]
+pub struct Category {
+    pub name: String,
+    pub apps: Vec<String>,
+}
+
+// Мягкий лимит из секции [limits]: browser = 2h
+#[derive(Debug, Clone)]
+pub struct UsageLimit {
+    pub target: String,
+    pub minutes: u32,
+}
+
+// Секции читаются каждый кадр, поэтому держим их в кэше до сохранения настроек
+static CATEGORIES_CACHE: Lazy<RwLock<Option<Vec<Category>>>> = Lazy::new(|| RwLock::new(None));
+static LIMITS_CACHE: Lazy<RwLock<Option<Vec<UsageLimit>>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn load_categories() -> Vec<Category> {
+    if let Ok(cache) = CATEGORIES_CACHE.read() {
+        if let Some(categories) = cache.as_ref() {
+            return categories.clone();
+        }
+    }
+
+    let categories: Vec<Category> = get_section_entries("categories")
+        .into_iter()
+        .map(|(name, apps)| Category {
+            name,
+            apps: apps
+                .split(',')
+                .map(|app| app.trim().to_lowercase())
+                .filter(|app| !app.is_empty())
+                .collect(),
+        })
+        .collect();
+
+    if let Ok(mut cache) = CATEGORIES_CACHE.write() {
+        *cache = Some(categories.clone());
+    }
+    categories
+}
+
+pub fn load_limits() -> Vec<UsageLimit> {
+    if let Ok(cache) = LIMITS_CACHE.read() {
+        if let Some(limits) = cache.as_ref() {
+            return limits.clone();
+        }
+    }
+
+    let limits: Vec<UsageLimit> = get_section_entries("limits")
+        .into_iter()
+        .filter_map(|(target, value)| {
+            parse_limit_minutes(&value).map(|minutes| UsageLimit { target, minutes })
+        })
+        .collect();
+
+    if let Ok(mut cache) = LIMITS_CACHE.write() {
+        *cache = Some(limits.clone());
+    }
+    limits
+}
+
+pub fn invalidate_activity_rules_cache() {
+    if let Ok(mut cache) = CATEGORIES_CACHE.write() {
+        *cache = None;
+    }
+    if let Ok(mut cache) = LIMITS_CACHE.write() {
+        *cache = None;
+    }
+}
+
+// Поддерживает "120", "90m", "2h" и "1h30m"
+pub fn parse_limit_minutes(value: &str) -> Option<u32> {
+    let value = value.trim().to_lowercase();
+    let (hours, rest) = match value.split_once('h') {
+        Some((hours, rest)) => (hours.trim().parse::<u32>().ok()?, rest.trim()),
+        None => (0, value.as_str()),
+    };
+    let minutes = match rest.strip_suffix('m').unwrap_or(rest).trim() {
+        "" => 0,
+        minutes => minutes.parse::<u32>().ok()?,
+    };
+
+    // Нулевой лимит не имеет смысла, переполнение считаем ошибкой ввода
+    let total = hours.checked_mul(60)?.checked_add(minutes)?;
+    (total > 0).then_some(total)
+}
+
+// Среднее только по дням с данными: после установки или включения трекера их меньше недели
+fn daily_average(events: &[AwEvent], first_day: DateTime<Utc>, days: i64) -> Option<f64> {
+    let totals: Vec<f64> = (0..days)
+        .map(|day| {
+            let start = first_day + chrono::Duration::days(day);
+            aggregate_usage(events, start, start + chrono::Duration::days(1))
+                .iter()
+                .map(|app| app.seconds)
+                .sum::<f64>()
+        })
+        .filter(|total| *total > 0.0)
+        .collect();
+    (!totals.is_empty()).then(|| totals.iter().sum::<f64>() / totals.len() as f64)
+}
+
+pub fn category_of<'a>(app: &str, categories: &'a [Category]) -> Option<&'a Category> {
+    let app = app.to_lowercase();
+    categories.iter().find(|category| category.apps.contains(&app))
+}
+
+pub fn app_category_color(app: &str, categories: &[Category]) -> Option<Color32> {
+    category_of(app, categories).and_then(|category| parse_category_color(&category.name))
+}
+
+// Лимит может относиться как к категории, так и к отдельному приложению
+fn limit_usage_seconds(limit: &UsageLimit, usage: &[AppUsage], categories: &[Category]) -> f64 {
+    let target = limit.target.to_lowercase();
+
+    usage
+        .iter()
+        .filter(|app| {
+            app.app.to_lowercase() == target
+                || category_of(&app.app, categories).is_some_and(|category| category.name == target)
+        })
+        .map(|app| app.seconds)
+        .sum()
+}
+
+#[derive(Default)]
+struct ScreenTimeState {
+    today: Vec<AppUsage>,
+    week_average: Option<f64>,
+    error: Option<String>,
+    loading: bool,
+    limits_checked: bool,
+}
+
+pub struct ScreenTimeWidget {
+    state: Arc<Mutex<ScreenTimeState>>,
+    next_update: Option<Instant>,
+    notifier: NotificationSender,
+    // Лимиты, о которых уже сообщили сегодня: "2024-05-01:browser"
+    notified_limits: HashSet<String>,
+}
+
+impl ScreenTimeWidget {
+    pub fn new(notifier: NotificationSender) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ScreenTimeState::default())),
+            next_update: None,
+            notifier,
+            notified_limits: HashSet::new(),
+        }
+    }
+
+    fn refresh_if_needed(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        if self.next_update.is_some_and(|next| now < next) {
+            return;
+        }
+        self.next_update = Some(now + REFRESH_INTERVAL);
+
+        if let Ok(mut state) = self.state.lock() {
+            if state.loading {
+                return;
+            }
+            state.loading = true;
+        }
+
+        let state = Arc::clone(&self.state);
+        let ctx = ctx.clone();
+
+        std::thread::spawn(move || {
+            let source = create_activity_source();
+            let (start, end) = today_range();
+            let today = fetch_usage(source.as_ref(), start, end);
+
+            // Среднее за предыдущие 7 полных дней
+            let week_start = start - chrono::Duration::days(AVERAGE_DAYS);
+            let week_average = source
+                .fetch_events(week_start, start)
+                .ok()
+                .and_then(|events| daily_average(&events, week_start, AVERAGE_DAYS));
+
+            if let Ok(mut state) = state.lock() {
+                match today {
+                    Ok(usage) => {
+                        state.today = usage;
+                        state.week_average = week_average;
+                        state.error = None;
+                        state.limits_checked = false;
+                    }
+                    Err(err) => {
+                        state.error = Some(err.to_string());
+                    }
+                }
+                state.loading = false;
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn check_limits(&mut self, usage: &[AppUsage], categories: &[Category], limits: &[UsageLimit]) {
+        let date = Local::now().format("%Y-%m-%d").to_string();
+
+        for limit in limits {
+            let used = limit_usage_seconds(limit, usage, categories);
+            let key = format!("{}:{}", date, limit.target);
+
+            if used >= limit.minutes as f64 * 60.0 && self.notified_limits.insert(key) {
+                self.notifier.send(
+                    "SideBar",
+                    "⏱ Usage limit exceeded",
+                    &format!(
+                        "{}: {} of {}",
+                        limit.target,
+                        format_duration(used),
+                        format_duration(limit.minutes as f64 * 60.0)
+                    ),
+                );
+            }
+        }
+    }
+
+    // Лимиты проверяются по каждому новому замеру, даже если виджет свёрнут
+    fn tick(&mut self, ctx: &egui::Context) {
+        self.refresh_if_needed(ctx);
+
+        let usage = match self.state.lock() {
+            Ok(mut state) if !state.limits_checked => {
+                state.limits_checked = true;
+                state.today.clone()
+            }
+            _ => return,
+        };
+        self.check_limits(&usage, &load_categories(), &load_limits());
+    }
+
+    pub fn render(&mut self, ui: &mut Ui) {
+        let (usage, week_average, error, loading) = match self.state.lock() {
+            Ok(state) => (state.today.clone(), state.week_average, state.error.clone(), state.loading),
+            Err(_) => (Vec::new(), None, None, false),
+        };
+
+        let categories = load_categories();
+        let limits = load_limits();
+
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        frame.show(ui, |ui| {
+            ui.heading("⏱ Screen Time");
+            ui.add_space(5.0);
+
+            if idle_unavailable() {
+                ui.label(
+                    egui::RichText::new("Idle time unknown: time away counts as active. Set [tracker] idle_command")
+                        .size(11.0)
+                        .color(Color32::from_rgb(220, 150, 40)),
+                );
+                ui.add_space(5.0);
+            }
+
+            if usage.is_empty() {
+                let message = if loading {
+                    "Loading activity...".to_string()
+                } else if let Some(error) = &error {
+                    format!("No activity data: {}", error)
+                } else {
+                    "No activity recorded today".to_string()
+                };
+                ui.label(egui::RichText::new(message).size(12.0).color(Color32::GRAY));
+                return;
+            }
+
+            let total: f64 = usage.iter().map(|app| app.seconds).sum();
+            ui.label(egui::RichText::new(format_duration(total)).size(22.0).strong());
+            render_average_comparison(ui, total, week_average);
+
+            ui.add_space(10.0);
+            render_top_apps(ui, &usage, &categories);
+
+            if !limits.is_empty() {
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(5.0);
+                render_limits(ui, &usage, &categories, &limits);
+            }
+        });
+    }
+}
+
+impl SidebarWidget for ScreenTimeWidget {
+    fn id(&self) -> &'static str {
+        "screen_time"
+    }
+
+    fn title(&self) -> &'static str {
+        "Screen time"
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        ScreenTimeWidget::render(self, ui);
+    }
+
+    fn tick(&mut self, ctx: &egui::Context) {
+        if WidgetLayout::current().is_enabled(self.id()) {
+            ScreenTimeWidget::tick(self, ctx);
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.next_update = None;
+    }
+}
+
+fn render_average_comparison(ui: &mut Ui, total: f64, week_average: Option<f64>) {
+    let Some(average) = week_average.filter(|average| *average > 0.0) else {
+        return;
+    };
+
+    let difference = total - average;
+    let (arrow, color) = if difference > 0.0 {
+        ("▲", Color32::from_rgb(200, 60, 60))
+    } else {
+        ("▼", Color32::from_rgb(60, 150, 60))
+    };
+
+    ui.label(
+        egui::RichText::new(format!(
+            "{} {} vs 7-day average ({})",
+            arrow,
+            format_duration(difference.abs()),
+            format_duration(average)
+        ))
+        .size(12.0)
+        .color(color),
+    );
+}
+
+fn render_top_apps(ui: &mut Ui, usage: &[AppUsage], categories: &[Category]) {
+    let Some(max_seconds) = usage.first().map(|app| app.seconds) else {
+        return;
+    };
+    let fallback = parse_color_from_ini("frame-border-color");
+
+    for app in usage.iter().take(TOP_APPS) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(&app.app).size(12.0));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new(format_duration(app.seconds))
+                        .size(12.0)
+                        .color(Color32::GRAY),
+                );
+            });
+        });
+
+        let color = app_category_color(&app.app, categories).unwrap_or(fallback);
+        let width = ui.available_width();
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(width, 6.0), Sense::hover());
+        ui.painter()
+            .rect_filled(rect, 3.0, parse_color_from_ini("button-color").linear_multiply(0.4));
+
+        let mut filled = rect;
+        filled.set_width(width * (app.seconds / max_seconds) as f32);
+        ui.painter().rect_filled(filled, 3.0, color);
+        ui.add_space(4.0);
+    }
+}
+
+fn render_limits(ui: &mut Ui, usage: &[AppUsage], categories: &[Category], limits: &[UsageLimit]) {
+    ui.label(egui::RichText::new("Limits").strong());
+
+    for limit in limits {
+        let used = limit_usage_seconds(limit, usage, categories);
+        let allowed = limit.minutes as f64 * 60.0;
+        let exceeded = used >= allowed;
+
+        let text = format!(
+            "{}: {} / {}",
+            limit.target,
+            format_duration(used),
+            format_duration(allowed)
+        );
+        let mut bar = egui::ProgressBar::new((used / allowed).min(1.0) as f32).text(text);
+        if exceeded {
+            bar = bar.fill(Color32::from_rgb(200, 60, 60));
+        } else if let Some(color) = parse_category_color(&limit.target) {
+            bar = bar.fill(color);
+        }
+        ui.add(bar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::activitywatch_reader::AwEventData;
+
+    fn event(timestamp: &str, duration: f64) -> AwEvent {
+        AwEvent {
+            timestamp: timestamp.to_string(),
+            duration,
+            data: AwEventData {
+                app: "firefox".to_string(),
+                title: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn limits_accept_minutes_and_hours() {
+        assert_eq!(parse_limit_minutes("120"), Some(120));
+        assert_eq!(parse_limit_minutes("90m"), Some(90));
+        assert_eq!(parse_limit_minutes("2h"), Some(120));
+        assert_eq!(parse_limit_minutes(" 1H30m "), Some(90));
+    }
+
+    #[test]
+    fn limits_reject_zero_overflow_and_garbage() {
+        for value in ["", "0", "0h", "0m", "99999999h", "4294967295h1m", "abc", "1x", "h", "1h30mm"] {
+            assert_eq!(parse_limit_minutes(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn average_skips_days_without_data() {
+        let first_day = DateTime::parse_from_rfc3339("2024-05-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        // Данные только за два последних дня недели
+        let events = [event("2024-05-06T10:00:00+00:00", 3600.0), event("2024-05-07T10:00:00+00:00", 7200.0)];
+        assert_eq!(daily_average(&events, first_day, 7), Some(5400.0));
+        assert_eq!(daily_average(&[], first_day, 7), None);
+    }
+}
@@ -0,0 +1,624 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::settings::{get_section_entries, get_section_names};
+use crate::ui::widget_registry::{SidebarWidget, WidgetLayout};
+use egui::{Color32, Frame, Sense, Stroke, Ui};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Виджеты из секций [script:<имя>] в settings.ini, как модули waybar/polybar:
+//
+//   [script:cpu]
+//   title = CPU
+//   command = ~/.config/sidebar/scripts/cpu.sh
+//   interval = 5          ; секунды, 0 — один раз при запуске
+//   mode = interval       ; или stream — каждая строка вывода долгоживущего процесса
+//   format = json         ; text, json ({text, tooltip, class, percentage}) или progress
+//   on_click = gnome-system-monitor
+//   on_right_click =
+//   timeout = 5           ; секунды на запуск command и on_click
+//   max_output = 4096     ; байты, остальное отбрасывается
+const SECTION_PREFIX: &str = "script:";
+const DEFAULT_INTERVAL_SECS: u64 = 10;
+const DEFAULT_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_MAX_OUTPUT: usize = 4096;
+const MAX_OUTPUT_LIMIT: usize = 1024 * 1024;
+const STREAM_RESTART_DELAY: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const ONE_SHOT_DELAY: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptMode {
+    Interval(Option<Duration>), // None — только один запуск
+    Stream,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptFormat {
+    Text,
+    Json,
+    Progress,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptConfig {
+    pub id: String, // "script:<имя>", ключ раскладки виджетов
+    pub title: String,
+    pub command: String,
+    pub mode: ScriptMode,
+    pub format: ScriptFormat,
+    pub on_click: Option<String>,
+    pub on_right_click: Option<String>,
+    pub timeout: Duration,
+    pub max_output: usize,
+}
+
+impl ScriptConfig {
+    fn from_section(section: &str) -> Option<Self> {
+        let name = section.strip_prefix(SECTION_PREFIX)?.trim();
+        let entries = get_section_entries(section);
+        let get = |key: &str| {
+            entries
+                .iter()
+                .find(|(entry, _)| entry == key)
+                .map(|(_, value)| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let number = |key: &str, default: u64| get(key).and_then(|value| value.parse().ok()).unwrap_or(default);
+
+        let command = get("command")?;
+        let mode = match get("mode").as_deref() {
+            Some("stream") => ScriptMode::Stream,
+            _ => match number("interval", DEFAULT_INTERVAL_SECS) {
+                0 => ScriptMode::Interval(None),
+                secs => ScriptMode::Interval(Some(Duration::from_secs(secs))),
+            },
+        };
+        let format = match get("format").as_deref() {
+            Some("json") => ScriptFormat::Json,
+            Some("progress") => ScriptFormat::Progress,
+            _ => ScriptFormat::Text,
+        };
+
+        Some(Self {
+            id: format!("{}{}", SECTION_PREFIX, name),
+            title: get("title").unwrap_or_else(|| name.to_string()),
+            command,
+            mode,
+            format,
+            on_click: get("on_click"),
+            on_right_click: get("on_right_click"),
+            timeout: Duration::from_secs(number("timeout", DEFAULT_TIMEOUT_SECS).max(1)),
+            max_output: (number("max_output", DEFAULT_MAX_OUTPUT as u64) as usize).clamp(64, MAX_OUTPUT_LIMIT),
+        })
+    }
+}
+
+// Секции без command пропускаются
+pub fn load_script_configs() -> Vec<ScriptConfig> {
+    get_section_names()
+        .iter()
+        .filter(|section| section.starts_with(SECTION_PREFIX))
+        .filter_map(|section| ScriptConfig::from_section(section))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptOutput {
+    pub text: String,
+    pub tooltip: Option<String>,
+    pub classes: Vec<String>,
+    pub percentage: Option<f32>,
+}
+
+// Разбор вывода по формату; для text — как в waybar: текст, подсказка, класс построчно
+pub fn parse_output(raw: &str, format: ScriptFormat) -> Result<ScriptOutput, String> {
+    let raw = raw.trim();
+    match format {
+        ScriptFormat::Text => {
+            let mut lines = raw.lines();
+            Ok(ScriptOutput {
+                text: lines.next().unwrap_or_default().to_string(),
+                tooltip: lines.next().map(str::to_string).filter(|line| !line.is_empty()),
+                classes: lines.next().map(|line| vec![line.trim().to_string()]).unwrap_or_default(),
+                percentage: None,
+            })
+        }
+        ScriptFormat::Json => {
+            // Скрипт мог напечатать несколько объектов, берём последний
+            let line = raw.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default();
+            let value: Value = serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+            let classes = match &value["class"] {
+                Value::String(class) => vec![class.clone()],
+                Value::Array(classes) => classes.iter().filter_map(|class| class.as_str().map(str::to_string)).collect(),
+                _ => Vec::new(),
+            };
+            Ok(ScriptOutput {
+                text: value["text"].as_str().unwrap_or_default().to_string(),
+                tooltip: value["tooltip"].as_str().map(str::to_string),
+                classes,
+                percentage: value["percentage"].as_f64().map(|percentage| percentage as f32),
+            })
+        }
+        ScriptFormat::Progress => {
+            let first = raw.split_whitespace().next().unwrap_or_default();
+            let percentage: f32 = first
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| format!("Expected a percentage, got '{}'", first))?;
+            Ok(ScriptOutput {
+                text: raw.lines().next().unwrap_or_default().to_string(),
+                tooltip: None,
+                classes: Vec::new(),
+                percentage: Some(percentage),
+            })
+        }
+    }
+}
+
+// Своя группа процессов: по таймауту убиваем и всё, что запустил скрипт
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command).stdin(Stdio::null()).stderr(Stdio::null()).process_group(0);
+    shell
+}
+
+// Группа совпадает с pid sh, потому что shell() создаёт её при запуске
+fn kill_group(child: &mut Child) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+// Читает не больше limit байт, остальное отбрасывает, чтобы процесс не блокировался на записи
+fn read_limited(mut reader: impl Read, limit: usize) -> String {
+    let mut output = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while let Ok(read) = reader.read(&mut chunk) {
+        if read == 0 {
+            break;
+        }
+        let room = limit.saturating_sub(output.len());
+        output.extend_from_slice(&chunk[..read.min(room)]);
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+// Строка без перевода строки длиннее limit обрезается; None — поток закрыт
+fn read_line_limited(reader: &mut impl BufRead, limit: usize) -> Option<String> {
+    let mut line = Vec::new();
+    loop {
+        let buffer = reader.fill_buf().ok()?;
+        if buffer.is_empty() {
+            return (!line.is_empty()).then(|| String::from_utf8_lossy(&line).into_owned());
+        }
+        let (chunk, done) = match buffer.iter().position(|byte| *byte == b'\n') {
+            Some(end) => (&buffer[..end], Some(end + 1)),
+            None => (buffer, None),
+        };
+        let room = limit.saturating_sub(line.len());
+        line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        let consumed = done.unwrap_or(buffer.len());
+        reader.consume(consumed);
+        if done.is_some() {
+            return Some(String::from_utf8_lossy(&line).into_owned());
+        }
+    }
+}
+
+// Запуск с ограничением по времени: по истечении timeout процесс убивается
+pub fn run_with_timeout(command: &str, timeout: Duration, max_output: usize) -> Result<String, String> {
+    let mut child = shell(command).stdout(Stdio::piped()).spawn().map_err(|e| e.to_string())?;
+    let stdout = child.stdout.take().ok_or("No stdout")?;
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(read_limited(stdout, max_output));
+    });
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() >= deadline => {
+                kill_group(&mut child);
+                return Err(format!("Timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    // Потомки скрипта могут держать stdout открытым, поэтому тоже ждём не дольше таймаута
+    receiver
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()).max(POLL_INTERVAL))
+        .map_err(|_| {
+            kill_group(&mut child);
+            "Output not closed in time".to_string()
+        })
+}
+
+#[derive(Default)]
+struct ScriptState {
+    output: Option<ScriptOutput>,
+    error: Option<String>,
+    running: bool,
+    refresh_requested: bool,
+}
+
+pub struct ScriptWidget {
+    config: ScriptConfig,
+    // SidebarWidget отдаёт &'static str; конфиги читаются один раз при запуске
+    id: &'static str,
+    title: &'static str,
+    state: Arc<Mutex<ScriptState>>,
+    stream: Arc<Mutex<Option<Child>>>,
+    next_run: Option<Instant>,
+}
+
+impl ScriptWidget {
+    pub fn new(config: ScriptConfig) -> Self {
+        Self {
+            id: Box::leak(config.id.clone().into_boxed_str()),
+            title: Box::leak(config.title.clone().into_boxed_str()),
+            config,
+            state: Arc::new(Mutex::new(ScriptState::default())),
+            stream: Arc::new(Mutex::new(None)),
+            next_run: None,
+        }
+    }
+
+    fn tick(&mut self, ctx: &egui::Context) {
+        let refresh_requested = self
+            .state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.refresh_requested))
+            .unwrap_or(false);
+        if refresh_requested {
+            self.next_run = None;
+        }
+
+        let now = Instant::now();
+        if self.next_run.is_some_and(|next| now < next) {
+            return;
+        }
+
+        match self.config.mode {
+            ScriptMode::Interval(interval) => {
+                // Без интервала — один запуск; ждать следующего нечего
+                self.next_run = Some(now + interval.unwrap_or(ONE_SHOT_DELAY));
+                if let Some(interval) = interval {
+                    ctx.request_repaint_after(interval);
+                }
+                self.run_once(ctx);
+            }
+            ScriptMode::Stream => {
+                self.next_run = Some(now + STREAM_RESTART_DELAY);
+                ctx.request_repaint_after(STREAM_RESTART_DELAY);
+                self.start_stream(ctx);
+            }
+        }
+    }
+
+    fn run_once(&mut self, ctx: &egui::Context) {
+        if let Ok(mut state) = self.state.lock() {
+            if state.running {
+                return;
+            }
+            state.running = true;
+        }
+
+        let state = Arc::clone(&self.state);
+        let config = self.config.clone();
+        let ctx = ctx.clone();
+
+        // Скрипт не должен блокировать отрисовку
+        std::thread::spawn(move || {
+            let result = run_with_timeout(&config.command, config.timeout, config.max_output)
+                .and_then(|raw| parse_output(&raw, config.format));
+
+            if let Ok(mut state) = state.lock() {
+                match result {
+                    Ok(output) => {
+                        state.output = Some(output);
+                        state.error = None;
+                    }
+                    Err(e) => state.error = Some(e),
+                }
+                state.running = false;
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    // Долгоживущий процесс перезапускается, если завершился
+    fn start_stream(&mut self, ctx: &egui::Context) {
+        let Ok(mut stream) = self.stream.lock() else {
+            return;
+        };
+        if let Some(child) = stream.as_mut() {
+            if matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+        }
+
+        let mut child = match shell(&self.config.command).stdout(Stdio::piped()).spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                if let Ok(mut state) = self.state.lock() {
+                    state.error = Some(e.to_string());
+                }
+                return;
+            }
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return;
+        };
+        *stream = Some(child);
+
+        let state = Arc::clone(&self.state);
+        let format = self.config.format;
+        let max_output = self.config.max_output;
+        let ctx = ctx.clone();
+
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(line) = read_line_limited(&mut reader, max_output) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if let Ok(mut state) = state.lock() {
+                    match parse_output(&line, format) {
+                        Ok(output) => {
+                            state.output = Some(output);
+                            state.error = None;
+                        }
+                        Err(e) => state.error = Some(e),
+                    }
+                }
+                ctx.request_repaint();
+            }
+
+            if let Ok(mut state) = state.lock() {
+                state.error = Some("Process exited".to_string());
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn stop_stream(&mut self) {
+        if let Ok(mut stream) = self.stream.lock() {
+            if let Some(mut child) = stream.take() {
+                kill_group(&mut child);
+            }
+        }
+        // После включения поток запустится сразу
+        self.next_run = None;
+    }
+
+    fn run_click(&self, command: &str) {
+        let state = Arc::clone(&self.state);
+        let command = command.to_string();
+        let timeout = self.config.timeout;
+
+        // После действия виджет обновляется, как после нажатия в waybar
+        std::thread::spawn(move || {
+            let result = run_with_timeout(&command, timeout, DEFAULT_MAX_OUTPUT);
+            if let Ok(mut state) = state.lock() {
+                if let Err(e) = result {
+                    state.error = Some(format!("Click action: {}", e));
+                }
+                state.refresh_requested = true;
+            }
+        });
+    }
+
+    fn render_contents(&self, ui: &mut Ui) {
+        let (output, error, running) = match self.state.lock() {
+            Ok(state) => (state.output.clone(), state.error.clone(), state.running),
+            Err(_) => (None, None, false),
+        };
+
+        match &output {
+            Some(output) => {
+                let color = class_color(&output.classes).unwrap_or(ui.visuals().text_color());
+                if !output.text.is_empty() && self.config.format != ScriptFormat::Progress {
+                    ui.label(egui::RichText::new(&output.text).color(color));
+                }
+                if let Some(percentage) = output.percentage {
+                    let mut bar = egui::ProgressBar::new((percentage / 100.0).clamp(0.0, 1.0));
+                    bar = if self.config.format == ScriptFormat::Progress && !output.text.is_empty() {
+                        bar.text(output.text.clone())
+                    } else {
+                        bar.show_percentage()
+                    };
+                    ui.add(bar.fill(class_color(&output.classes).unwrap_or(parse_color_from_ini("button-color"))));
+                }
+            }
+            None if running => {
+                ui.label(egui::RichText::new("Running…").size(12.0).color(Color32::GRAY));
+            }
+            None => {}
+        }
+
+        if let Some(error) = error {
+            ui.label(egui::RichText::new(error).size(11.0).color(Color32::from_rgb(200, 60, 60)));
+        }
+    }
+}
+
+// Классы waybar, у которых есть смысловой цвет
+fn class_color(classes: &[String]) -> Option<Color32> {
+    classes.iter().find_map(|class| match class.as_str() {
+        "critical" | "urgent" => Some(Color32::from_rgb(200, 60, 60)),
+        "warning" => Some(Color32::from_rgb(220, 150, 40)),
+        "good" => Some(Color32::from_rgb(70, 160, 90)),
+        _ => None,
+    })
+}
+
+impl SidebarWidget for ScriptWidget {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn title(&self) -> &'static str {
+        self.title
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        let response = frame
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.heading(self.title);
+                ui.add_space(5.0);
+                self.render_contents(ui);
+            })
+            .response;
+
+        let tooltip = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|state| state.output.as_ref().and_then(|output| output.tooltip.clone()));
+        let clickable = self.config.on_click.is_some() || self.config.on_right_click.is_some();
+        let sense = if clickable { Sense::click() } else { Sense::hover() };
+        let mut response = ui.interact(response.rect, ui.id().with(self.id), sense);
+        if clickable {
+            response = response.on_hover_cursor(egui::CursorIcon::PointingHand);
+        }
+        if let Some(tooltip) = tooltip {
+            response = response.on_hover_text(tooltip);
+        }
+
+        if response.clicked() {
+            if let Some(command) = &self.config.on_click {
+                self.run_click(command);
+            }
+        }
+        if response.secondary_clicked() {
+            if let Some(command) = &self.config.on_right_click {
+                self.run_click(command);
+            }
+        }
+    }
+
+    // Выключенный виджет не запускает команду и не держит процесс stream
+    fn tick(&mut self, ctx: &egui::Context) {
+        if WidgetLayout::current().is_enabled(self.id) {
+            ScriptWidget::tick(self, ctx);
+        } else {
+            self.stop_stream();
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.next_run = None;
+    }
+}
+
+impl Drop for ScriptWidget {
+    fn drop(&mut self) {
+        self.stop_stream();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_output_is_split_like_waybar() {
+        let output = parse_output("42%\nCPU load\nwarning\n", ScriptFormat::Text).unwrap();
+        assert_eq!(output.text, "42%");
+        assert_eq!(output.tooltip.as_deref(), Some("CPU load"));
+        assert_eq!(output.classes, vec!["warning".to_string()]);
+        assert_eq!(output.percentage, None);
+
+        let output = parse_output("only text", ScriptFormat::Text).unwrap();
+        assert_eq!(output.tooltip, None);
+        assert!(output.classes.is_empty());
+    }
+
+    #[test]
+    fn json_output_uses_the_last_object() {
+        let raw = r#"{"text":"old"}
+{"text":"3 updates","tooltip":"pacman","class":["warning","updates"],"percentage":30}
+"#;
+        let output = parse_output(raw, ScriptFormat::Json).unwrap();
+        assert_eq!(output.text, "3 updates");
+        assert_eq!(output.tooltip.as_deref(), Some("pacman"));
+        assert_eq!(output.classes, vec!["warning".to_string(), "updates".to_string()]);
+        assert_eq!(output.percentage, Some(30.0));
+
+        let output = parse_output(r#"{"text":"ok","class":"good"}"#, ScriptFormat::Json).unwrap();
+        assert_eq!(output.classes, vec!["good".to_string()]);
+        assert!(parse_output("not json", ScriptFormat::Json).is_err());
+    }
+
+    #[test]
+    fn progress_output_needs_a_percentage() {
+        let output = parse_output("73% disk\n", ScriptFormat::Progress).unwrap();
+        assert_eq!(output.percentage, Some(73.0));
+        assert_eq!(output.text, "73% disk");
+        assert_eq!(parse_output("12.5", ScriptFormat::Progress).unwrap().percentage, Some(12.5));
+        assert!(parse_output("full", ScriptFormat::Progress).is_err());
+    }
+
+    #[test]
+    fn lines_are_read_one_by_one_and_cut_to_the_limit() {
+        let mut reader = BufReader::with_capacity(4, "first\nsecond line\n\nlast".as_bytes());
+        assert_eq!(read_line_limited(&mut reader, 64).as_deref(), Some("first"));
+        // Буфер меньше строки: куски склеиваются, лишнее отбрасывается до перевода строки
+        assert_eq!(read_line_limited(&mut reader, 6).as_deref(), Some("second"));
+        assert_eq!(read_line_limited(&mut reader, 64).as_deref(), Some(""));
+        // Последняя строка без перевода строки
+        assert_eq!(read_line_limited(&mut reader, 64).as_deref(), Some("last"));
+        assert_eq!(read_line_limited(&mut reader, 64), None);
+    }
+
+    #[test]
+    fn timeout_kills_the_whole_process_group() {
+        let pid_file = crate::ui::test_support::temp_dir("script-group").join("pid");
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = Instant::now();
+        let result = run_with_timeout(&command, Duration::from_secs(1), DEFAULT_MAX_OUTPUT);
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Фоновый sleep держал бы stdout открытым; после таймаута его быть не должно
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let alive = |pid: &str| {
+            std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()))
+                .is_ok_and(|stat| !stat.contains(") Z "))
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while alive(&pid) && Instant::now() < deadline {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        assert!(!alive(&pid));
+    }
+
+    #[test]
+    fn output_is_returned_and_limited() {
+        assert_eq!(run_with_timeout("echo hello", Duration::from_secs(5), 64).unwrap(), "hello\n");
+        assert_eq!(run_with_timeout("printf 1234567890", Duration::from_secs(5), 4).unwrap(), "1234");
+    }
+}
@@ -1,10 +1,18 @@
+use crate::ui::activitywatch_reader::DEFAULT_AW_URL;
 use crate::ui::color_parser::{parse_color_from_ini, invalidate_color_cache};
 use crate::ui::custom_vidgets::StyledImageButton;
+use crate::ui::data_store::{config_dir, config_file};
+use crate::ui::i18n::{invalidate_language_cache, Language};
+use crate::ui::screen_time::invalidate_activity_rules_cache;
+use crate::ui::widget_registry::WidgetRegistry;
 use configparser::ini::Ini;
 use egui::{Vec2, Window};
-use std::env;
 use std::error::Error;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Растёт при записи [health] из виджетов; форма настроек по нему перечитывает поля
+static HEALTH_SETTINGS_VERSION: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Default)]
 pub struct Settings {
@@ -12,15 +20,15 @@ pub struct Settings {
     themes: Vec<String>,
     pub popup_open: bool,
     
-    // Weather settings
-    city: String,
-    country: String,
-    api_key: String,
-    
-    // Health settings
-    daily_water_goal: String,
-    water_increment: String,
-    daily_calorie_goal: String,
+    // Activity settings
+    activity_source: String,
+    tracker_enabled: bool,
+    tracker_probe: String,
+    tracker_interval_secs: String,
+    tracker_command: String,
+    tracker_idle_command: String,
+    activity_categories: String,
+    activity_limits: String,
     
     settings_icon_texture: Option<egui::TextureHandle>,
     config_dir: Option<PathBuf>,
@@ -34,8 +42,8 @@ pub struct Settings {
 enum SettingsSection {
     None,
     Themes,
-    Weather,
-    Health,
+    Widgets,
+    Activity,
 }
 
 impl Default for SettingsSection {
@@ -46,8 +54,7 @@ impl Default for SettingsSection {
 
 impl Settings {
     fn get_config_dir() -> Result<PathBuf, Box<dyn Error>> {
-        let home = env::var("HOME")?;
-        Ok(PathBuf::from(home).join(".config/sidebar"))
+        Ok(config_dir())
     }
 
     fn ensure_config_dir(&mut self) -> Result<&PathBuf, Box<dyn Error>> {
@@ -74,7 +81,7 @@ impl Settings {
         Ok(())
     }
 
-    pub fn create_settings_window(&mut self, ctx: &egui::Context) -> Result<(), Box<dyn Error>> {
+    pub fn create_settings_window(&mut self, ctx: &egui::Context, widgets: &mut WidgetRegistry) -> Result<(), Box<dyn Error>> {
         self.ensure_config_dir()?;
         
         if !self.first_open {
@@ -82,7 +89,7 @@ impl Settings {
             self.first_open = true;
         }
 
-        self.render_window(ctx);
+        self.render_window(ctx, widgets);
         Ok(())
     }
 
@@ -93,26 +100,29 @@ impl Settings {
         // Load themes
         self.themes = themes.sections();
         
-        // Load weather settings
-        self.city = settings.get("settings", "city").unwrap_or_default();
-        self.country = settings.get("settings", "country").unwrap_or_default();
-        self.api_key = settings.get("settings", "owm_api_key").unwrap_or_default();
-        
-        // Load health settings with defaults
-        self.daily_water_goal = settings
-            .get("health", "daily_water_goal")
-            .unwrap_or_else(|| "2000".to_string());
-        self.water_increment = settings
-            .get("health", "water_increment")
-            .unwrap_or_else(|| "400".to_string());
-        self.daily_calorie_goal = settings
-            .get("health", "daily_calorie_goal")
-            .unwrap_or_else(|| "2000".to_string());
+        // Load activity settings with defaults
+        self.activity_source = settings
+            .get("activitywatch", "source")
+            .unwrap_or_else(|| "auto".to_string());
+        self.tracker_enabled = settings
+            .get("tracker", "enabled")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+        self.tracker_probe = settings
+            .get("tracker", "probe")
+            .unwrap_or_else(|| "auto".to_string());
+        self.tracker_interval_secs = settings
+            .get("tracker", "interval_secs")
+            .unwrap_or_else(|| "10".to_string());
+        self.tracker_command = settings.get("tracker", "command").unwrap_or_default();
+        self.tracker_idle_command = settings.get("tracker", "idle_command").unwrap_or_default();
+        self.activity_categories = section_to_lines(&settings, "categories");
+        self.activity_limits = section_to_lines(&settings, "limits");
         
         Ok(())
     }
 
-    fn render_window(&mut self, ctx: &egui::Context) {
+    fn render_window(&mut self, ctx: &egui::Context, widgets: &mut WidgetRegistry) {
         let bg_color = parse_color_from_ini("background-color");
         let text_color = parse_color_from_ini("text-color");
         let accent_color = parse_color_from_ini("button-color");
@@ -137,9 +147,9 @@ impl Settings {
                     .show(ui, |ui| {
                         self.render_theme_section(ui, ctx);
                         self.add_separator(ui);
-                        self.render_health_section(ui);
+                        self.render_widgets_section(ui, widgets);
                         self.add_separator(ui);
-                        self.render_weather_settings(ui);
+                        self.render_activity_section(ui);
                         self.add_separator(ui);
                         self.render_action_buttons(ui);
                     });
@@ -199,84 +209,158 @@ impl Settings {
                 ctx.request_repaint();
             }
             
+            ui.add_space(5.0);
+            
+            let current_language = Language::from_code(&get_language_setting());
+            let mut selected_language = current_language;
+            
+            ui.horizontal(|ui| {
+                ui.label("Language:");
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(selected_language.label())
+                    .width(220.0)
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(&mut selected_language, language, language.label());
+                        }
+                    });
+            });
+            
+            if selected_language != current_language {
+                self.apply_language(selected_language);
+                ctx.request_repaint();
+            }
+            
             ui.add_space(10.0);
         }
     }
 
-    fn render_health_section(&mut self, ui: &mut egui::Ui) {
-        if self.render_collapsible_header(ui, SettingsSection::Health, "💪", "Health Tracking") {
+    fn render_widgets_section(&mut self, ui: &mut egui::Ui, widgets: &mut WidgetRegistry) {
+        if self.render_collapsible_header(ui, SettingsSection::Widgets, "🧩", "Widgets") {
             ui.add_space(10.0);
-            
-            ui.heading("Water Settings");
+            ui.label(
+                egui::RichText::new("Show, hide and reorder widgets on the panel")
+                    .size(11.0)
+                    .color(egui::Color32::GRAY),
+            );
             ui.add_space(5.0);
+            widgets.settings_ui(ui);
+            ui.add_space(10.0);
+        }
+    }
+
+    fn render_activity_section(&mut self, ui: &mut egui::Ui) {
+        if self.render_collapsible_header(ui, SettingsSection::Activity, "📊", "Activity") {
+            ui.add_space(10.0);
             
             ui.horizontal(|ui| {
-                ui.label("Daily goal (ml):");
-                ui.add_space(5.0);
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.daily_water_goal)
-                        .desired_width(100.0)
-                        .hint_text("2000")
-                );
+                ui.label("Data source:");
+                egui::ComboBox::from_id_source("activity_source")
+                    .selected_text(&self.activity_source)
+                    .show_ui(ui, |ui| {
+                        for source in ["auto", "activitywatch", "builtin"] {
+                            ui.selectable_value(&mut self.activity_source, source.to_string(), source);
+                        }
+                    });
             });
+            ui.add_space(10.0);
+            
+            ui.label("Categories (one per line: browser = firefox, chromium):");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.activity_categories)
+                    .desired_width(ui.available_width())
+                    .desired_rows(3)
+            );
             ui.add_space(3.0);
             
-            ui.horizontal(|ui| {
-                ui.label("Increment (ml):");
-                ui.add_space(5.0);
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.water_increment)
-                        .desired_width(100.0)
-                        .hint_text("400")
-                );
-            });
+            ui.label("Usage limits (app or category = 2h):");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.activity_limits)
+                    .desired_width(ui.available_width())
+                    .desired_rows(3)
+            );
+            ui.label(
+                egui::RichText::new("Category colors come from the theme: category-browser = 255,120,80")
+                    .size(11.0)
+                    .color(egui::Color32::GRAY)
+            );
+            ui.add_space(10.0);
             
-            ui.add_space(15.0);
-            ui.heading("Food Settings");
+            ui.heading("Built-in Tracker");
             ui.add_space(5.0);
             
+            ui.checkbox(&mut self.tracker_enabled, "Sample focused window (restart required)");
+            ui.add_space(3.0);
+            
             ui.horizontal(|ui| {
-                ui.label("Daily goal (kcal):");
+                ui.label("Window probe:");
+                egui::ComboBox::from_id_source("tracker_probe")
+                    .selected_text(&self.tracker_probe)
+                    .show_ui(ui, |ui| {
+                        for probe in ["auto", "x11", "sway", "hyprland", "command"] {
+                            ui.selectable_value(&mut self.tracker_probe, probe.to_string(), probe);
+                        }
+                    });
+            });
+            ui.add_space(3.0);
+            
+            ui.horizontal(|ui| {
+                ui.label("Interval (s):");
                 ui.add_space(5.0);
                 ui.add(
-                    egui::TextEdit::singleline(&mut self.daily_calorie_goal)
+                    egui::TextEdit::singleline(&mut self.tracker_interval_secs)
                         .desired_width(100.0)
-                        .hint_text("2000")
+                        .hint_text("10")
                 );
             });
             
+            if self.tracker_probe == "command" {
+                ui.add_space(3.0);
+                render_text_input(ui, "Command (prints app and title):", &mut self.tracker_command);
+            }
+            
+            // xprintidle есть только на X11, а на sway и Hyprland простой ничем не измерить
+            ui.add_space(3.0);
+            render_text_input(ui, "Idle command (prints idle ms):", &mut self.tracker_idle_command);
+            ui.label(
+                egui::RichText::new("Without it sway and Hyprland count time away as active")
+                    .size(11.0)
+                    .color(egui::Color32::GRAY)
+            );
+            
             ui.add_space(10.0);
             
             if ui.add(
-                egui::Button::new("💾 Save Health Settings")
+                egui::Button::new("💾 Save Activity Settings")
                     .min_size(Vec2::new(200.0, 30.0))
                     .fill(parse_color_from_ini("button-color"))
             ).clicked() {
-                self.save_health_settings();
+                self.save_activity_settings();
             }
             
             ui.add_space(10.0);
         }
     }
 
-    fn save_health_settings(&self) {
+    fn save_activity_settings(&self) {
         if let Ok(mut settings) = self.load_ini("settings.ini") {
-            // Validate and save water goal
-            if let Ok(goal) = self.daily_water_goal.parse::<u32>() {
-                settings.set("health", "daily_water_goal", Some(goal.to_string()));
-            }
+            settings.set("activitywatch", "source", Some(self.activity_source.clone()));
+            settings.set("tracker", "enabled", Some(self.tracker_enabled.to_string()));
+            settings.set("tracker", "probe", Some(self.tracker_probe.clone()));
+            settings.set("tracker", "command", Some(self.tracker_command.clone()));
+            settings.set("tracker", "idle_command", Some(self.tracker_idle_command.clone()));
             
-            // Validate and save water increment
-            if let Ok(increment) = self.water_increment.parse::<u32>() {
-                settings.set("health", "water_increment", Some(increment.to_string()));
+            if let Ok(interval) = self.tracker_interval_secs.parse::<u64>() {
+                if interval > 0 {
+                    settings.set("tracker", "interval_secs", Some(interval.to_string()));
+                }
             }
             
-            // Validate and save calorie goal
-            if let Ok(goal) = self.daily_calorie_goal.parse::<i32>() {
-                settings.set("health", "daily_calorie_goal", Some(goal.to_string()));
-            }
+            lines_to_section(&mut settings, "categories", &self.activity_categories);
+            lines_to_section(&mut settings, "limits", &self.activity_limits);
             
             let _ = self.save_ini(&settings, "settings.ini");
+            invalidate_activity_rules_cache();
         }
     }
 
@@ -287,40 +371,19 @@ impl Settings {
             .unwrap_or_else(|| "yellow".to_string())
     }
 
-    fn apply_theme(&mut self, theme: &str) {
+    fn apply_language(&mut self, language: Language) {
         if let Ok(mut settings) = self.load_ini("settings.ini") {
-            settings.set("settings", "current-theme", Some(theme.to_string()));
+            settings.set("settings", "language", Some(language.code().to_string()));
             let _ = self.save_ini(&settings, "settings.ini");
-            invalidate_color_cache();
-        }
-    }
-
-    fn render_weather_settings(&mut self, ui: &mut egui::Ui) {
-        if self.render_collapsible_header(ui, SettingsSection::Weather, "🌤", "Weather Settings") {
-            ui.add_space(10.0);
-
-            render_text_input(ui, "City:", &mut self.city);
-            render_text_input(ui, "Country:", &mut self.country);
-            render_text_input(ui, "OpenWeatherMap API Key:", &mut self.api_key);
-
-            if ui.add(
-                egui::Button::new("💾 Save Weather Settings")
-                    .min_size(Vec2::new(200.0, 30.0))
-                    .fill(parse_color_from_ini("button-color")),
-            ).clicked() {
-                self.save_weather_settings();
-            }
-            
-            ui.add_space(10.0);
+            invalidate_language_cache();
         }
     }
 
-    fn save_weather_settings(&self) {
+    fn apply_theme(&mut self, theme: &str) {
         if let Ok(mut settings) = self.load_ini("settings.ini") {
-            settings.set("settings", "city", Some(self.city.clone()));
-            settings.set("settings", "country", Some(self.country.clone()));
-            settings.set("settings", "owm_api_key", Some(self.api_key.clone()));
+            settings.set("settings", "current-theme", Some(theme.to_string()));
             let _ = self.save_ini(&settings, "settings.ini");
+            invalidate_color_cache();
         }
     }
 
@@ -386,7 +449,35 @@ impl Settings {
     }
 }
 
-fn render_text_input(ui: &mut egui::Ui, label: &str, text: &mut String) {
+// Секция вида "key = value" в виде многострочного текста для редактирования
+pub fn section_to_lines(ini: &Ini, section: &str) -> String {
+    let mut entries: Vec<String> = ini
+        .get_map_ref()
+        .get(section)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|(key, value)| value.as_ref().map(|value| format!("{} = {}", key, value)))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries.join("\n")
+}
+
+pub fn lines_to_section(ini: &mut Ini, section: &str, text: &str) {
+    ini.remove_section(section);
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            if !key.is_empty() {
+                ini.set(section, key, Some(value.trim().to_string()));
+            }
+        }
+    }
+}
+
+pub fn render_text_input(ui: &mut egui::Ui, label: &str, text: &mut String) {
     ui.label(label);
     let input_bg = parse_color_from_ini("background-color").linear_multiply(1.2);
     ui.style_mut().visuals.extreme_bg_color = input_bg;
@@ -394,6 +485,14 @@ fn render_text_input(ui: &mut egui::Ui, label: &str, text: &mut String) {
     ui.add_space(5.0);
 }
 
+// 0 — полночь; позже полудня день не начинаем
+pub fn get_day_start_hour() -> u32 {
+    get_health_setting("day_start_hour", "0")
+        .parse()
+        .unwrap_or(0)
+        .min(12)
+}
+
 pub fn get_daily_water_goal() -> u32 {
     get_health_setting("daily_water_goal", "2000")
         .parse()
@@ -406,24 +505,269 @@ pub fn get_water_increment() -> u32 {
         .unwrap_or(400)
 }
 
+// Кнопки быстрого добавления, по возрастанию объёма
+pub fn get_water_presets() -> Vec<(String, u32)> {
+    let mut presets: Vec<(String, u32)> = get_section_entries("water_presets")
+        .into_iter()
+        .filter_map(|(name, ml)| ml.trim().parse().ok().filter(|ml| *ml > 0).map(|ml| (name, ml)))
+        .collect();
+    if presets.is_empty() {
+        presets = vec![
+            ("glass".to_string(), 250),
+            ("mug".to_string(), 350),
+            ("bottle".to_string(), 500),
+        ];
+    }
+    presets.sort_by_key(|(_, ml)| *ml);
+    presets
+}
+
+// Напитки и доля объёма, которая засчитывается в воду; вода всегда первая
+pub fn get_beverages() -> Vec<(String, f32)> {
+    let mut beverages: Vec<(String, f32)> = get_section_entries("beverages")
+        .into_iter()
+        .filter_map(|(name, factor)| factor.trim().parse().ok().map(|factor: f32| (name, factor.clamp(0.0, 1.5))))
+        .filter(|(name, _)| name != "water")
+        .collect();
+    if beverages.is_empty() {
+        beverages = vec![
+            ("tea".to_string(), 0.9),
+            ("coffee".to_string(), 0.8),
+            ("juice".to_string(), 0.9),
+            ("milk".to_string(), 0.9),
+        ];
+    }
+    beverages.insert(0, ("water".to_string(), 1.0));
+    beverages
+}
+
+pub fn get_water_reminder_enabled() -> bool {
+    get_health_setting("water_reminder", "false") == "true"
+}
+
+// Часы, в которые набирается дневная цель: (начало, конец)
+pub fn get_water_reminder_hours() -> (u32, u32) {
+    let start = get_health_setting("reminder_start_hour", "8").parse().unwrap_or(8).min(23);
+    let end = get_health_setting("reminder_end_hour", "22").parse().unwrap_or(22).clamp(start + 1, 24);
+    (start, end)
+}
+
+pub fn get_water_reminder_interval_minutes() -> u64 {
+    get_health_setting("reminder_interval_minutes", "60")
+        .parse()
+        .unwrap_or(60)
+        .max(15)
+}
+
 pub fn get_daily_calorie_goal() -> i32 {
     get_health_setting("daily_calorie_goal", "2000")
         .parse()
         .unwrap_or(2000)
 }
 
-fn get_health_setting(key: &str, default: &str) -> String {
-    let config_dir = match env::var("HOME") {
-        Ok(home) => PathBuf::from(home).join(".config/sidebar"),
-        Err(_) => return default.to_string(),
-    };
-    
+// Пусто — цель не задана
+pub fn get_goal_weight() -> Option<f32> {
+    get_health_setting("goal_weight", "")
+        .parse()
+        .ok()
+        .filter(|kg: &f32| *kg > 0.0)
+}
+
+pub fn get_daily_protein_goal() -> u32 {
+    get_health_setting("daily_protein_goal", "100")
+        .parse()
+        .unwrap_or(100)
+}
+
+pub fn get_daily_fat_goal() -> u32 {
+    get_health_setting("daily_fat_goal", "70")
+        .parse()
+        .unwrap_or(70)
+}
+
+pub fn get_daily_carbs_goal() -> u32 {
+    get_health_setting("daily_carbs_goal", "250")
+        .parse()
+        .unwrap_or(250)
+}
+
+pub fn get_pomodoro_work_minutes() -> u32 {
+    get_pomodoro_setting("work_minutes", "25")
+        .parse()
+        .unwrap_or(25)
+}
+
+pub fn get_pomodoro_short_break_minutes() -> u32 {
+    get_pomodoro_setting("short_break_minutes", "5")
+        .parse()
+        .unwrap_or(5)
+}
+
+pub fn get_pomodoro_long_break_minutes() -> u32 {
+    get_pomodoro_setting("long_break_minutes", "15")
+        .parse()
+        .unwrap_or(15)
+}
+
+pub fn get_pomodoro_sessions() -> u32 {
+    get_pomodoro_setting("sessions_before_long_break", "4")
+        .parse()
+        .unwrap_or(4)
+}
+
+pub fn get_pomodoro_auto_dnd() -> bool {
+    get_pomodoro_setting("auto_dnd", "true") == "true"
+}
+
+pub fn get_activitywatch_url() -> String {
+    get_section_setting("activitywatch", "url", DEFAULT_AW_URL)
+}
+
+pub fn get_activitywatch_db_path() -> String {
+    get_section_setting("activitywatch", "db_path", "")
+}
+
+pub fn get_activity_source() -> String {
+    get_section_setting("activitywatch", "source", "auto")
+}
+
+pub fn get_activity_fixture_path() -> String {
+    get_section_setting("activitywatch", "fixture_path", "")
+}
+
+pub fn get_tracker_enabled() -> bool {
+    get_section_setting("tracker", "enabled", "false") == "true"
+}
+
+pub fn get_tracker_probe() -> String {
+    get_section_setting("tracker", "probe", "auto")
+}
+
+pub fn get_tracker_command() -> String {
+    get_section_setting("tracker", "command", "")
+}
+
+pub fn get_tracker_idle_command() -> String {
+    get_section_setting("tracker", "idle_command", "")
+}
+
+pub fn get_tracker_interval_secs() -> u64 {
+    get_section_setting("tracker", "interval_secs", "10")
+        .parse()
+        .unwrap_or(10)
+}
+
+pub fn get_tracker_idle_threshold_secs() -> u64 {
+    get_section_setting("tracker", "idle_threshold_secs", "300")
+        .parse()
+        .unwrap_or(300)
+}
+
+pub fn get_section_names() -> Vec<String> {
     let mut ini = Ini::new();
-    let settings_path = config_dir.join("settings.ini");
+    if ini.load(config_file("settings.ini")).is_err() {
+        return Vec::new();
+    }
+    let mut sections = ini.sections();
+    sections.sort();
+    sections
+}
+
+pub fn get_section_entries(section: &str) -> Vec<(String, String)> {
+    let mut ini = Ini::new();
+    if ini.load(config_file("settings.ini")).is_err() {
+        return Vec::new();
+    }
     
-    if ini.load(&settings_path).is_ok() {
-        ini.get("health", key).unwrap_or_else(|| default.to_string())
-    } else {
-        default.to_string()
+    let mut entries: Vec<(String, String)> = ini
+        .get_map_ref()
+        .get(section)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|(key, value)| value.clone().map(|value| (key.clone(), value)))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+pub fn get_weather_setting(key: &str, default: &str) -> String {
+    get_section_setting("settings", key, default)
+}
+
+pub fn get_language_setting() -> String {
+    get_section_setting("settings", "language", "en")
+}
+
+// Не чаще раза в минуту, чтобы не выбивать квоту провайдера
+// Больше суток между обновлениями прогноза смысла не имеет
+pub const MAX_WEATHER_REFRESH_MINUTES: i64 = 24 * 60;
+
+pub fn get_weather_refresh_minutes() -> i64 {
+    get_weather_setting("refresh_minutes", "10")
+        .trim()
+        .parse::<i64>()
+        .unwrap_or(10)
+        .clamp(1, MAX_WEATHER_REFRESH_MINUTES)
+}
+
+fn get_health_setting(key: &str, default: &str) -> String {
+    get_section_setting("health", key, default)
+}
+
+pub fn health_settings_version() -> u64 {
+    HEALTH_SETTINGS_VERSION.load(Ordering::Relaxed)
+}
+
+// Для значений, которые меняются из виджетов, а не из окна настроек
+pub fn set_health_setting(key: &str, value: &str) {
+    set_section_setting("health", key, value);
+    HEALTH_SETTINGS_VERSION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn get_widget_setting(key: &str, default: &str) -> String {
+    get_section_setting("widgets", key, default)
+}
+
+pub fn set_widget_setting(key: &str, value: &str) {
+    set_section_setting("widgets", key, value);
+}
+
+fn set_section_setting(section: &str, key: &str, value: &str) {
+    update_settings(|ini| {
+        ini.set(section, key, Some(value.to_string()));
+    });
+}
+
+// settings.ini целиком; None, если файла нет или он не читается
+pub fn load_settings() -> Option<Ini> {
+    let mut ini = Ini::new();
+    ini.load(config_file("settings.ini")).ok()?;
+    Some(ini)
+}
+
+// Читает settings.ini, применяет change и записывает обратно
+pub fn update_settings(change: impl FnOnce(&mut Ini)) {
+    let mut ini = Ini::new();
+    let path = config_file("settings.ini");
+    // Не перезаписываем файл, который не удалось прочитать
+    if path.exists() && ini.load(&path).is_err() {
+        return;
     }
+    change(&mut ini);
+    if let Err(e) = ini.write(&path) {
+        eprintln!("Failed to save settings: {}", e);
+    }
+}
+
+fn get_pomodoro_setting(key: &str, default: &str) -> String {
+    get_section_setting("pomodoro", key, default)
+}
+
+fn get_section_setting(section: &str, key: &str, default: &str) -> String {
+    load_settings()
+        .and_then(|ini| ini.get(section, key))
+        .unwrap_or_else(|| default.to_string())
 }
\ No newline at end of file
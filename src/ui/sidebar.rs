@@ -1,10 +1,16 @@
+use crate::ui::checkin_widget::CheckInWidget;
 use crate::ui::color_parser::parse_color_from_ini;
 use crate::ui::health_widget::HealthWidget;
 use crate::ui::settings::Settings;
 use crate::ui::task_manager::TaskManager;
 use crate::ui::weather_widget::WeatherWidget;
 use crate::ui::aw_qt::SunburstWidget;
+use crate::ui::activity_tracker::ActivityTracker;
+use crate::ui::screen_time::ScreenTimeWidget;
+use crate::ui::script_widget::{load_script_configs, ScriptWidget};
 use crate::ui::notifications_listener::{NotificationsListener, Notification};
+use crate::ui::pomodoro_widget::PomodoroWidget;
+use crate::ui::widget_registry::WidgetRegistry;
 
 use egui::Context;
 use std::sync::{Arc, Mutex};
@@ -17,10 +23,7 @@ enum ViewMode {
 
 pub(crate) struct SideBar {
     view_mode: ViewMode,
-    task_manager: TaskManager,
-    weather_widget: WeatherWidget,
-    sunburst_widget: SunburstWidget,
-    health_widget: HealthWidget,
+    widgets: WidgetRegistry,
     settings: Settings,
     notifications_listener: NotificationsListener,
     notifications: Arc<Mutex<Vec<Notification>>>,
@@ -36,12 +39,25 @@ impl SideBar {
         // Запускаем слушатель
         notifications_listener.start_listening(cc.egui_ctx.clone());
         
+        // Встроенный трекер работает, только если включён в настройках
+        ActivityTracker::start_if_enabled();
+        
+        // Порядок регистрации — порядок по умолчанию, пока его не поменяли в настройках
+        let mut widgets = WidgetRegistry::new();
+        widgets.register(SunburstWidget::new());
+        widgets.register(ScreenTimeWidget::new(notifications_listener.sender(&cc.egui_ctx)));
+        widgets.register(WeatherWidget::new(notifications_listener.sender(&cc.egui_ctx)));
+        widgets.register(TaskManager::default());
+        widgets.register(PomodoroWidget::new());
+        widgets.register(HealthWidget::new(notifications_listener.sender(&cc.egui_ctx)));
+        widgets.register(CheckInWidget::new());
+        for config in load_script_configs() {
+            widgets.register(ScriptWidget::new(config));
+        }
+        
         Self {
             view_mode: ViewMode::Widgets,
-            task_manager: TaskManager::default(),
-            weather_widget: WeatherWidget::default(),
-            sunburst_widget: SunburstWidget::new(),
-            health_widget: HealthWidget::new(),
+            widgets,
             settings: Settings::default(),
             notifications_listener,
             notifications,
@@ -160,21 +176,10 @@ impl SideBar {
 
 fn render_widgets_view(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
     // Виджеты теперь рисуются через обычный layout, без абсолютного позиционирования
-    
-    // Sunburst widget (Activity Watch visualization)
-    //self.sunburst_widget.show_sunburst_widget(ui);
-    //ui.add_space(10.0);
-    
-    // Weather widget
-    //self.weather_widget.show_weather_widget(ui);
-    //ui.add_space(10.0);
-    
-    // Tasks widget
-    self.task_manager.show_tasks_widget(ui, ctx);
-    ui.add_space(10.0);
-    
-    //Health widgets (food + water)
-      self.health_widget.render(ui, ctx);
+    egui::ScrollArea::vertical()
+        .id_source("widgets_scroll")
+        .auto_shrink([false; 2])
+        .show(ui, |ui| self.widgets.render(ui, ctx));
 }
 
     fn render_notifications_view(&mut self, ui: &mut egui::Ui) {
@@ -291,26 +296,17 @@ fn render_widgets_view(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
 
     fn render_popups(&mut self, ctx: &egui::Context) {
         if self.settings.popup_open {
-            self.settings.create_settings_window(ctx);
-        }
-
-        if self.task_manager.new_task_popup {
-            self.task_manager.new_task_popup(ctx);
-        }
-
-        if self.task_manager.edit_task_popup {
-            self.task_manager.edit_task_popup(ctx);
+            self.settings.create_settings_window(ctx, &mut self.widgets);
         }
 
-        //if self.food_widget.calory_popup {
-            //self.food_widget.calory_popup(ctx);
-        //}
+        self.widgets.render_popups(ctx);
     }
 }
 
 impl eframe::App for SideBar {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         ctx.set_pixels_per_point(2.0);
+        self.widgets.tick(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.add_space(10.0);
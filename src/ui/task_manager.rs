@@ -4,6 +4,10 @@ use egui::{Frame, Key, TextEdit, Vec2, Window};
 use std::process::Command;
 use image::GenericImageView;
 use crate::ui::custom_vidgets::StyledImageButton;
+use crate::ui::widget_registry::SidebarWidget;
+
+const MIN_TASK_LIST_HEIGHT: f32 = 200.0;
+const TASK_LIST_SCREEN_SHARE: f32 = 0.5;
 
 pub(crate) struct TaskManager {
     pub tasks: Vec<Task>,
@@ -87,6 +91,14 @@ impl TaskManager {
             .expect("Failed to execute 'task' command");
     }
 
+    pub fn start_task(&mut self, task_id: i32) {
+        Command::new("task")
+            .arg(format!("{}", task_id))
+            .arg("start")
+            .output()
+            .expect("Failed to execute 'task' command");
+    }
+
     pub fn done_task(&mut self, task_id: i32) {
         Command::new("task")
             .arg("done")
@@ -284,11 +296,13 @@ impl TaskManager {
     }
 
     fn render_task_list(&mut self, ui: &mut egui::Ui) {
-        let available_height = ui.available_height().min(200.0);
+        // Внутри прокручиваемой панели высота не ограничена, поэтому считаем от окна;
+        // короткий список не занимает лишнего места
+        let max_height = (ui.ctx().screen_rect().height() * TASK_LIST_SCREEN_SHARE).max(MIN_TASK_LIST_HEIGHT);
         
         egui::ScrollArea::vertical()
-            .auto_shrink([false; 2])
-            .max_height(available_height)
+            .auto_shrink([false, true])
+            .max_height(max_height)
             .show(ui, |ui| {
                 let tasks = self.tasks.clone();
                 self.show_tasks(ui, &tasks);
@@ -434,6 +448,20 @@ impl TaskManager {
                 self.is_update = true;
             }
 
+            // Кнопка запуска (для привязки к Pomodoro)
+            if ui
+                .add(
+                    egui::Button::new("▶")
+                        .min_size(Vec2 { x: 24.0, y: 24.0 })
+                        .fill(parse_color_from_ini("button-color")),
+                )
+                .on_hover_text("Start task")
+                .clicked()
+            {
+                self.start_task(task.id);
+                self.is_update = true;
+            }
+
             // Кнопка завершения
             if ui
                 .add(
@@ -449,4 +477,32 @@ impl TaskManager {
             }
         });
     }
-}
\ No newline at end of file
+}
+
+impl SidebarWidget for TaskManager {
+    fn id(&self) -> &'static str {
+        "tasks"
+    }
+
+    fn title(&self) -> &'static str {
+        "Tasks"
+    }
+
+    fn render(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        self.show_tasks_widget(ui, ctx);
+    }
+
+    fn render_popups(&mut self, ctx: &egui::Context) {
+        if self.new_task_popup {
+            self.new_task_popup(ctx);
+        }
+
+        if self.edit_task_popup {
+            self.edit_task_popup(ctx);
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.is_update = true;
+    }
+}
@@ -1,112 +1,593 @@
 use crate::ui::color_parser::parse_color_from_ini;
-use crate::ui::widgets::weather_plugin::{get_weather, WeatherForecast};
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use egui::{FontId, Frame, Pos2, TextStyle, Ui, Vec2};
+use crate::ui::health_widget::NARROW_WINDOW_THRESHOLD;
+use crate::ui::i18n::{tr, weekday_short};
+use crate::ui::notifications_listener::NotificationSender;
+use crate::ui::settings::get_weather_refresh_minutes;
+use crate::ui::widgets::weather_cache::CachedForecast;
+use crate::ui::widgets::weather_locations::LocationStore;
+use crate::ui::widgets::weather_units::WeatherUnits;
+use crate::ui::widgets::weather_plugin::{
+    forecast_source_key, get_weather, WeatherAlert, WeatherEntry, WeatherForecast, HOURLY_HOURS,
+};
+use crate::ui::widget_registry::{SidebarWidget, WidgetLayout};
+use crate::ui::widget_settings::WeatherSettings;
+use chrono::{DateTime, Datelike, Duration, Local, Utc};
+use egui::{Color32, Frame, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+const FORECAST_DAYS: usize = 4;
+const DAY_CARD_WIDTH: f32 = 80.0;
+const HOURLY_CHART_HEIGHT: f32 = 130.0;
+const HOUR_LABEL_HEIGHT: f32 = 16.0;
+const TEMP_LABEL_HEIGHT: f32 = 14.0;
+const MAX_BACKOFF_MINUTES: i64 = 60;
+
+// Результат фоновой загрузки вместе с ключом места, для которого она запускалась
+type FetchResult = (String, Result<WeatherForecast, String>);
 
-#[derive(Default)]
 pub(crate) struct WeatherWidget {
     weather_forecast: Option<WeatherForecast>,
     update_time: DateTime<Utc>,
-    first_call: bool,
-    emoji_list: Vec<String>,
+    fetched_at: Option<i64>,
+    error: Option<String>,
+    pending: Arc<Mutex<Option<FetchResult>>>,
+    loading: bool,
+    failures: u32,
+    locations: LocationStore,
+    show_hourly: bool,
+    hourly_hours: i64,
+    notifier: NotificationSender,
+    // Предупреждения, о которых уже сообщили
+    notified_alerts: HashSet<String>,
+    settings: Option<WeatherSettings>,
 }
 
 impl WeatherWidget {
+    pub fn new(notifier: NotificationSender) -> Self {
+        let mut widget = Self {
+            weather_forecast: None,
+            update_time: DateTime::default(),
+            fetched_at: None,
+            error: None,
+            pending: Arc::new(Mutex::new(None)),
+            loading: false,
+            failures: 0,
+            locations: LocationStore::load(),
+            show_hourly: false,
+            hourly_hours: 24,
+            notifier,
+            notified_alerts: HashSet::new(),
+            settings: None,
+        };
+        widget.load_cached_forecast();
+        widget
+    }
+
+    // Показываем последний известный прогноз сразу, не дожидаясь сети
+    fn load_cached_forecast(&mut self) {
+        match CachedForecast::load(&forecast_source_key()) {
+            Some(cached) => {
+                // Уже показанные предупреждения не повторяем после перезапуска
+                self.notified_alerts
+                    .extend(cached.forecast.alerts.iter().map(|alert| alert.id.clone()));
+                // Битая метка времени в кэше просто вызывает обновление сразу
+                self.update_time = DateTime::from_timestamp(cached.fetched_at, 0)
+                    .and_then(|fetched| fetched.checked_add_signed(Duration::minutes(get_weather_refresh_minutes())))
+                    .unwrap_or_default();
+                self.set_forecast(cached.forecast, cached.fetched_at);
+            }
+            None => {
+                self.weather_forecast = None;
+                self.fetched_at = None;
+                self.update_time = DateTime::default();
+            }
+        }
+    }
+
+    fn set_forecast(&mut self, forecast: WeatherForecast, fetched_at: i64) {
+        self.weather_forecast = Some(forecast);
+        self.fetched_at = Some(fetched_at);
+    }
+
     fn get_day_of_week(&self, dt: i64) -> String {
-        let naive_datetime = NaiveDateTime::from_timestamp(dt, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive_datetime, Utc);
-        datetime.format("%A").to_string()
+        let datetime = DateTime::from_timestamp(dt, 0).unwrap_or_default().with_timezone(&Local);
+        weekday_short(datetime.weekday()).to_string()
     }
 
-    fn get_weather_emoji(&self, description: &str) -> String {
-        match description {
-            "clear sky" => "".to_string(),
-            "few clouds" => "".to_string(),
-            "scattered clouds" => "".to_string(),
-            "broken clouds" => "".to_string(),
-            "overcast clouds" => "".to_string(),
-            "shower rain" => "".to_string(),
-            "rain" => "".to_string(),
-            "thunderstorm" => "".to_string(),
-            "snow" => "".to_string(),
-            "mist" => "".to_string(),
-            _ => description.to_string(),
+    fn update_forecast_if_needed(&mut self, ctx: &egui::Context) {
+        // Места изменились в настройках — показываем их кэш и сразу перезапрашиваем
+        if LocationStore::take_changed() {
+            self.locations = LocationStore::load();
+            self.load_cached_forecast();
+            self.update_time = DateTime::default();
+            self.failures = 0;
+            self.error = None;
         }
+
+        self.take_fetch_result();
+
+        let now = Utc::now();
+        if self.loading || now < self.update_time {
+            return;
+        }
+        self.loading = true;
+
+        let pending = Arc::clone(&self.pending);
+        let ctx = ctx.clone();
+
+        // Запрос к провайдеру не должен блокировать отрисовку
+        std::thread::spawn(move || {
+            let source_key = forecast_source_key();
+            let result = get_weather().map_err(|err| err.to_string());
+
+            if let Ok(mut pending) = pending.lock() {
+                *pending = Some((source_key, result));
+            }
+            ctx.request_repaint();
+        });
     }
 
-    pub fn show_weather_widget(&mut self, ui: &mut Ui) {
+    fn take_fetch_result(&mut self) {
+        let Some((source_key, result)) = self.pending.lock().ok().and_then(|mut pending| pending.take()) else {
+            return;
+        };
+        self.loading = false;
+
         let now = Utc::now();
 
-        if self.first_call || now >= self.update_time {
-            match get_weather() {
-                Ok(weather_forecast) => {
-                    self.weather_forecast = Some(weather_forecast);
-                    self.emoji_list = self
-                        .weather_forecast
-                        .as_ref()
-                        .unwrap()
-                        .list
-                        .iter()
-                        .map(|entry| self.get_weather_emoji(&entry.weather[0].description))
-                        .collect();
-                }
-                Err(err) => {
-                    println!("Error fetching weather data: {}", err);
+        // Место сменилось во время запроса — ответ устарел
+        if source_key != forecast_source_key() {
+            self.update_time = DateTime::default();
+            return;
+        }
+
+        match result {
+            Ok(forecast) => {
+                CachedForecast {
+                    source_key,
+                    fetched_at: now.timestamp(),
+                    forecast: forecast.clone(),
                 }
+                .save();
+
+                self.notify_alerts(&forecast.alerts);
+                self.set_forecast(forecast, now.timestamp());
+                self.failures = 0;
+                self.error = None;
+                self.update_time = now + Duration::minutes(get_weather_refresh_minutes());
+            }
+            Err(err) => {
+                println!("Error fetching weather data: {}", err);
+                self.failures += 1;
+                self.error = Some(err);
+                self.update_time = now + self.retry_delay();
             }
-            self.update_time = now + Duration::minutes(5);
-            self.first_call = false;
         }
+    }
 
-        if let Some(weather_forecast) = &self.weather_forecast {
-            let container_size = Vec2::new(438.0, 50.0);
-            let frame = Frame {
-                fill: parse_color_from_ini("frame-background"),
-                stroke: egui::Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
-                rounding: egui::Rounding::same(2.0),
-                ..Default::default()
+    // Экспоненциальная задержка после ошибок, но не чаще интервала обновления
+    fn retry_delay(&self) -> Duration {
+        let refresh = get_weather_refresh_minutes();
+        let exponent = self.failures.saturating_sub(1).min(10);
+        let minutes = refresh
+            .saturating_mul(1_i64.checked_shl(exponent).unwrap_or(i64::MAX))
+            .min(MAX_BACKOFF_MINUTES.max(refresh));
+        Duration::minutes(minutes)
+    }
+
+    fn notify_alerts(&mut self, alerts: &[WeatherAlert]) {
+        for alert in alerts {
+            if self.notified_alerts.insert(alert.id.clone()) {
+                self.notifier
+                    .send("SideBar", &format!("⚠ {}", alert.event), &alert.description);
+            }
+        }
+    }
+
+    fn create_card_frame(&self) -> Frame {
+        Frame {
+            fill: parse_color_from_ini("button-color").linear_multiply(0.3),
+            rounding: egui::Rounding::same(6.0),
+            inner_margin: egui::Margin::same(6.0),
+            ..Default::default()
+        }
+    }
+
+    pub fn show_weather_widget(&mut self, ui: &mut Ui) {
+        let frame = Frame {
+            fill: parse_color_from_ini("frame-background"),
+            stroke: egui::Stroke::new(1.0, parse_color_from_ini("frame-border-color")),
+            rounding: egui::Rounding::same(8.0),
+            inner_margin: egui::Margin::same(15.0),
+            ..Default::default()
+        };
+
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(format!("🌤 {}", tr("Weather")));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.render_location_picker(ui);
+                });
+            });
+            ui.add_space(5.0);
+
+            let current = self
+                .weather_forecast
+                .as_ref()
+                .and_then(|forecast| forecast.list.first());
+
+            let Some(current) = current else {
+                let message = match &self.error {
+                    Some(error) => format!("{}: {}", tr("Weather unavailable"), error),
+                    None => tr("Loading weather..."),
+                };
+                ui.label(egui::RichText::new(message).size(12.0).color(egui::Color32::GRAY));
+                return;
             };
 
-            let custom_text_style = TextStyle::Name("custom".into());
-            ui.style_mut().text_styles.insert(
-                custom_text_style.clone(),
-                FontId::new(10.0, egui::FontFamily::Proportional),
-            );
+            self.render_current(ui, current);
+            self.render_age(ui);
+            self.render_alerts(ui);
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            self.render_days(ui);
+
+            ui.add_space(5.0);
+            self.render_hourly(ui);
+        });
+    }
+
+    // Возраст данных; при ошибке загрузки показываем, что прогноз устарел
+    fn render_age(&self, ui: &mut Ui) {
+        let Some(fetched_at) = self.fetched_at else {
+            return;
+        };
+
+        let age = format_age(Utc::now().timestamp() - fetched_at);
+        let (text, color) = match &self.error {
+            Some(_) => (
+                format!("⚠ {} {}", tr("Offline, data from"), age),
+                Color32::from_rgb(220, 150, 60),
+            ),
+            None => (format!("{} {}", tr("Updated"), age), Color32::GRAY),
+        };
+
+        ui.add_space(3.0);
+        let label = ui.label(egui::RichText::new(text).size(11.0).color(color));
+        if let Some(error) = &self.error {
+            label.on_hover_text(error);
+        }
+    }
 
-            let rect = egui::Rect::from_min_size(Pos2::new(7.0, 250.0), container_size);
-            ui.allocate_ui_at_rect(rect, |ui| {
-                frame.show(ui, |ui| {
-                    ui.allocate_space(Vec2::new(438.0, 5.0));
-
-                    for (i, entry) in weather_forecast.list.iter().take(4).enumerate() {
-                        let rect1 = egui::Rect::from_min_size(
-                            Pos2::new(14.0 + i as f32 * 110.0, 260.0),
-                            Vec2::new(90.0, 40.0),
-                        );
-                        ui.allocate_ui_at_rect(rect1, |ui| {
-                            frame.show(ui, |ui| {
-                                ui.allocate_space(Vec2::new(90.0, 3.0));
-                                ui.vertical(|ui| {
-                                    ui.with_layout(
-                                        egui::Layout::top_down(egui::Align::Center),
-                                        |ui| {
-                                            let day_of_week = self.get_day_of_week(entry.dt);
-                                            ui.label(format!("{}:", day_of_week));
-                                            ui.label(format!("{}", self.emoji_list[i]));
-                                            ui.label(format!(": {}°C", entry.main.temp as i32));
-                                            ui.label(format!(": {}%", entry.main.humidity));
-                                            ui.label(format!(": {}мм", entry.main.pressure));
-                                            ui.label(format!(": {}м/с", entry.wind.speed));
-                                        },
-                                    );
-                                });
-                                ui.allocate_space(Vec2::new(0.0, 3.0));
-                            });
-                        });
-                    }
-                    ui.allocate_space(Vec2::new(438.0, 5.0));
+    fn render_alerts(&self, ui: &mut Ui) {
+        let Some(forecast) = &self.weather_forecast else {
+            return;
+        };
+
+        for alert in &forecast.alerts {
+            ui.add_space(5.0);
+            let local = format_weekday_time;
+            let mut text = format!("⚠ {}", alert.event);
+            if alert.start > Utc::now().timestamp() {
+                text.push_str(&format!(" ({} {})", tr("from"), local(alert.start)));
+            } else if alert.end > 0 {
+                text.push_str(&format!(" ({} {})", tr("until"), local(alert.end)));
+            }
+            ui.label(
+                egui::RichText::new(text)
+                    .size(12.0)
+                    .strong()
+                    .color(Color32::from_rgb(220, 90, 60)),
+            )
+            .on_hover_text(&alert.description);
+        }
+    }
+
+    fn render_hourly(&mut self, ui: &mut Ui) {
+        let has_hourly = self
+            .weather_forecast
+            .as_ref()
+            .is_some_and(|forecast| forecast.hourly.len() > 1);
+        if !has_hourly {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let arrow = if self.show_hourly { "⏶" } else { "⏷" };
+            if ui
+                .add(egui::Button::new(format!("{} {}", arrow, tr("Hourly"))).frame(false))
+                .clicked()
+            {
+                self.show_hourly = !self.show_hourly;
+            }
+
+            if self.show_hourly {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.selectable_value(&mut self.hourly_hours, HOURLY_HOURS, format!("{}h", HOURLY_HOURS));
+                    ui.selectable_value(&mut self.hourly_hours, 24, "24h");
                 });
+            }
+        });
+
+        if !self.show_hourly {
+            return;
+        }
+
+        if let Some(forecast) = &self.weather_forecast {
+            // Из кэша могли остаться уже прошедшие часы
+            let now = Utc::now().timestamp();
+            let end = now + self.hourly_hours * 3600;
+            let entries: Vec<&WeatherEntry> = forecast
+                .hourly
+                .iter()
+                .filter(|entry| entry.dt + 3600 >= now && entry.dt <= end)
+                .collect();
+            render_hourly_chart(ui, &entries);
+        }
+    }
+
+    fn render_location_picker(&mut self, ui: &mut Ui) {
+        if self.locations.locations.len() < 2 {
+            return;
+        }
+
+        let mut active = self.locations.active;
+        let selected = self
+            .locations
+            .active_location()
+            .map(|location| location.name.clone())
+            .unwrap_or_default();
+
+        egui::ComboBox::from_id_source("weather_location")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for (index, location) in self.locations.locations.iter().enumerate() {
+                    ui.selectable_value(&mut active, index, &location.name);
+                }
             });
+
+        if active != self.locations.active {
+            self.locations.active = active;
+            self.locations.save();
+        }
+    }
+
+    fn render_current(&self, ui: &mut Ui, entry: &WeatherEntry) {
+        let units = WeatherUnits::current();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(entry.condition.icon(entry.is_day)).size(32.0));
+            ui.add_space(5.0);
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(units.temperature(entry.temp))
+                        .size(22.0)
+                        .strong(),
+                );
+                ui.label(tr(&entry.description));
+            });
+        });
+
+        ui.add_space(5.0);
+
+        // Подробности переносятся на новую строку в узком окне
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!(": {}%", entry.humidity.round() as i32));
+            ui.add_space(10.0);
+            ui.label(format!(": {}", units.pressure(entry.pressure)));
+            ui.add_space(10.0);
+            ui.label(format!(": {}", units.wind(entry.wind_speed)));
+        });
+    }
+
+    fn render_day_card(&self, ui: &mut Ui, entry: &WeatherEntry) {
+        let units = WeatherUnits::current();
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(self.get_day_of_week(entry.dt)).strong());
+            ui.label(egui::RichText::new(entry.condition.icon(entry.is_day)).size(20.0));
+            ui.label(format!(": {}", units.temperature(entry.temp)));
+            ui.label(
+                egui::RichText::new(format!(": {}", units.wind(entry.wind_speed)))
+                    .size(12.0),
+            );
+        });
+    }
+
+    fn render_days(&self, ui: &mut Ui) {
+        let Some(forecast) = &self.weather_forecast else {
+            return;
+        };
+
+        let days: Vec<&WeatherEntry> = forecast.list.iter().take(FORECAST_DAYS).collect();
+
+        if days.is_empty() {
+            return;
+        }
+
+        let card_frame = self.create_card_frame();
+
+        if ui.available_width() >= NARROW_WINDOW_THRESHOLD {
+            ui.columns(days.len(), |columns| {
+                for (column, entry) in columns.iter_mut().zip(days) {
+                    card_frame.show(column, |ui| {
+                        ui.set_width(ui.available_width());
+                        self.render_day_card(ui, entry);
+                    });
+                }
+            });
+        } else {
+            ui.horizontal_wrapped(|ui| {
+                for entry in days {
+                    card_frame.show(ui, |ui| {
+                        ui.set_width(DAY_CARD_WIDTH);
+                        self.render_day_card(ui, entry);
+                    });
+                }
+            });
+        }
+    }
+}
+
+impl SidebarWidget for WeatherWidget {
+    fn id(&self) -> &'static str {
+        "weather"
+    }
+
+    fn title(&self) -> &'static str {
+        "Weather"
+    }
+
+    fn render(&mut self, ui: &mut Ui, _ctx: &egui::Context) {
+        self.show_weather_widget(ui);
+    }
+
+    // Предупреждения приходят и в свёрнутый виджет
+    fn tick(&mut self, ctx: &egui::Context) {
+        if WidgetLayout::current().is_enabled(self.id()) {
+            self.update_forecast_if_needed(ctx);
+        }
+    }
+
+    fn has_settings(&self) -> bool {
+        true
+    }
+
+    // Форма правит те же сохранённые места, что и выбор места в заголовке
+    fn settings_ui(&mut self, ui: &mut Ui) {
+        self.settings
+            .get_or_insert_with(WeatherSettings::load)
+            .ui(ui, &mut self.locations);
+    }
+
+    // Запрос уйдёт на следующем кадре; уже идущий не дублируется
+    fn refresh(&mut self) {
+        self.update_time = DateTime::default();
+    }
+}
+
+fn format_weekday_time(dt: i64) -> String {
+    let time = DateTime::from_timestamp(dt, 0).unwrap_or_default().with_timezone(&Local);
+    format!("{} {}", weekday_short(time.weekday()), time.format("%H:%M"))
+}
+
+fn format_age(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    match minutes {
+        0 => tr("just now"),
+        1..=59 => format!("{} {}", minutes, tr("min ago")),
+        60..=1439 => format!("{} {}", minutes / 60, tr("h ago")),
+        _ => format!("{} {}", minutes / 1440, tr("d ago")),
+    }
+}
+
+// Линия температуры поверх столбиков вероятности осадков
+fn render_hourly_chart(ui: &mut Ui, entries: &[&WeatherEntry]) {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return;
+    };
+    if last.dt <= first.dt {
+        return;
+    }
+
+    let units = WeatherUnits::current();
+    let width = ui.available_width();
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(width, HOURLY_CHART_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let plot = Rect::from_min_max(
+        Pos2::new(rect.left() + 4.0, rect.top() + TEMP_LABEL_HEIGHT),
+        Pos2::new(rect.right() - 4.0, rect.bottom() - HOUR_LABEL_HEIGHT),
+    );
+
+    let span = (last.dt - first.dt) as f32;
+    let x_of = |dt: i64| plot.left() + plot.width() * (dt - first.dt) as f32 / span;
+
+    let min_temp = entries.iter().map(|e| e.temp).fold(f64::INFINITY, f64::min);
+    let max_temp = entries.iter().map(|e| e.temp).fold(f64::NEG_INFINITY, f64::max);
+    let temp_range = (max_temp - min_temp).max(1.0);
+    // Температура занимает верхние две трети, чтобы не сливаться со столбиками
+    let y_of = |temp: f64| {
+        plot.bottom() - plot.height() * (0.3 + 0.65 * ((temp - min_temp) / temp_range) as f32)
+    };
+
+    let text_color = ui.visuals().text_color();
+    let grid = Stroke::new(1.0, parse_color_from_ini("frame-border-color").linear_multiply(0.5));
+    painter.line_segment([plot.left_bottom(), plot.right_bottom()], grid);
+
+    // Столбики вероятности осадков
+    let step = plot.width() / (entries.len() - 1) as f32;
+    let bar_width = (step * 0.6).clamp(2.0, 14.0);
+    let rain_color = Color32::from_rgb(90, 150, 220);
+    for entry in entries {
+        let Some(chance) = entry.precipitation_chance.filter(|chance| *chance > 0.0) else {
+            continue;
+        };
+        let x = x_of(entry.dt);
+        let height = plot.height() * (chance.min(100.0) / 100.0) as f32;
+        let bar = Rect::from_min_max(
+            Pos2::new(x - bar_width / 2.0, plot.bottom() - height),
+            Pos2::new(x + bar_width / 2.0, plot.bottom()),
+        );
+        painter.rect_filled(bar, 2.0, rain_color.linear_multiply(0.5));
+    }
+
+    let points: Vec<Pos2> = entries
+        .iter()
+        .map(|entry| Pos2::new(x_of(entry.dt), y_of(entry.temp)))
+        .collect();
+    let temp_color = Color32::from_rgb(230, 150, 60);
+    painter.add(Shape::line(points.clone(), Stroke::new(2.0, temp_color)));
+
+    // Подписи минимума и максимума температуры
+    let font = egui::FontId::proportional(11.0);
+    for target in [max_temp, min_temp] {
+        if let Some(point) = entries.iter().zip(&points).find(|(e, _)| e.temp == target).map(|(_, p)| *p) {
+            painter.text(
+                point - Vec2::new(0.0, 4.0),
+                egui::Align2::CENTER_BOTTOM,
+                units.temperature_short(target),
+                font.clone(),
+                text_color,
+            );
+        }
+    }
+
+    // Подписи часов не чаще, чем раз в 40 пикселей
+    let label_every = ((40.0 / step).ceil() as usize).max(1);
+    for entry in entries.iter().step_by(label_every) {
+        let time = DateTime::from_timestamp(entry.dt, 0).unwrap_or_default().with_timezone(&Local);
+        painter.text(
+            Pos2::new(x_of(entry.dt), rect.bottom()),
+            egui::Align2::CENTER_BOTTOM,
+            time.format("%H").to_string(),
+            font.clone(),
+            Color32::GRAY,
+        );
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let nearest = entries
+            .iter()
+            .zip(&points)
+            .min_by(|(_, a), (_, b)| (a.x - pointer.x).abs().total_cmp(&(b.x - pointer.x).abs()));
+
+        if let Some((entry, point)) = nearest {
+            painter.line_segment([Pos2::new(point.x, plot.top()), Pos2::new(point.x, plot.bottom())], grid);
+            painter.circle_filled(*point, 3.5, temp_color);
+
+            let mut text = format!(
+                "{}\n{}, {}",
+                format_weekday_time(entry.dt),
+                units.temperature(entry.temp),
+                tr(&entry.description)
+            );
+            if let Some(chance) = entry.precipitation_chance {
+                text.push_str(&format!("\n{}: {}%", tr("Precipitation"), chance.round() as i32));
+            }
+            response.on_hover_text_at_pointer(text);
         }
     }
 }
@@ -0,0 +1,451 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::data_store::{DataStore, Result};
+use crate::ui::food_diary::{current_day, FoodDiary};
+use crate::ui::health_export::data_version;
+use crate::ui::settings::{get_daily_calorie_goal, get_goal_weight, set_health_setting};
+use chrono::{Duration, NaiveDate};
+use egui::{Color32, Pos2, Sense, Shape, Stroke, TextEdit, Ui, Vec2};
+
+const TREND_DAYS: i64 = 7; // Окно скользящего среднего
+const CHART_DAYS: i64 = 60;
+const CHART_HEIGHT: f32 = 90.0;
+const TDEE_WINDOW_DAYS: i64 = 28;
+const TDEE_MIN_SPAN_DAYS: i64 = 10;
+const TDEE_MIN_FOOD_DAYS: usize = 7;
+const KCAL_PER_KG: f32 = 7700.0;
+const MIN_SUGGESTED_KCAL: f32 = 1200.0;
+
+// Одно взвешивание в день; жир в процентах, талия в сантиметрах
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyMetrics {
+    pub date: String, // "%Y-%m-%d"
+    pub weight_kg: f32,
+    pub body_fat_pct: Option<f32>,
+    pub waist_cm: Option<f32>,
+}
+
+impl BodyMetrics {
+    fn naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TdeeEstimate {
+    pub tdee: f32,
+    pub kg_per_week: f32,
+    pub days: i64,
+}
+
+// Среднее по взвешиваниям за последние TREND_DAYS дней до каждой даты
+pub fn moving_average(points: &[(NaiveDate, f32)]) -> Vec<(NaiveDate, f32)> {
+    points
+        .iter()
+        .map(|(date, _)| {
+            let window: Vec<f32> = points
+                .iter()
+                .filter(|(other, _)| other <= date && *date - *other < Duration::days(TREND_DAYS))
+                .map(|(_, kg)| *kg)
+                .collect();
+            (*date, window.iter().sum::<f32>() / window.len() as f32)
+        })
+        .collect()
+}
+
+// Расход = среднее потребление минус изменение тренда в калориях.
+// Дни без записей еды не считаются нулевыми, а пропускаются
+pub fn estimate_tdee(trend: &[(NaiveDate, f32)], kcal: &[(NaiveDate, f32)], today: NaiveDate) -> Option<TdeeEstimate> {
+    let recent: Vec<&(NaiveDate, f32)> = trend
+        .iter()
+        .filter(|(date, _)| today - *date <= Duration::days(TDEE_WINDOW_DAYS))
+        .collect();
+    let (first, last) = (recent.first()?, recent.last()?);
+    let days = (last.0 - first.0).num_days();
+    if days < TDEE_MIN_SPAN_DAYS {
+        return None;
+    }
+
+    let logged: Vec<f32> = kcal
+        .iter()
+        .filter(|(date, value)| *date >= first.0 && *date < last.0 && *value > 0.0)
+        .map(|(_, value)| *value)
+        .collect();
+    if logged.len() < TDEE_MIN_FOOD_DAYS {
+        return None;
+    }
+
+    let intake = logged.iter().sum::<f32>() / logged.len() as f32;
+    let kg_per_day = (last.1 - first.1) / days as f32;
+    Some(TdeeEstimate {
+        tdee: intake - kg_per_day * KCAL_PER_KG,
+        kg_per_week: kg_per_day * 7.0,
+        days,
+    })
+}
+
+// Около 0.5 кг в неделю к цели, без цели — поддержание
+pub fn suggested_calorie_goal(tdee: f32, trend_kg: f32, goal_kg: Option<f32>) -> i32 {
+    let adjustment = match goal_kg {
+        Some(goal) if trend_kg - goal > 0.5 => -500.0,
+        Some(goal) if goal - trend_kg > 0.5 => 300.0,
+        _ => 0.0,
+    };
+    let kcal = (tdee + adjustment).max(MIN_SUGGESTED_KCAL);
+    ((kcal / 50.0).round() * 50.0) as i32
+}
+
+#[derive(Default)]
+pub struct WeightWidget {
+    store: Option<DataStore>,
+    loaded: Option<(NaiveDate, u64)>,
+    entries: Vec<BodyMetrics>,
+    trend: Vec<(NaiveDate, f32)>,
+    kcal: Vec<(NaiveDate, f32)>, // Калории за прошедшие дни окна TDEE
+    weight_input: String,
+    body_fat_input: String,
+    waist_input: String,
+    error: Option<String>,
+}
+
+impl WeightWidget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn refresh(&mut self) {
+        self.loaded = None;
+    }
+
+    fn ensure_loaded(&mut self) -> Result<()> {
+        if self.store.is_none() {
+            self.store = Some(DataStore::open()?);
+        }
+
+        let today = current_day();
+        if self.loaded != Some((today, data_version())) {
+            self.reload(today)?;
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self, today: NaiveDate) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        self.entries = store.body_metrics()?;
+
+        let points: Vec<(NaiveDate, f32)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry.naive_date()?, entry.weight_kg)))
+            .collect();
+        self.trend = moving_average(&points);
+
+        let diary = FoodDiary::open()?;
+        self.kcal.clear();
+        for offset in (1..=TDEE_WINDOW_DAYS).rev() {
+            let date = today - Duration::days(offset);
+            self.kcal.push((date, diary.day_totals(date)?.kcal as f32));
+        }
+
+        // Поля ввода показывают сегодняшнее взвешивание, если оно есть
+        let today_key = FoodDiary::date_key(today);
+        let existing = self.entries.iter().find(|entry| entry.date == today_key);
+        let optional = |value: Option<f32>| value.map(|value| value.to_string()).unwrap_or_default();
+        self.weight_input = existing.map(|entry| entry.weight_kg.to_string()).unwrap_or_default();
+        self.body_fat_input = optional(existing.and_then(|entry| entry.body_fat_pct));
+        self.waist_input = optional(existing.and_then(|entry| entry.waist_cm));
+
+        self.loaded = Some((today, data_version()));
+        Ok(())
+    }
+
+    fn save_today(&mut self) -> std::result::Result<(), String> {
+        let parse = |value: &str, name: &str| -> std::result::Result<Option<f32>, String> {
+            match value.trim().replace(',', ".").as_str() {
+                "" => Ok(None),
+                value => value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|value| *value > 0.0)
+                    .map(Some)
+                    .ok_or_else(|| format!("Invalid {}", name)),
+            }
+        };
+
+        let weight_kg = parse(&self.weight_input, "weight")?.ok_or("Enter your weight")?;
+        let metrics = BodyMetrics {
+            date: FoodDiary::today_key(),
+            weight_kg,
+            body_fat_pct: parse(&self.body_fat_input, "body fat")?,
+            waist_cm: parse(&self.waist_input, "waist")?,
+        };
+
+        if let Some(store) = &self.store {
+            store.set_body_metrics(&metrics).map_err(|e| e.to_string())?;
+        }
+        self.loaded = None;
+        Ok(())
+    }
+
+    fn remove(&mut self, date: &str) {
+        if let Some(store) = &self.store {
+            let _ = store.remove_body_metrics(date);
+        }
+        self.loaded = None;
+    }
+
+    pub fn render(&mut self, ui: &mut Ui) -> Result<()> {
+        self.ensure_loaded()?;
+        let button_color = parse_color_from_ini("button-color");
+        let goal = get_goal_weight();
+        let today = current_day();
+
+        ui.heading("⚖ Weight");
+        ui.add_space(5.0);
+
+        if let (Some(latest), Some((_, trend))) = (self.entries.last(), self.trend.last()) {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(format!("Last: {:.1} kg", latest.weight_kg));
+                ui.label(
+                    egui::RichText::new(format!("trend {:.1} kg", trend))
+                        .color(Color32::GRAY),
+                );
+                if let Some(fat) = latest.body_fat_pct {
+                    ui.label(format!("· {:.1}% fat", fat));
+                }
+                if let Some(waist) = latest.waist_cm {
+                    ui.label(format!("· {:.0} cm waist", waist));
+                }
+            });
+            if let Some(goal) = goal {
+                let left = trend - goal;
+                let text = if left.abs() < 0.1 {
+                    format!("Goal: {:.1} kg · reached", goal)
+                } else {
+                    format!("Goal: {:.1} kg · {:.1} kg to go", goal, left.abs())
+                };
+                ui.label(text);
+            }
+        }
+        ui.add_space(5.0);
+
+        let mut save = false;
+        ui.horizontal_wrapped(|ui| {
+            for (value, hint, width) in [
+                (&mut self.weight_input, "kg", 60.0),
+                (&mut self.body_fat_input, "fat %", 50.0),
+                (&mut self.waist_input, "waist cm", 65.0),
+            ] {
+                let response = ui.add(TextEdit::singleline(value).desired_width(width).hint_text(hint));
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    save = true;
+                }
+            }
+            if ui.add(egui::Button::new("💾 Save").fill(button_color)).clicked() {
+                save = true;
+            }
+        });
+        if save {
+            self.error = self.save_today().err();
+        }
+        if let Some(error) = &self.error {
+            ui.label(egui::RichText::new(error).size(11.0).color(Color32::from_rgb(200, 60, 60)));
+        }
+
+        if !self.entries.is_empty() {
+            ui.add_space(5.0);
+            self.render_chart(ui, today, goal);
+        }
+
+        ui.add_space(5.0);
+        self.render_tdee(ui, today, goal);
+        self.render_entries(ui);
+
+        Ok(())
+    }
+
+    fn render_tdee(&self, ui: &mut Ui, today: NaiveDate, goal: Option<f32>) {
+        let Some(estimate) = estimate_tdee(&self.trend, &self.kcal, today) else {
+            ui.label(
+                egui::RichText::new("Log weight and food for two weeks to estimate your TDEE")
+                    .size(11.0)
+                    .color(Color32::GRAY),
+            );
+            return;
+        };
+
+        ui.label(format!(
+            "Estimated TDEE: {:.0} kcal/day ({:+.2} kg/week over {} days)",
+            estimate.tdee, estimate.kg_per_week, estimate.days
+        ));
+
+        let trend = self.trend.last().map(|(_, kg)| *kg).unwrap_or_default();
+        let suggested = suggested_calorie_goal(estimate.tdee, trend, goal);
+        let current = get_daily_calorie_goal();
+        ui.horizontal(|ui| {
+            ui.label(format!("Suggested goal: {} kcal", suggested));
+            if suggested != current
+                && ui
+                    .small_button("Use")
+                    .on_hover_text(format!("Replace the current goal of {} kcal", current))
+                    .clicked()
+            {
+                set_health_setting("daily_calorie_goal", &suggested.to_string());
+            }
+        });
+    }
+
+    fn render_entries(&mut self, ui: &mut Ui) {
+        let mut remove = None;
+        egui::CollapsingHeader::new(format!("Entries ({})", self.entries.len()))
+            .id_source("weight_entries")
+            .show(ui, |ui| {
+                for entry in self.entries.iter().rev().take(30) {
+                    ui.horizontal(|ui| {
+                        let date = entry
+                            .naive_date()
+                            .map(|date| date.format("%d.%m.%Y").to_string())
+                            .unwrap_or_else(|| entry.date.clone());
+                        ui.label(egui::RichText::new(date).size(11.0).color(Color32::GRAY));
+                        ui.label(format!("{:.1} kg", entry.weight_kg));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("✕").on_hover_text("Remove").clicked() {
+                                remove = Some(entry.date.clone());
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(date) = remove {
+            self.remove(&date);
+        }
+    }
+
+    // Точки — взвешивания, линия — скользящее среднее, пунктир — цель
+    fn render_chart(&self, ui: &mut Ui, today: NaiveDate, goal: Option<f32>) {
+        let start = today - Duration::days(CHART_DAYS - 1);
+        let points: Vec<(NaiveDate, f32)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry.naive_date()?, entry.weight_kg)))
+            .filter(|(date, _)| *date >= start)
+            .collect();
+        if points.is_empty() {
+            return;
+        }
+        let trend: Vec<&(NaiveDate, f32)> = self.trend.iter().filter(|(date, _)| *date >= start).collect();
+
+        let width = ui.available_width();
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(width, CHART_HEIGHT), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let plot = rect.shrink(4.0);
+
+        let values = points.iter().map(|(_, kg)| *kg).chain(goal);
+        let (min, max) = values.fold((f32::MAX, f32::MIN), |(min, max), kg| (min.min(kg), max.max(kg)));
+        let (min, max) = (min - 0.5, max + 0.5);
+
+        let x_of = |date: NaiveDate| {
+            plot.left() + plot.width() * (date - start).num_days() as f32 / (CHART_DAYS - 1) as f32
+        };
+        let y_of = |kg: f32| plot.bottom() - plot.height() * (kg - min) / (max - min);
+
+        let grid = Stroke::new(1.0, parse_color_from_ini("frame-border-color").linear_multiply(0.5));
+        painter.line_segment([plot.left_bottom(), plot.right_bottom()], grid);
+
+        if let Some(goal) = goal {
+            let y = y_of(goal);
+            painter.extend(Shape::dashed_line(
+                &[Pos2::new(plot.left(), y), Pos2::new(plot.right(), y)],
+                Stroke::new(1.0, ui.visuals().text_color().linear_multiply(0.6)),
+                4.0,
+                3.0,
+            ));
+        }
+
+        let color = parse_color_from_ini("button-color");
+        let line: Vec<Pos2> = trend.iter().map(|(date, kg)| Pos2::new(x_of(*date), y_of(*kg))).collect();
+        if line.len() > 1 {
+            painter.add(Shape::line(line, Stroke::new(2.0, color)));
+        }
+        for (date, kg) in &points {
+            painter.circle_filled(Pos2::new(x_of(*date), y_of(*kg)), 2.5, color.linear_multiply(0.6));
+        }
+
+        // Подсказка для ближайшего по горизонтали взвешивания
+        if let Some(pointer) = response.hover_pos() {
+            let nearest = points
+                .iter()
+                .min_by(|a, b| (x_of(a.0) - pointer.x).abs().total_cmp(&(x_of(b.0) - pointer.x).abs()));
+            if let Some((date, kg)) = nearest {
+                let trend_kg = self.trend.iter().find(|(other, _)| other == date).map(|(_, kg)| *kg);
+                painter.circle_stroke(Pos2::new(x_of(*date), y_of(*kg)), 4.0, Stroke::new(1.0, color));
+                let mut text = format!("{}\n{:.1} kg", date.format("%a %d.%m"), kg);
+                if let Some(trend_kg) = trend_kg {
+                    text.push_str(&format!(" · trend {:.1}", trend_kg));
+                }
+                response.on_hover_text_at_pointer(text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap() + Duration::days(offset)
+    }
+
+    #[test]
+    fn moving_average_uses_the_last_week_only() {
+        let points = [(day(0), 80.0), (day(3), 82.0), (day(7), 84.0), (day(10), 90.0)];
+        let average = moving_average(&points);
+
+        assert_eq!(average[0], (day(0), 80.0));
+        assert_eq!(average[1], (day(3), 81.0));
+        // day(0) уже выпал из окна в 7 дней
+        assert_eq!(average[2], (day(7), 83.0));
+        assert_eq!(average[3], (day(10), 87.0));
+        assert!(moving_average(&[]).is_empty());
+    }
+
+    #[test]
+    fn tdee_comes_from_intake_and_trend_change() {
+        // Минус килограмм за две недели при 2000 ккал в день
+        let trend: Vec<_> = (0..=14).map(|offset| (day(offset), 80.0 - offset as f32 / 14.0)).collect();
+        let kcal: Vec<_> = (0..14).map(|offset| (day(offset), 2000.0)).collect();
+
+        let estimate = estimate_tdee(&trend, &kcal, day(14)).unwrap();
+        assert_eq!(estimate.days, 14);
+        assert!((estimate.tdee - 2550.0).abs() < 1.0);
+        assert!((estimate.kg_per_week + 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn tdee_needs_enough_history() {
+        let trend: Vec<_> = (0..=14).map(|offset| (day(offset), 80.0)).collect();
+        let kcal: Vec<_> = (0..14).map(|offset| (day(offset), 2000.0)).collect();
+
+        // Слишком короткий отрезок взвешиваний
+        assert!(estimate_tdee(&trend[..8], &kcal, day(7)).is_none());
+        // Дни без еды не считаются нулевыми, а их слишком мало
+        let sparse: Vec<_> = kcal.iter().copied().filter(|(date, _)| date.day() % 3 == 0).collect();
+        assert!(estimate_tdee(&trend, &sparse, day(14)).is_none());
+        // Старые взвешивания вне окна не учитываются
+        assert!(estimate_tdee(&trend, &kcal, day(14 + TDEE_WINDOW_DAYS)).is_none());
+        assert!(estimate_tdee(&trend, &kcal, day(14)).is_some());
+    }
+
+    #[test]
+    fn calorie_goal_follows_the_weight_goal() {
+        assert_eq!(suggested_calorie_goal(2510.0, 80.0, None), 2500);
+        assert_eq!(suggested_calorie_goal(2500.0, 80.0, Some(80.3)), 2500);
+        assert_eq!(suggested_calorie_goal(2500.0, 80.0, Some(75.0)), 2000);
+        assert_eq!(suggested_calorie_goal(2500.0, 70.0, Some(75.0)), 2800);
+        assert_eq!(suggested_calorie_goal(1400.0, 80.0, Some(70.0)), MIN_SUGGESTED_KCAL as i32);
+    }
+}
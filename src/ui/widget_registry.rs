@@ -0,0 +1,418 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::health_widget::NARROW_WINDOW_THRESHOLD;
+use crate::ui::settings::{get_widget_setting, set_widget_setting};
+use egui::{Color32, Context, DragAndDrop, Sense, Stroke, Ui, Vec2};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+const WIDGET_SPACING: f32 = 10.0;
+const DROP_ZONE_HEIGHT: f32 = 40.0;
+
+// Общий интерфейс виджетов панели
+pub trait SidebarWidget {
+    // Постоянный ключ для settings.ini, не меняется при переименовании
+    fn id(&self) -> &'static str;
+
+    fn title(&self) -> &'static str;
+
+    fn render(&mut self, ui: &mut Ui, ctx: &Context);
+
+    // Окна виджета рисуются после панели, поверх остальных
+    fn render_popups(&mut self, _ctx: &Context) {}
+
+    // Таймеры, напоминания и фоновые обновления. Вызывается каждый кадр, даже когда
+    // виджет свёрнут или выключен: запущенный таймер должен доработать. Кому без показа
+    // делать нечего, сами проверяют WidgetLayout::is_enabled
+    fn tick(&mut self, _ctx: &Context) {}
+
+    // Собственные настройки в разделе Widgets окна настроек
+    fn has_settings(&self) -> bool {
+        false
+    }
+
+    fn settings_ui(&mut self, _ui: &mut Ui) {}
+
+    // Перечитать данные из источника, не дожидаясь своего таймера
+    fn refresh(&mut self) {}
+}
+
+// Раскладка из секции [widgets]: порядок общий для обеих колонок,
+// right — виджеты второй колонки
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WidgetLayout {
+    pub order: Vec<String>,
+    pub disabled: Vec<String>,
+    pub collapsed: Vec<String>,
+    pub right: Vec<String>,
+    pub columns: usize,
+}
+
+static LAYOUT_CACHE: Lazy<RwLock<Option<WidgetLayout>>> = Lazy::new(|| RwLock::new(None));
+
+impl WidgetLayout {
+    pub fn current() -> Self {
+        if let Ok(cache) = LAYOUT_CACHE.read() {
+            if let Some(layout) = cache.as_ref() {
+                return layout.clone();
+            }
+        }
+
+        let list = |key: &str| -> Vec<String> {
+            get_widget_setting(key, "")
+                .split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect()
+        };
+        let layout = Self {
+            order: list("order"),
+            disabled: list("disabled"),
+            collapsed: list("collapsed"),
+            right: list("right"),
+            columns: get_widget_setting("columns", "1").parse().unwrap_or(1).clamp(1, 2),
+        };
+
+        if let Ok(mut cache) = LAYOUT_CACHE.write() {
+            *cache = Some(layout.clone());
+        }
+        layout
+    }
+
+    pub fn save(&self) {
+        set_widget_setting("order", &self.order.join(","));
+        set_widget_setting("disabled", &self.disabled.join(","));
+        set_widget_setting("collapsed", &self.collapsed.join(","));
+        set_widget_setting("right", &self.right.join(","));
+        set_widget_setting("columns", &self.columns.to_string());
+        invalidate_layout_cache();
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == id)
+    }
+
+    pub fn is_collapsed(&self, id: &str) -> bool {
+        self.collapsed.iter().any(|collapsed| collapsed == id)
+    }
+
+    fn column_of(&self, id: &str) -> usize {
+        if self.right.iter().any(|right| right == id) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn toggle(list: &mut Vec<String>, id: &str, on: bool) {
+        list.retain(|existing| existing != id);
+        if on {
+            list.push(id.to_string());
+        }
+    }
+}
+
+// Что перетаскивают за ручку в заголовке
+struct DraggedWidget(&'static str);
+
+enum DropTarget {
+    Before(&'static str),
+    After(&'static str),
+    ColumnEnd(usize),
+}
+
+// Изменения раскладки за кадр; применяются после отрисовки всех колонок
+#[derive(Default)]
+struct LayoutChange {
+    toggle_collapsed: Option<&'static str>,
+    moved: Option<(&'static str, DropTarget)>,
+}
+
+pub fn invalidate_layout_cache() {
+    if let Ok(mut cache) = LAYOUT_CACHE.write() {
+        *cache = None;
+    }
+}
+
+#[derive(Default)]
+pub struct WidgetRegistry {
+    widgets: Vec<Box<dyn SidebarWidget>>,
+}
+
+impl WidgetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Порядок регистрации — порядок по умолчанию
+    pub fn register(&mut self, widget: impl SidebarWidget + 'static) {
+        self.widgets.push(Box::new(widget));
+    }
+
+    // Сначала виджеты в порядке из настроек, затем новые, которых там ещё нет
+    fn ordered(&self, layout: &WidgetLayout) -> Vec<usize> {
+        let mut indices: Vec<usize> = layout
+            .order
+            .iter()
+            .filter_map(|id| self.widgets.iter().position(|widget| widget.id() == id))
+            .collect();
+        for index in 0..self.widgets.len() {
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        indices
+    }
+
+    fn enabled(&self, layout: &WidgetLayout) -> Vec<usize> {
+        self.ordered(layout)
+            .into_iter()
+            .filter(|index| layout.is_enabled(self.widgets[*index].id()))
+            .collect()
+    }
+
+    pub fn render(&mut self, ui: &mut Ui, ctx: &Context) {
+        let layout = WidgetLayout::current();
+        let enabled = self.enabled(&layout);
+        let mut change = LayoutChange::default();
+
+        // В узком окне вторая колонка не помещается, показываем всё одной
+        if layout.columns > 1 && ui.available_width() >= NARROW_WINDOW_THRESHOLD {
+            let (left, right): (Vec<usize>, Vec<usize>) = enabled
+                .into_iter()
+                .partition(|index| layout.column_of(self.widgets[*index].id()) == 0);
+            ui.columns(2, |columns| {
+                self.render_column(&mut columns[0], ctx, &left, &layout, 0, &mut change);
+                self.render_column(&mut columns[1], ctx, &right, &layout, 1, &mut change);
+            });
+        } else {
+            self.render_column(ui, ctx, &enabled, &layout, 0, &mut change);
+        }
+
+        if let Some(dragged) = DragAndDrop::payload::<DraggedWidget>(ctx) {
+            let title = self.widgets.iter().find(|widget| widget.id() == dragged.0).map(|widget| widget.title());
+            if let Some(title) = title {
+                egui::show_tooltip_at_pointer(ctx, egui::Id::new("dragged_widget"), |ui| {
+                    ui.label(format!("☰ {}", title));
+                });
+            }
+        }
+
+        self.apply(layout, change);
+    }
+
+    fn render_column(
+        &mut self,
+        ui: &mut Ui,
+        ctx: &Context,
+        indices: &[usize],
+        layout: &WidgetLayout,
+        column: usize,
+        change: &mut LayoutChange,
+    ) {
+        let marker = Stroke::new(2.0, parse_color_from_ini("button-color"));
+
+        for (position, index) in indices.iter().copied().enumerate() {
+            if position > 0 {
+                ui.add_space(WIDGET_SPACING);
+            }
+
+            let id = self.widgets[index].id();
+            let collapsed = layout.is_collapsed(id);
+            let response = ui
+                .scope(|ui| {
+                    render_header(ui, id, self.widgets[index].title(), collapsed, change);
+                    if !collapsed {
+                        self.widgets[index].render(ui, ctx);
+                    }
+                })
+                .response;
+
+            // Верхняя половина — вставить перед виджетом, нижняя — после
+            let pointer = ctx.pointer_latest_pos().unwrap_or_default();
+            let before = pointer.y < response.rect.center().y;
+            if response.dnd_hover_payload::<DraggedWidget>().is_some_and(|dragged| dragged.0 != id) {
+                let y = if before {
+                    response.rect.top() - WIDGET_SPACING / 2.0
+                } else {
+                    response.rect.bottom() + WIDGET_SPACING / 2.0
+                };
+                ui.painter().hline(response.rect.x_range(), y, marker);
+            }
+            if let Some(dragged) = response.dnd_release_payload::<DraggedWidget>() {
+                if dragged.0 != id {
+                    let target = if before { DropTarget::Before(id) } else { DropTarget::After(id) };
+                    change.moved = Some((dragged.0, target));
+                }
+            }
+        }
+
+        // Свободное место под колонкой принимает виджет в её конец
+        let height = if indices.is_empty() { DROP_ZONE_HEIGHT * 2.0 } else { DROP_ZONE_HEIGHT };
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), height), Sense::hover());
+        if response.dnd_hover_payload::<DraggedWidget>().is_some() {
+            ui.painter().rect_stroke(rect.shrink(2.0), 6.0, Stroke::new(1.0, marker.color));
+        } else if indices.is_empty() && DragAndDrop::has_any_payload(ctx) {
+            ui.painter().rect_stroke(rect.shrink(2.0), 6.0, Stroke::new(1.0, Color32::GRAY));
+        }
+        if let Some(dragged) = response.dnd_release_payload::<DraggedWidget>() {
+            change.moved = Some((dragged.0, DropTarget::ColumnEnd(column)));
+        }
+    }
+
+    fn apply(&self, mut layout: WidgetLayout, change: LayoutChange) {
+        if change.toggle_collapsed.is_none() && change.moved.is_none() {
+            return;
+        }
+
+        if let Some(id) = change.toggle_collapsed {
+            let collapsed = layout.is_collapsed(id);
+            WidgetLayout::toggle(&mut layout.collapsed, id, !collapsed);
+        }
+
+        if let Some((dragged, target)) = change.moved {
+            // Колонки — срезы общего порядка, поэтому вставляем в общий список
+            let mut ids: Vec<String> = self
+                .ordered(&layout)
+                .iter()
+                .map(|index| self.widgets[*index].id().to_string())
+                .collect();
+            ids.retain(|id| id != dragged);
+            let position = |other: &str| ids.iter().position(|id| id == other).unwrap_or(ids.len());
+            let (column, insert_at) = match target {
+                DropTarget::Before(other) => (layout.column_of(other), position(other)),
+                DropTarget::After(other) => (layout.column_of(other), position(other) + 1),
+                DropTarget::ColumnEnd(column) => (column, ids.len()),
+            };
+            ids.insert(insert_at.min(ids.len()), dragged.to_string());
+            layout.order = ids;
+            // В одноколоночном режиме колонку виджета не трогаем
+            if layout.columns > 1 {
+                WidgetLayout::toggle(&mut layout.right, dragged, column == 1);
+            }
+        }
+
+        layout.save();
+    }
+
+    pub fn tick(&mut self, ctx: &Context) {
+        for widget in &mut self.widgets {
+            widget.tick(ctx);
+        }
+    }
+
+    pub fn render_popups(&mut self, ctx: &Context) {
+        for index in self.enabled(&WidgetLayout::current()) {
+            self.widgets[index].render_popups(ctx);
+        }
+    }
+
+    // Содержимое раздела Widgets в окне настроек; изменения сохраняются сразу
+    pub fn settings_ui(&mut self, ui: &mut Ui) {
+        let mut layout = WidgetLayout::current();
+        let ordered = self.ordered(&layout);
+        let last = ordered.len().saturating_sub(1);
+        let mut moved = None;
+        let mut toggled = None;
+
+        let mut columns = layout.columns;
+        ui.horizontal(|ui| {
+            ui.label("Columns:");
+            ui.radio_value(&mut columns, 1, "One");
+            ui.radio_value(&mut columns, 2, "Two");
+        });
+        ui.label(
+            egui::RichText::new("Drag widgets by ☰ to reorder or move them between columns")
+                .size(11.0)
+                .color(Color32::GRAY),
+        );
+        ui.add_space(5.0);
+        let columns_changed = columns != layout.columns;
+        layout.columns = columns;
+
+        for (position, index) in ordered.iter().copied().enumerate() {
+            let widget = &mut self.widgets[index];
+            let mut enabled = layout.is_enabled(widget.id());
+
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut enabled, widget.title()).changed() {
+                    toggled = Some((index, enabled));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.add_enabled(position < last, egui::Button::new("⬇").small()).clicked() {
+                        moved = Some((position, position + 1));
+                    }
+                    if ui.add_enabled(position > 0, egui::Button::new("⬆").small()).clicked() {
+                        moved = Some((position, position - 1));
+                    }
+                });
+            });
+
+            // Настройки и экспорт данных нужны и у выключенного виджета
+            if widget.has_settings() {
+                ui.indent(("widget_settings", widget.id()), |ui| {
+                    egui::CollapsingHeader::new(egui::RichText::new("Options").size(12.0))
+                        .id_source(("widget_options", widget.id()))
+                        .show(ui, |ui| widget.settings_ui(ui));
+                });
+            }
+            ui.add_space(3.0);
+        }
+
+        ui.add_space(5.0);
+        if ui
+            .add(egui::Button::new("⟳ Refresh all widgets").fill(parse_color_from_ini("button-color")))
+            .on_hover_text("Reload data without waiting for the next update")
+            .clicked()
+        {
+            self.refresh_all();
+        }
+
+        if toggled.is_none() && moved.is_none() && !columns_changed {
+            return;
+        }
+
+        // Сохраняем полный порядок, чтобы он не зависел от порядка регистрации
+        let mut ids: Vec<String> = ordered.iter().map(|index| self.widgets[*index].id().to_string()).collect();
+        if let Some((from, to)) = moved {
+            ids.swap(from, to);
+        }
+        layout.order = ids;
+
+        if let Some((index, enabled)) = toggled {
+            let id = self.widgets[index].id().to_string();
+            layout.disabled.retain(|disabled| *disabled != id);
+            if enabled {
+                // Пока виджет был выключен, его данные не обновлялись
+                self.widgets[index].refresh();
+            } else {
+                layout.disabled.push(id);
+            }
+        }
+        layout.save();
+    }
+
+    fn refresh_all(&mut self) {
+        for widget in &mut self.widgets {
+            widget.refresh();
+        }
+    }
+}
+
+// Строка над виджетом: ручка для перетаскивания, название и сворачивание
+fn render_header(ui: &mut Ui, id: &'static str, title: &str, collapsed: bool, change: &mut LayoutChange) {
+    ui.horizontal(|ui| {
+        let handle = ui
+            .add(egui::Label::new(egui::RichText::new("☰").color(Color32::GRAY)).sense(Sense::drag()))
+            .on_hover_text("Drag to move");
+        handle.dnd_set_drag_payload(DraggedWidget(id));
+
+        ui.label(egui::RichText::new(title).size(11.0).color(Color32::GRAY));
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let (arrow, hint) = if collapsed { ("▶", "Expand") } else { ("▼", "Collapse") };
+            if ui.small_button(arrow).on_hover_text(hint).clicked() {
+                change.toggle_collapsed = Some(id);
+            }
+        });
+    });
+}
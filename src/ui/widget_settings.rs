@@ -0,0 +1,766 @@
+use crate::ui::color_parser::parse_color_from_ini;
+use crate::ui::food_diary::invalidate_day_start_cache;
+use crate::ui::health_export::{expand_path, export_to, import_from, ImportMode};
+use crate::ui::settings::{
+    health_settings_version, lines_to_section, load_settings, render_text_input, section_to_lines, update_settings,
+    MAX_WEATHER_REFRESH_MINUTES,
+};
+use crate::ui::widgets::weather_locations::{search_locations, LocationStore, SavedLocation};
+use crate::ui::widgets::weather_units::{invalidate_units_cache, PressureUnit, TemperatureUnit, WindUnit};
+use configparser::ini::Ini;
+use egui::Vec2;
+use std::sync::{Arc, Mutex};
+
+// Формы собственных настроек виджетов для раздела Widgets окна настроек.
+// Виджет создаёт форму при первом показе; сохраняется она кнопкой, как остальные разделы
+
+#[derive(Default)]
+pub struct HealthSettings {
+    day_start_hour: String,
+    daily_water_goal: String,
+    water_increment: String,
+    water_presets: String,
+    beverages: String,
+    water_reminder: bool,
+    reminder_start_hour: String,
+    reminder_end_hour: String,
+    reminder_interval_minutes: String,
+    daily_calorie_goal: String,
+    daily_protein_goal: String,
+    daily_fat_goal: String,
+    daily_carbs_goal: String,
+    goal_weight: String,
+    health_data_path: String,
+    health_data_status: Option<Result<String, String>>,
+    version: u64,
+}
+
+impl HealthSettings {
+    pub fn load() -> Self {
+        let mut form = Self {
+            health_data_path: "~/sidebar-health.csv".to_string(),
+            ..Default::default()
+        };
+        if let Some(settings) = load_settings() {
+            form.load_from(&settings);
+        }
+        form
+    }
+
+    fn load_from(&mut self, settings: &Ini) {
+        self.version = health_settings_version();
+        self.day_start_hour = settings
+            .get("health", "day_start_hour")
+            .unwrap_or_else(|| "0".to_string());
+        self.daily_water_goal = settings
+            .get("health", "daily_water_goal")
+            .unwrap_or_else(|| "2000".to_string());
+        self.water_increment = settings
+            .get("health", "water_increment")
+            .unwrap_or_else(|| "400".to_string());
+        self.water_presets = section_to_lines(settings, "water_presets");
+        self.beverages = section_to_lines(settings, "beverages");
+        self.water_reminder = settings
+            .get("health", "water_reminder")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+        self.reminder_start_hour = settings
+            .get("health", "reminder_start_hour")
+            .unwrap_or_else(|| "8".to_string());
+        self.reminder_end_hour = settings
+            .get("health", "reminder_end_hour")
+            .unwrap_or_else(|| "22".to_string());
+        self.reminder_interval_minutes = settings
+            .get("health", "reminder_interval_minutes")
+            .unwrap_or_else(|| "60".to_string());
+        self.daily_calorie_goal = settings
+            .get("health", "daily_calorie_goal")
+            .unwrap_or_else(|| "2000".to_string());
+        self.daily_protein_goal = settings
+            .get("health", "daily_protein_goal")
+            .unwrap_or_else(|| "100".to_string());
+        self.daily_fat_goal = settings
+            .get("health", "daily_fat_goal")
+            .unwrap_or_else(|| "70".to_string());
+        self.daily_carbs_goal = settings
+            .get("health", "daily_carbs_goal")
+            .unwrap_or_else(|| "250".to_string());
+        self.goal_weight = settings.get("health", "goal_weight").unwrap_or_default();
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        // Цель по калориям могли принять в трекере веса, пока окно открыто
+        if self.version != health_settings_version() {
+            if let Some(settings) = load_settings() {
+                self.load_from(&settings);
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Day starts at (hour):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.day_start_hour)
+                    .desired_width(100.0)
+                    .hint_text("0")
+            );
+        });
+        ui.label(
+            egui::RichText::new("Entries before this hour count towards the previous day")
+                .size(11.0)
+                .color(egui::Color32::GRAY),
+        );
+        ui.add_space(10.0);
+        
+        ui.heading("Water Settings");
+        ui.add_space(5.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Daily goal (ml):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.daily_water_goal)
+                    .desired_width(100.0)
+                    .hint_text("2000")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Custom amount (ml):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.water_increment)
+                    .desired_width(100.0)
+                    .hint_text("400")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.label("Quick-add sizes (one per line: glass = 250):");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.water_presets)
+                .desired_width(ui.available_width())
+                .desired_rows(3)
+                .hint_text("glass = 250\nmug = 350\nbottle = 500")
+        );
+        ui.add_space(3.0);
+        
+        ui.label("Beverages and hydration factor (coffee = 0.8):");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.beverages)
+                .desired_width(ui.available_width())
+                .desired_rows(3)
+                .hint_text("tea = 0.9\ncoffee = 0.8")
+        );
+        ui.add_space(5.0);
+        
+        ui.checkbox(&mut self.water_reminder, "Remind me when I fall behind");
+        if self.water_reminder {
+            for (label, value, hint) in [
+                ("From hour:", &mut self.reminder_start_hour, "8"),
+                ("Until hour:", &mut self.reminder_end_hour, "22"),
+                ("Every (min):", &mut self.reminder_interval_minutes, "60"),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.add_space(5.0);
+                    ui.add(
+                        egui::TextEdit::singleline(value)
+                            .desired_width(100.0)
+                            .hint_text(hint)
+                    );
+                });
+            }
+        }
+        
+        ui.add_space(15.0);
+        ui.heading("Food Settings");
+        ui.add_space(5.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Daily goal (kcal):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.daily_calorie_goal)
+                    .desired_width(100.0)
+                    .hint_text("2000")
+            );
+        });
+        ui.add_space(3.0);
+        
+        for (label, value, hint) in [
+            ("Protein goal (g):", &mut self.daily_protein_goal, "100"),
+            ("Fat goal (g):", &mut self.daily_fat_goal, "70"),
+            ("Carbs goal (g):", &mut self.daily_carbs_goal, "250"),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add_space(5.0);
+                ui.add(
+                    egui::TextEdit::singleline(value)
+                        .desired_width(100.0)
+                        .hint_text(hint)
+                );
+            });
+            ui.add_space(3.0);
+        }
+        
+        ui.add_space(15.0);
+        ui.heading("Weight Settings");
+        ui.add_space(5.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Goal weight (kg):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.goal_weight)
+                    .desired_width(100.0)
+                    .hint_text("none")
+            );
+        });
+        
+        ui.add_space(10.0);
+        
+        if ui.add(
+            egui::Button::new("💾 Save Health Settings")
+                .min_size(Vec2::new(200.0, 30.0))
+                .fill(parse_color_from_ini("button-color"))
+        ).clicked() {
+            self.save();
+        }
+        
+        ui.add_space(15.0);
+        self.render_data_transfer(ui);
+        ui.add_space(10.0);
+    }
+
+    fn render_data_transfer(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Export / Import");
+        ui.add_space(5.0);
+        ui.label(
+            egui::RichText::new("Water and food diary as .csv or .json with ISO dates")
+                .size(11.0)
+                .color(egui::Color32::GRAY),
+        );
+
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.health_data_path)
+                    .desired_width(220.0)
+                    .hint_text("~/sidebar-health.csv")
+            );
+        });
+        ui.add_space(3.0);
+
+        let button_color = parse_color_from_ini("button-color");
+        let path = expand_path(&self.health_data_path);
+        ui.horizontal(|ui| {
+            if ui.add(egui::Button::new("📤 Export").fill(button_color)).clicked() {
+                self.health_data_status = Some(
+                    export_to(&path).map(|records| format!("Exported {} records to {}", records, path.display())),
+                );
+            }
+            for (label, mode) in [("📥 Merge", ImportMode::Merge), ("📥 Overwrite", ImportMode::Overwrite)] {
+                let hint = match mode {
+                    ImportMode::Merge => "Add missing days and new entries",
+                    ImportMode::Overwrite => "Replace local days that exist in the file",
+                };
+                if ui.add(egui::Button::new(label).fill(button_color)).on_hover_text(hint).clicked() {
+                    self.health_data_status = Some(import_from(&path, mode).map(|summary| {
+                        format!(
+                            "Imported {} water days and {} food entries",
+                            summary.water_days, summary.food_entries
+                        )
+                    }));
+                }
+            }
+        });
+
+        match &self.health_data_status {
+            Some(Ok(message)) => {
+                ui.label(egui::RichText::new(message).size(11.0));
+            }
+            Some(Err(e)) => {
+                ui.label(egui::RichText::new(e).size(11.0).color(egui::Color32::from_rgb(200, 60, 60)));
+            }
+            None => {}
+        }
+    }
+
+    fn save(&self) {
+        update_settings(|settings| {
+            if let Ok(hour) = self.day_start_hour.trim().parse::<u32>() {
+                settings.set("health", "day_start_hour", Some(hour.min(12).to_string()));
+            }
+            
+            // Validate and save water goal
+            if let Ok(goal) = self.daily_water_goal.parse::<u32>() {
+                settings.set("health", "daily_water_goal", Some(goal.to_string()));
+            }
+            
+            // Validate and save water increment
+            if let Ok(increment) = self.water_increment.parse::<u32>() {
+                settings.set("health", "water_increment", Some(increment.to_string()));
+            }
+            
+            lines_to_section(settings, "water_presets", &self.water_presets);
+            lines_to_section(settings, "beverages", &self.beverages);
+            settings.set("health", "water_reminder", Some(self.water_reminder.to_string()));
+            for (key, value) in [
+                ("reminder_start_hour", &self.reminder_start_hour),
+                ("reminder_end_hour", &self.reminder_end_hour),
+                ("reminder_interval_minutes", &self.reminder_interval_minutes),
+            ] {
+                if let Ok(parsed) = value.parse::<u32>() {
+                    settings.set("health", key, Some(parsed.to_string()));
+                }
+            }
+            
+            // Validate and save calorie goal
+            if let Ok(goal) = self.daily_calorie_goal.parse::<i32>() {
+                settings.set("health", "daily_calorie_goal", Some(goal.to_string()));
+            }
+            
+            // Validate and save macronutrient goals
+            for (key, value) in [
+                ("daily_protein_goal", &self.daily_protein_goal),
+                ("daily_fat_goal", &self.daily_fat_goal),
+                ("daily_carbs_goal", &self.daily_carbs_goal),
+            ] {
+                if let Ok(goal) = value.parse::<u32>() {
+                    settings.set("health", key, Some(goal.to_string()));
+                }
+            }
+            
+            // Пустое поле убирает цель по весу
+            let goal_weight = self.goal_weight.trim().replace(',', ".");
+            if goal_weight.is_empty() {
+                settings.remove_key("health", "goal_weight");
+            } else if let Ok(kg) = goal_weight.parse::<f32>() {
+                settings.set("health", "goal_weight", Some(kg.to_string()));
+            }
+        });
+        invalidate_day_start_cache();
+    }
+}
+
+#[derive(Default)]
+pub struct PomodoroSettings {
+    work_minutes: String,
+    short_break_minutes: String,
+    long_break_minutes: String,
+    sessions: String,
+    auto_dnd: bool,
+}
+
+impl PomodoroSettings {
+    pub fn load() -> Self {
+        let mut form = Self::default();
+        if let Some(settings) = load_settings() {
+            form.load_from(&settings);
+        }
+        form
+    }
+
+    fn load_from(&mut self, settings: &Ini) {
+        self.work_minutes = settings
+            .get("pomodoro", "work_minutes")
+            .unwrap_or_else(|| "25".to_string());
+        self.short_break_minutes = settings
+            .get("pomodoro", "short_break_minutes")
+            .unwrap_or_else(|| "5".to_string());
+        self.long_break_minutes = settings
+            .get("pomodoro", "long_break_minutes")
+            .unwrap_or_else(|| "15".to_string());
+        self.sessions = settings
+            .get("pomodoro", "sessions_before_long_break")
+            .unwrap_or_else(|| "4".to_string());
+        self.auto_dnd = settings
+            .get("pomodoro", "auto_dnd")
+            .map(|v| v == "true")
+            .unwrap_or(true);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Focus (min):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.work_minutes)
+                    .desired_width(100.0)
+                    .hint_text("25")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Short break (min):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.short_break_minutes)
+                    .desired_width(100.0)
+                    .hint_text("5")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Long break (min):");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.long_break_minutes)
+                    .desired_width(100.0)
+                    .hint_text("15")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.horizontal(|ui| {
+            ui.label("Sessions before long break:");
+            ui.add_space(5.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.sessions)
+                    .desired_width(100.0)
+                    .hint_text("4")
+            );
+        });
+        ui.add_space(3.0);
+        
+        ui.checkbox(&mut self.auto_dnd, "Do Not Disturb during focus (dunst/mako/swaync)");
+        
+        ui.add_space(10.0);
+        
+        if ui.add(
+            egui::Button::new("💾 Save Pomodoro Settings")
+                .min_size(Vec2::new(200.0, 30.0))
+                .fill(parse_color_from_ini("button-color"))
+        ).clicked() {
+            self.save();
+        }
+        
+        ui.add_space(10.0);
+    }
+
+    fn save(&self) {
+        update_settings(|settings| {
+            let minutes = [
+                ("work_minutes", &self.work_minutes),
+                ("short_break_minutes", &self.short_break_minutes),
+                ("long_break_minutes", &self.long_break_minutes),
+                ("sessions_before_long_break", &self.sessions),
+            ];
+            
+            // Сохраняем только положительные значения
+            for (key, value) in minutes {
+                if let Ok(parsed) = value.parse::<u32>() {
+                    if parsed > 0 {
+                        settings.set("pomodoro", key, Some(parsed.to_string()));
+                    }
+                }
+            }
+            
+            settings.set("pomodoro", "auto_dnd", Some(self.auto_dnd.to_string()));
+        });
+    }
+}
+
+type SearchResult = Result<Vec<SavedLocation>, String>;
+
+// Сохранённые места общие с виджетом погоды, поэтому форма правит его копию
+#[derive(Default)]
+pub struct WeatherSettings {
+    city: String,
+    country: String,
+    api_key: String,
+    provider: String,
+    refresh_minutes: String,
+    temperature_unit: String,
+    wind_unit: String,
+    pressure_unit: String,
+    location_query: String,
+    location_results: Vec<SavedLocation>,
+    location_error: Option<String>,
+    searching: bool,
+    pending_search: Arc<Mutex<Option<SearchResult>>>,
+}
+
+impl WeatherSettings {
+    pub fn load() -> Self {
+        let mut form = Self::default();
+        if let Some(settings) = load_settings() {
+            form.load_from(&settings);
+        }
+        form
+    }
+
+    fn load_from(&mut self, settings: &Ini) {
+        self.city = settings.get("settings", "city").unwrap_or_default();
+        self.country = settings.get("settings", "country").unwrap_or_default();
+        self.api_key = settings.get("settings", "owm_api_key").unwrap_or_default();
+        self.provider = settings
+            .get("settings", "weather_provider")
+            .unwrap_or_else(|| "openweathermap".to_string());
+        self.refresh_minutes = settings
+            .get("settings", "refresh_minutes")
+            .unwrap_or_else(|| "10".to_string());
+        self.temperature_unit = settings
+            .get("settings", "temperature_unit")
+            .unwrap_or_else(|| "celsius".to_string());
+        self.wind_unit = settings
+            .get("settings", "wind_unit")
+            .unwrap_or_else(|| "ms".to_string());
+        self.pressure_unit = settings
+            .get("settings", "pressure_unit")
+            .unwrap_or_else(|| "hpa".to_string());
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, locations: &mut LocationStore) {
+        ui.horizontal(|ui| {
+            ui.label("Provider:");
+            egui::ComboBox::from_id_source("weather_provider")
+                .selected_text(weather_provider_label(&self.provider))
+                .show_ui(ui, |ui| {
+                    for provider in ["openweathermap", "open-meteo", "met-norway"] {
+                        ui.selectable_value(
+                            &mut self.provider,
+                            provider.to_string(),
+                            weather_provider_label(provider),
+                        );
+                    }
+                });
+        });
+        ui.add_space(5.0);
+
+        if self.provider == "openweathermap" {
+            render_text_input(ui, "OpenWeatherMap API Key:", &mut self.api_key);
+        }
+        
+        self.render_location_search(ui, locations);
+        ui.add_space(5.0);
+        render_saved_locations(ui, locations);
+        
+        // Город/страна используются OWM, только если нет сохранённых мест
+        if self.provider == "openweathermap" && locations.locations.is_empty() {
+            ui.add_space(5.0);
+            render_text_input(ui, "City:", &mut self.city);
+            render_text_input(ui, "Country:", &mut self.country);
+        }
+        ui.add_space(5.0);
+        
+        render_text_input(ui, "Refresh interval (minutes):", &mut self.refresh_minutes);
+        self.render_unit_settings(ui);
+
+        if ui.add(
+            egui::Button::new("💾 Save Weather Settings")
+                .min_size(Vec2::new(200.0, 30.0))
+                .fill(parse_color_from_ini("button-color")),
+        ).clicked() {
+            self.save();
+        }
+        
+        ui.add_space(10.0);
+    }
+
+    fn render_unit_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Temperature:");
+            egui::ComboBox::from_id_source("temperature_unit")
+                .selected_text(TemperatureUnit::from_setting(&self.temperature_unit).label())
+                .show_ui(ui, |ui| {
+                    for unit in TemperatureUnit::ALL {
+                        ui.selectable_value(&mut self.temperature_unit, unit.setting_value().to_string(), unit.label());
+                    }
+                });
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Wind speed:");
+            egui::ComboBox::from_id_source("wind_unit")
+                .selected_text(WindUnit::from_setting(&self.wind_unit).label())
+                .show_ui(ui, |ui| {
+                    for unit in WindUnit::ALL {
+                        ui.selectable_value(&mut self.wind_unit, unit.setting_value().to_string(), unit.label());
+                    }
+                });
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Pressure:");
+            egui::ComboBox::from_id_source("pressure_unit")
+                .selected_text(PressureUnit::from_setting(&self.pressure_unit).label())
+                .show_ui(ui, |ui| {
+                    for unit in PressureUnit::ALL {
+                        ui.selectable_value(&mut self.pressure_unit, unit.setting_value().to_string(), unit.label());
+                    }
+                });
+        });
+        ui.add_space(5.0);
+    }
+
+    fn render_location_search(&mut self, ui: &mut egui::Ui, locations: &mut LocationStore) {
+        ui.label("Location:");
+        
+        let mut search = false;
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.location_query)
+                    .hint_text("Search city...")
+                    .desired_width(220.0)
+            );
+            search = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            
+            if ui.add(
+                egui::Button::new("🔍 Search")
+                    .fill(parse_color_from_ini("button-color"))
+            ).clicked() {
+                search = true;
+            }
+        });
+        
+        if search && !self.searching {
+            self.start_search(ui.ctx());
+        }
+        self.take_search_result();
+        
+        if self.searching {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("Searching…").size(11.0).color(egui::Color32::GRAY));
+            });
+        }
+        
+        if let Some(error) = &self.location_error {
+            ui.label(egui::RichText::new(error).size(11.0).color(egui::Color32::GRAY));
+        }
+        
+        let mut picked = None;
+        for (index, result) in self.location_results.iter().enumerate() {
+            let text = format!("{} ({:.2}, {:.2})", result.name, result.latitude, result.longitude);
+            if ui.add(
+                egui::Button::new(text)
+                    .min_size(Vec2::new(ui.available_width(), 25.0))
+                    .fill(parse_color_from_ini("button-color").linear_multiply(0.5))
+                    .wrap(true)
+            ).clicked() {
+                picked = Some(index);
+            }
+        }
+        
+        if let Some(index) = picked {
+            let location = self.location_results.remove(index);
+            update_locations(locations, |locations| locations.add(location));
+            self.location_results.clear();
+            self.location_query.clear();
+        }
+    }
+
+    // Геокодинг идёт по сети, поэтому, как и прогноз, ищем в фоне
+    fn start_search(&mut self, ctx: &egui::Context) {
+        self.searching = true;
+        self.location_error = None;
+
+        let pending = Arc::clone(&self.pending_search);
+        let query = self.location_query.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = search_locations(&query).map_err(|e| e.to_string());
+            if let Ok(mut pending) = pending.lock() {
+                *pending = Some(result);
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn take_search_result(&mut self) {
+        let Some(result) = self.pending_search.lock().ok().and_then(|mut pending| pending.take()) else {
+            return;
+        };
+        self.searching = false;
+
+        match result {
+            Ok(results) => {
+                self.location_error = results.is_empty().then(|| "Nothing found".to_string());
+                self.location_results = results;
+            }
+            Err(e) => {
+                self.location_error = Some(e);
+                self.location_results.clear();
+            }
+        }
+    }
+
+    fn save(&self) {
+        update_settings(|settings| {
+            settings.set("settings", "city", Some(self.city.clone()));
+            settings.set("settings", "country", Some(self.country.clone()));
+            settings.set("settings", "owm_api_key", Some(self.api_key.clone()));
+            settings.set("settings", "weather_provider", Some(self.provider.clone()));
+            if let Ok(minutes) = self.refresh_minutes.trim().parse::<i64>() {
+                let minutes = minutes.clamp(1, MAX_WEATHER_REFRESH_MINUTES);
+                settings.set("settings", "refresh_minutes", Some(minutes.to_string()));
+            }
+            settings.set("settings", "temperature_unit", Some(self.temperature_unit.clone()));
+            settings.set("settings", "wind_unit", Some(self.wind_unit.clone()));
+            settings.set("settings", "pressure_unit", Some(self.pressure_unit.clone()));
+        });
+        invalidate_units_cache();
+    }
+}
+
+fn render_saved_locations(ui: &mut egui::Ui, locations: &mut LocationStore) {
+    if locations.locations.is_empty() {
+        return;
+    }
+    
+    ui.label("Saved locations:");
+    
+    let mut activate = None;
+    let mut remove = None;
+    for (index, location) in locations.locations.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let marker = if index == locations.active { "●" } else { "○" };
+            if ui.selectable_label(index == locations.active, format!("{} {}", marker, location.name)).clicked() {
+                activate = Some(index);
+            }
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✕").on_hover_text("Remove location").clicked() {
+                    remove = Some(index);
+                }
+            });
+        });
+    }
+    
+    // Индексы из показанного списка; после перечитывания ищем то же место
+    if let Some(location) = activate.map(|index| locations.locations[index].clone()) {
+        update_locations(locations, |locations| {
+            if let Some(index) = locations.locations.iter().position(|saved| *saved == location) {
+                locations.active = index;
+            }
+        });
+    }
+    if let Some(location) = remove.map(|index| locations.locations[index].clone()) {
+        update_locations(locations, |locations| {
+            if let Some(index) = locations.locations.iter().position(|saved| *saved == location) {
+                locations.remove(index);
+            }
+        });
+    }
+}
+
+// Виджет погоды сам меняет активное место, поэтому правим свежую копию
+fn update_locations(locations: &mut LocationStore, change: impl FnOnce(&mut LocationStore)) {
+    *locations = LocationStore::load();
+    change(locations);
+    locations.save();
+}
+
+fn weather_provider_label(provider: &str) -> &'static str {
+    match provider {
+        "open-meteo" => "Open-Meteo",
+        "met-norway" => "MET Norway",
+        _ => "OpenWeatherMap",
+    }
+}
@@ -1,2 +1,7 @@
 pub mod todo_widget;
+pub mod weather_cache;
+pub mod weather_conditions;
+pub mod weather_locations;
 pub mod weather_plugin;
+pub mod weather_providers;
+pub mod weather_units;
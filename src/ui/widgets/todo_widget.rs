@@ -48,3 +48,19 @@ pub fn get_tasks() -> Vec<Task> {
 
     tasks
 }
+
+pub fn get_active_task() -> Option<Task> {
+    // Задача, запущенная через `task <id> start`
+    let output = Command::new("task")
+        .args(["+ACTIVE", "export"])
+        .output()
+        .ok()?;
+
+    let parsed: Vec<Value> = serde_json::from_slice(&output.stdout).ok()?;
+
+    parsed.first().map(|task_data| Task {
+        description: task_data["description"].as_str().unwrap_or("").to_string(),
+        project: task_data["project"].as_str().unwrap_or("").to_string(),
+        id: task_data["id"].as_i64().unwrap_or(0) as i32,
+    })
+}
@@ -0,0 +1,31 @@
+use crate::ui::data_store::DataStore;
+use crate::ui::widgets::weather_plugin::{WeatherForecast, WEATHER_COLLECTION};
+use serde::{Deserialize, Serialize};
+
+pub const WEATHER_CACHE_KEY: &str = "forecast_cache";
+
+// Последний успешный прогноз вместе со временем загрузки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedForecast {
+    pub source_key: String, // Провайдер и место, для которых получен прогноз
+    pub fetched_at: i64,    // Метка времени загрузки (UTC)
+    pub forecast: WeatherForecast,
+}
+
+impl CachedForecast {
+    // Возвращает кэш, только если он относится к текущему месту
+    pub fn load(source_key: &str) -> Option<Self> {
+        DataStore::open()
+            .and_then(|store| store.document::<Self>(WEATHER_COLLECTION, WEATHER_CACHE_KEY))
+            .ok()
+            .flatten()
+            .filter(|cached| cached.source_key == source_key)
+    }
+
+    pub fn save(&self) {
+        let result = DataStore::open().and_then(|store| store.save_document(WEATHER_COLLECTION, WEATHER_CACHE_KEY, self));
+        if let Err(e) = result {
+            eprintln!("Failed to save weather cache: {}", e);
+        }
+    }
+}
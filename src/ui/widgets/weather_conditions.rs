@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+// Общая классификация погоды, к которой приводятся коды всех провайдеров
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WeatherCondition {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Overcast,
+    Mist,
+    Fog,
+    Smoke,
+    Dust,
+    Drizzle,
+    Rain,
+    FreezingRain,
+    Showers,
+    Thunderstorm,
+    Snow,
+    Sleet,
+    Hail,
+    Tornado,
+    #[default]
+    Unknown,
+}
+
+impl WeatherCondition {
+    // Коды OpenWeatherMap: https://openweathermap.org/weather-conditions
+    pub fn from_owm(id: u32) -> Option<Self> {
+        let condition = match id {
+            200..=232 => WeatherCondition::Thunderstorm,
+            300..=321 => WeatherCondition::Drizzle,
+            500..=504 => WeatherCondition::Rain,
+            511 => WeatherCondition::FreezingRain,
+            520..=531 => WeatherCondition::Showers,
+            600..=602 | 620..=622 => WeatherCondition::Snow,
+            611..=616 => WeatherCondition::Sleet,
+            701 => WeatherCondition::Mist,
+            711 => WeatherCondition::Smoke,
+            721 => WeatherCondition::Mist,
+            731 | 751 | 761 | 762 => WeatherCondition::Dust,
+            741 => WeatherCondition::Fog,
+            771 | 781 => WeatherCondition::Tornado,
+            800 => WeatherCondition::Clear,
+            801 => WeatherCondition::PartlyCloudy,
+            802 | 803 => WeatherCondition::Cloudy,
+            804 => WeatherCondition::Overcast,
+            _ => return None,
+        };
+        Some(condition)
+    }
+
+    // Коды WMO 4677, которые отдаёт Open-Meteo
+    pub fn from_wmo(code: u32) -> Option<Self> {
+        let condition = match code {
+            0 => WeatherCondition::Clear,
+            1 => WeatherCondition::PartlyCloudy,
+            2 => WeatherCondition::Cloudy,
+            3 => WeatherCondition::Overcast,
+            45 | 48 => WeatherCondition::Fog,
+            51 | 53 | 55 => WeatherCondition::Drizzle,
+            56 | 57 | 66 | 67 => WeatherCondition::FreezingRain,
+            61 | 63 | 65 => WeatherCondition::Rain,
+            71 | 73 | 75 | 77 | 85 | 86 => WeatherCondition::Snow,
+            80..=82 => WeatherCondition::Showers,
+            95 => WeatherCondition::Thunderstorm,
+            96 | 99 => WeatherCondition::Hail,
+            _ => return None,
+        };
+        Some(condition)
+    }
+
+    // Символы MET Norway: "partlycloudy_day", "heavyrainshowersandthunder_night"
+    pub fn from_met_symbol(symbol_code: &str) -> Option<Self> {
+        let symbol = symbol_code.split('_').next().unwrap_or("");
+
+        let condition = if symbol.is_empty() {
+            return None;
+        } else if symbol.contains("thunder") {
+            WeatherCondition::Thunderstorm
+        } else if symbol.contains("sleet") {
+            WeatherCondition::Sleet
+        } else if symbol.contains("snow") {
+            WeatherCondition::Snow
+        } else if symbol.contains("showers") {
+            WeatherCondition::Showers
+        } else if symbol == "lightrain" {
+            WeatherCondition::Drizzle
+        } else if symbol.contains("rain") {
+            WeatherCondition::Rain
+        } else {
+            match symbol {
+                "clearsky" => WeatherCondition::Clear,
+                "fair" => WeatherCondition::PartlyCloudy,
+                "partlycloudy" => WeatherCondition::Cloudy,
+                "cloudy" => WeatherCondition::Overcast,
+                "fog" => WeatherCondition::Fog,
+                _ => return None,
+            }
+        };
+        Some(condition)
+    }
+
+    // Описание в стиле OWM, служит ключом для перевода
+    pub fn description(&self) -> &'static str {
+        match self {
+            WeatherCondition::Clear => "clear sky",
+            WeatherCondition::PartlyCloudy => "few clouds",
+            WeatherCondition::Cloudy => "scattered clouds",
+            WeatherCondition::Overcast => "overcast clouds",
+            WeatherCondition::Mist => "mist",
+            WeatherCondition::Fog => "fog",
+            WeatherCondition::Smoke => "smoke",
+            WeatherCondition::Dust => "dust",
+            WeatherCondition::Drizzle => "drizzle",
+            WeatherCondition::Rain => "rain",
+            WeatherCondition::FreezingRain => "freezing rain",
+            WeatherCondition::Showers => "shower rain",
+            WeatherCondition::Thunderstorm => "thunderstorm",
+            WeatherCondition::Snow => "snow",
+            WeatherCondition::Sleet => "sleet",
+            WeatherCondition::Hail => "hail",
+            WeatherCondition::Tornado => "tornado",
+            WeatherCondition::Unknown => "unknown",
+        }
+    }
+
+    // Иконки из набора Weather Icons в Nerd Fonts
+    pub fn icon(&self, is_day: bool) -> &'static str {
+        match (self, is_day) {
+            (WeatherCondition::Clear, true) => "\u{e30d}",
+            (WeatherCondition::Clear, false) => "\u{e32b}",
+            (WeatherCondition::PartlyCloudy, true) => "\u{e30c}",
+            (WeatherCondition::PartlyCloudy, false) => "\u{e379}",
+            (WeatherCondition::Cloudy, true) => "\u{e302}",
+            (WeatherCondition::Cloudy, false) => "\u{e37e}",
+            (WeatherCondition::Overcast, _) => "\u{e312}",
+            (WeatherCondition::Mist, true) => "\u{e36b}",
+            (WeatherCondition::Mist, false) | (WeatherCondition::Fog, false) => "\u{e346}",
+            (WeatherCondition::Fog, true) => "\u{e303}",
+            (WeatherCondition::Smoke, _) => "\u{e35c}",
+            (WeatherCondition::Dust, _) => "\u{e35d}",
+            (WeatherCondition::Drizzle, true) => "\u{e30b}",
+            (WeatherCondition::Drizzle, false) => "\u{e328}",
+            (WeatherCondition::Rain, true) => "\u{e308}",
+            (WeatherCondition::Rain, false) => "\u{e325}",
+            (WeatherCondition::FreezingRain, true) | (WeatherCondition::Sleet, true) => "\u{e3aa}",
+            (WeatherCondition::FreezingRain, false) | (WeatherCondition::Sleet, false) => "\u{e3ac}",
+            (WeatherCondition::Showers, true) => "\u{e309}",
+            (WeatherCondition::Showers, false) => "\u{e326}",
+            (WeatherCondition::Thunderstorm, true) => "\u{e30f}",
+            (WeatherCondition::Thunderstorm, false) => "\u{e32a}",
+            (WeatherCondition::Snow, true) => "\u{e30a}",
+            (WeatherCondition::Snow, false) => "\u{e327}",
+            (WeatherCondition::Hail, _) => "\u{e314}",
+            (WeatherCondition::Tornado, _) => "\u{e351}",
+            (WeatherCondition::Unknown, _) => "\u{e374}",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Все коды из документации OpenWeatherMap
+    const OWM_CODES: &[u32] = &[
+        200, 201, 202, 210, 211, 212, 221, 230, 231, 232,
+        300, 301, 302, 310, 311, 312, 313, 314, 321,
+        500, 501, 502, 503, 504, 511, 520, 521, 522, 531,
+        600, 601, 602, 611, 612, 613, 615, 616, 620, 621, 622,
+        701, 711, 721, 731, 741, 751, 761, 762, 771, 781,
+        800, 801, 802, 803, 804,
+    ];
+
+    // Все коды WMO, перечисленные в документации Open-Meteo
+    const WMO_CODES: &[u32] = &[
+        0, 1, 2, 3, 45, 48, 51, 53, 55, 56, 57, 61, 63, 65, 66, 67,
+        71, 73, 75, 77, 80, 81, 82, 85, 86, 95, 96, 99,
+    ];
+
+    const NO_ICON: &str = "\u{e374}";
+
+    #[test]
+    fn every_owm_code_maps_to_an_icon() {
+        for &code in OWM_CODES {
+            let condition = WeatherCondition::from_owm(code)
+                .unwrap_or_else(|| panic!("OWM code {} is not mapped", code));
+            assert_ne!(condition.icon(true), NO_ICON, "OWM code {}", code);
+            assert_ne!(condition.icon(false), NO_ICON, "OWM code {}", code);
+        }
+    }
+
+    #[test]
+    fn every_wmo_code_maps_to_an_icon() {
+        for &code in WMO_CODES {
+            let condition = WeatherCondition::from_wmo(code)
+                .unwrap_or_else(|| panic!("WMO code {} is not mapped", code));
+            assert_ne!(condition.icon(true), NO_ICON, "WMO code {}", code);
+            assert_ne!(condition.icon(false), NO_ICON, "WMO code {}", code);
+        }
+    }
+
+    #[test]
+    fn met_symbols_keep_day_and_night_variants_apart() {
+        assert_eq!(WeatherCondition::from_met_symbol("clearsky_day"), Some(WeatherCondition::Clear));
+        assert_eq!(
+            WeatherCondition::from_met_symbol("heavyrainshowersandthunder_night"),
+            Some(WeatherCondition::Thunderstorm)
+        );
+        assert_eq!(WeatherCondition::from_met_symbol("lightsleet"), Some(WeatherCondition::Sleet));
+        assert_ne!(WeatherCondition::Clear.icon(true), WeatherCondition::Clear.icon(false));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::ui::data_store::DataStore;
+use crate::ui::widgets::weather_plugin::WEATHER_COLLECTION;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const SEARCH_RESULTS: u32 = 10;
+pub const LOCATIONS_KEY: &str = "locations";
+
+// Выставляется при сохранении, чтобы виджет погоды перечитал список
+static LOCATIONS_CHANGED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SavedLocation {
+    pub name: String,         // Отображаемое имя: "Kyiv, Kyiv City, Ukraine"
+    pub city: String,         // Для OWM-запроса по городу
+    pub country_code: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LocationStore {
+    pub active: usize,
+    pub locations: Vec<SavedLocation>,
+}
+
+impl LocationStore {
+    pub fn load() -> Self {
+        DataStore::open()
+            .and_then(|store| store.document(WEATHER_COLLECTION, LOCATIONS_KEY))
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match DataStore::open().and_then(|store| store.save_document(WEATHER_COLLECTION, LOCATIONS_KEY, self)) {
+            Ok(()) => LOCATIONS_CHANGED.store(true, Ordering::Relaxed),
+            Err(e) => eprintln!("Failed to save weather locations: {}", e),
+        }
+    }
+
+    pub fn take_changed() -> bool {
+        LOCATIONS_CHANGED.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn active_location(&self) -> Option<&SavedLocation> {
+        self.locations.get(self.active)
+    }
+
+    pub fn add(&mut self, location: SavedLocation) {
+        match self.locations.iter().position(|saved| *saved == location) {
+            Some(index) => self.active = index,
+            None => {
+                self.locations.push(location);
+                self.active = self.locations.len() - 1;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.locations.len() {
+            self.locations.remove(index);
+        }
+        if self.active >= self.locations.len() {
+            self.active = self.locations.len().saturating_sub(1);
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<GeocodingResult>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GeocodingResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    country: String,
+    #[serde(default)]
+    country_code: String,
+    #[serde(default)]
+    admin1: Option<String>,
+}
+
+impl From<GeocodingResult> for SavedLocation {
+    fn from(result: GeocodingResult) -> Self {
+        // Регион помогает различать одноимённые города
+        let mut parts = vec![result.name.clone()];
+        if let Some(region) = result.admin1.filter(|region| *region != result.name) {
+            parts.push(region);
+        }
+        if !result.country.is_empty() {
+            parts.push(result.country);
+        }
+
+        Self {
+            name: parts.join(", "),
+            city: result.name,
+            country_code: result.country_code,
+            latitude: result.latitude,
+            longitude: result.longitude,
+        }
+    }
+}
+
+pub fn search_locations(query: &str) -> Result<Vec<SavedLocation>, Box<dyn Error>> {
+    search_locations_at(GEOCODING_URL, query)
+}
+
+pub fn search_locations_at(url: &str, query: &str) -> Result<Vec<SavedLocation>, Box<dyn Error>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let response: GeocodingResponse = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(url)
+        .query(&[
+            ("name", query.to_string()),
+            ("count", SEARCH_RESULTS.to_string()),
+            ("language", "en".to_string()),
+            ("format", "json".to_string()),
+        ])
+        .send()?
+        .error_for_status()?
+        .json()?;
+
+    Ok(response.results.into_iter().map(SavedLocation::from).collect())
+}
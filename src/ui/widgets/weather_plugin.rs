@@ -1,84 +1,141 @@
+use crate::ui::settings::get_weather_setting;
+use crate::ui::widgets::weather_conditions::WeatherCondition;
+use crate::ui::widgets::weather_locations::LocationStore;
+use crate::ui::widgets::weather_providers::{create_provider, WeatherProvider};
 use chrono::{DateTime, Duration, NaiveDate, Timelike, Utc};
-use reqwest;
-use serde::Deserialize; // Импортирование serde для десериализации JSON
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
 use std::error::Error;
 
-#[derive(Deserialize, Debug)]
+pub const HOURLY_HOURS: i64 = 48;
+// Коллекция документов погоды в общем хранилище
+pub const WEATHER_COLLECTION: &str = "weather";
+
+// Общая модель прогноза, к которой приводятся ответы всех провайдеров
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherForecast {
-    pub list: Vec<WeatherEntry>, // Список прогнозов погоды
+    pub list: Vec<WeatherEntry>,    // Список прогнозов погоды
+    pub hourly: Vec<WeatherEntry>,  // Почасовой прогноз на ближайшие 48 часов
+    pub alerts: Vec<WeatherAlert>,  // Предупреждения об опасной погоде
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherEntry {
-    pub dt: i64,                          // Метка времени прогноза
-    pub main: Main,                       // Основные параметры погоды
-    pub weather: Vec<WeatherDescription>, // Описание погоды
-    pub wind: Wind,                       // Информация о ветре
+    pub dt: i64,             // Метка времени прогноза (UTC)
+    pub temp: f64,           // Температура в градусах Цельсия
+    pub humidity: f64,       // Влажность в процентах
+    pub pressure: f64,       // Атмосферное давление в гПа
+    pub wind_speed: f64,     // Скорость ветра в метрах в секунду
+    pub description: String, // Текстовое описание погоды
+    pub precipitation_chance: Option<f64>, // Вероятность осадков в процентах, если провайдер её даёт
+    #[serde(default)]
+    pub condition: WeatherCondition,       // Состояние по коду провайдера, для выбора иконки
+    #[serde(default = "default_is_day")]
+    pub is_day: bool,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct WeatherDescription {
-    pub description: String, // Текстовое описание погоды
+fn default_is_day() -> bool {
+    true
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Main {
-    pub temp: f64,     // Температура в градусах Цельсия
-    pub humidity: f64, // Влажность в процентах
-    pub pressure: f64, // Атмосферное давление в гПа
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherAlert {
+    pub id: String,          // Идентификатор от провайдера, чтобы не уведомлять повторно
+    pub event: String,
+    pub description: String,
+    pub start: i64,
+    pub end: i64,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Wind {
-    pub speed: f64, // Скорость ветра в метрах в секунду
+#[derive(Debug, Clone, Default)]
+pub struct WeatherLocation {
+    pub city: String,
+    pub country: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
 }
 
-fn get_weather_info(
-    city: &str,
-    country_code: &str,
-    api_key: &str,
-) -> Result<WeatherForecast, Box<dyn Error>> {
-    let url = format!(
-        "http://api.openweathermap.org/data/2.5/forecast?q={},{}&units=metric&appid={}",
-        city, country_code, api_key
-    );
-    let response = reqwest::blocking::get(&url)?;
-    let response_json = response.json::<WeatherForecast>()?;
-    Ok(response_json)
+// Активное сохранённое место, иначе город и страна из settings.ini
+fn get_location() -> WeatherLocation {
+    match LocationStore::load().active_location() {
+        Some(location) => WeatherLocation {
+            city: location.city.clone(),
+            country: location.country_code.clone(),
+            latitude: Some(location.latitude),
+            longitude: Some(location.longitude),
+        },
+        None => WeatherLocation {
+            city: get_weather_setting("city", ""),
+            country: get_weather_setting("country", ""),
+            latitude: None,
+            longitude: None,
+        },
+    }
+}
+
+// Ключ кэша: прогноз другого места или провайдера показывать нельзя
+pub fn forecast_source_key() -> String {
+    let location = get_location();
+    format!(
+        "{}|{}|{}|{:?}|{:?}",
+        get_weather_setting("weather_provider", "openweathermap"),
+        location.city,
+        location.country,
+        location.latitude,
+        location.longitude
+    )
 }
 
 pub fn get_weather() -> Result<WeatherForecast, Box<dyn Error>> {
-    let home: String = env::var("HOME").expect("Could not determine the home directory");
-    let settings_path: String = format!("{}/{}", home, ".config/sidebar/settings.ini");
-    let settings = ini!(&settings_path);
-    let city = settings["settings"]["city"].clone().unwrap();
-    let country_code = settings["settings"]["country"].clone().unwrap();
-    let api_key = settings["settings"]["owm_api_key"]
-        .clone()
-        .unwrap()
-        .to_string();
-
-    let forecast = get_weather_info(&city, &country_code, &api_key)?;
+    let provider = create_provider(
+        &get_weather_setting("weather_provider", "openweathermap"),
+        &get_weather_setting("owm_api_key", ""),
+    );
+
+    build_forecast(provider.as_ref(), &get_location(), Utc::now())
+}
+
+// Приводит ответ провайдера к дневному и почасовому прогнозу относительно now
+pub fn build_forecast(
+    provider: &dyn WeatherProvider,
+    location: &WeatherLocation,
+    now: DateTime<Utc>,
+) -> Result<WeatherForecast, Box<dyn Error>> {
+    let entries = provider
+        .fetch_forecast(location)
+        .map_err(|e| format!("{}: {}", provider.name(), e))?;
+
+    // Без предупреждений прогноз всё равно показываем
+    let alerts = provider.fetch_alerts(location).unwrap_or_else(|e| {
+        eprintln!("{}: failed to fetch weather alerts: {}", provider.name(), e);
+        Vec::new()
+    });
 
     // Текущая дата и время UTC
-    let current_date = Utc::now();
+    let current_date = now;
+
+    let hourly_end = (current_date + Duration::hours(HOURLY_HOURS)).timestamp();
+    let hourly: Vec<WeatherEntry> = entries
+        .iter()
+        .filter(|entry| entry.dt + 3600 >= current_date.timestamp() && entry.dt <= hourly_end)
+        .cloned()
+        .collect();
     // Дата и время через 4 дня
     let end_date = current_date + Duration::days(4);
 
     // Группировка данных по дням и выбор записи для текущего дня и записи на час дня для следующих трех дней
     let mut daily_forecasts: HashMap<NaiveDate, WeatherEntry> = HashMap::new();
 
-    for entry in forecast.list {
-        let forecast_date =
-            DateTime::<Utc>::from_utc(chrono::NaiveDateTime::from_timestamp(entry.dt, 0), Utc);
-        let date = forecast_date.date().naive_utc();
+    for entry in entries {
+        let forecast_date = DateTime::from_timestamp(entry.dt, 0).unwrap_or_default();
+        let date = forecast_date.date_naive();
 
-        if date >= current_date.date().naive_utc() && date < end_date.date().naive_utc() {
-            if date == current_date.date().naive_utc() {
+        if date >= current_date.date_naive() && date < end_date.date_naive() {
+            if date == current_date.date_naive() {
                 // Для текущего дня выбираем ближайшее к текущему времени значение
-                daily_forecasts.entry(date).or_insert(entry);
+                if entry.dt + 3600 >= current_date.timestamp() {
+                    daily_forecasts.entry(date).or_insert(entry);
+                }
             } else if forecast_date.hour() == 12 {
                 // Для следующих дней выбираем значение на час дня
                 daily_forecasts.entry(date).or_insert(entry);
@@ -87,8 +144,7 @@ pub fn get_weather() -> Result<WeatherForecast, Box<dyn Error>> {
     }
 
     let mut limited_forecast: Vec<WeatherEntry> = daily_forecasts
-        .into_iter()
-        .map(|(_, entry)| entry)
+        .into_values()
         .collect();
 
     // Сортировка записей по дате
@@ -96,5 +152,10 @@ pub fn get_weather() -> Result<WeatherForecast, Box<dyn Error>> {
 
     Ok(WeatherForecast {
         list: limited_forecast,
+        hourly,
+        alerts: alerts
+            .into_iter()
+            .filter(|alert| alert.end == 0 || alert.end >= current_date.timestamp())
+            .collect(),
     })
 }
@@ -0,0 +1,694 @@
+use crate::ui::widgets::weather_conditions::WeatherCondition;
+use crate::ui::widgets::weather_plugin::{WeatherAlert, WeatherEntry, WeatherLocation};
+use chrono::DateTime;
+use serde::Deserialize;
+use std::error::Error;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+// MET Norway требует User-Agent с названием приложения
+const USER_AGENT: &str = "SideBar/1.0 github.com/WaldLumen/SideBar";
+
+pub trait WeatherProvider {
+    fn name(&self) -> &'static str;
+    fn fetch_forecast(&self, location: &WeatherLocation) -> Result<Vec<WeatherEntry>, Box<dyn Error>>;
+
+    // Предупреждения есть не у всех провайдеров
+    fn fetch_alerts(&self, _location: &WeatherLocation) -> Result<Vec<WeatherAlert>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+}
+
+fn http_client() -> reqwest::blocking::Client {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .user_agent(USER_AGENT)
+        .build()
+        .unwrap_or_default()
+}
+
+fn require_coordinates(location: &WeatherLocation) -> Result<(f64, f64), Box<dyn Error>> {
+    match (location.latitude, location.longitude) {
+        (Some(lat), Some(lon)) => Ok((lat, lon)),
+        _ => Err("Latitude and longitude are required for this provider".into()),
+    }
+}
+
+pub fn create_provider(name: &str, api_key: &str) -> Box<dyn WeatherProvider> {
+    match name {
+        "open-meteo" => Box::new(OpenMeteo::new()),
+        "met-norway" => Box::new(MetNorway::new()),
+        _ => Box::new(OpenWeatherMap::new(api_key)),
+    }
+}
+
+// ---------- OpenWeatherMap ----------
+
+#[derive(Deserialize, Debug)]
+struct OwmResponse {
+    list: Vec<OwmEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmEntry {
+    dt: i64,
+    main: OwmMain,
+    weather: Vec<OwmDescription>,
+    wind: OwmWind,
+    #[serde(default)]
+    pop: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmMain {
+    temp: f64,
+    humidity: f64,
+    pressure: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmDescription {
+    id: u32,
+    description: String,
+    #[serde(default)]
+    icon: String, // "01d" / "01n": суффикс показывает день или ночь
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmWind {
+    speed: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmOneCallResponse {
+    #[serde(default)]
+    alerts: Vec<OwmAlert>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwmAlert {
+    #[serde(default)]
+    sender_name: String,
+    event: String,
+    start: i64,
+    end: i64,
+    #[serde(default)]
+    description: String,
+}
+
+pub struct OpenWeatherMap {
+    api_key: String,
+    base_url: String,
+}
+
+impl OpenWeatherMap {
+    pub fn new(api_key: &str) -> Self {
+        Self::with_base_url(api_key, "https://api.openweathermap.org")
+    }
+
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl WeatherProvider for OpenWeatherMap {
+    fn name(&self) -> &'static str {
+        "OpenWeatherMap"
+    }
+
+    fn fetch_forecast(&self, location: &WeatherLocation) -> Result<Vec<WeatherEntry>, Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Err("OpenWeatherMap API key is not set".into());
+        }
+
+        let mut query = vec![
+            ("units", "metric".to_string()),
+            ("appid", self.api_key.clone()),
+        ];
+        match (location.latitude, location.longitude) {
+            (Some(lat), Some(lon)) => {
+                query.push(("lat", lat.to_string()));
+                query.push(("lon", lon.to_string()));
+            }
+            _ => query.push(("q", format!("{},{}", location.city, location.country))),
+        }
+
+        let response: OwmResponse = http_client()
+            .get(format!("{}/data/2.5/forecast", self.base_url))
+            .query(&query)
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(response
+            .list
+            .into_iter()
+            .map(|entry| {
+                let weather = entry.weather.into_iter().next();
+                let condition = weather
+                    .as_ref()
+                    .and_then(|w| WeatherCondition::from_owm(w.id))
+                    .unwrap_or_default();
+
+                WeatherEntry {
+                    dt: entry.dt,
+                    temp: entry.main.temp,
+                    humidity: entry.main.humidity,
+                    pressure: entry.main.pressure,
+                    wind_speed: entry.wind.speed,
+                    is_day: !weather.as_ref().is_some_and(|w| w.icon.ends_with('n')),
+                    description: weather.map(|w| w.description).unwrap_or_default(),
+                    precipitation_chance: entry.pop.map(|pop| pop * 100.0),
+                    condition,
+                }
+            })
+            .collect())
+    }
+
+    // One Call 3.0 доступен не на всех ключах, ошибка здесь не мешает прогнозу
+    fn fetch_alerts(&self, location: &WeatherLocation) -> Result<Vec<WeatherAlert>, Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (lat, lon) = require_coordinates(location)?;
+
+        let response: OwmOneCallResponse = http_client()
+            .get(format!("{}/data/3.0/onecall", self.base_url))
+            .query(&[
+                ("lat", lat.to_string()),
+                ("lon", lon.to_string()),
+                ("exclude", "current,minutely,hourly,daily".to_string()),
+                ("appid", self.api_key.clone()),
+            ])
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(response
+            .alerts
+            .into_iter()
+            .map(|alert| WeatherAlert {
+                id: format!("owm:{}:{}:{}", alert.sender_name, alert.event, alert.start),
+                event: alert.event,
+                description: alert.description,
+                start: alert.start,
+                end: alert.end,
+            })
+            .collect())
+    }
+}
+
+// ---------- Open-Meteo ----------
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoResponse {
+    hourly: OpenMeteoHourly,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoHourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    pressure_msl: Vec<Option<f64>>,
+    wind_speed_10m: Vec<Option<f64>>,
+    weather_code: Vec<Option<u32>>,
+    #[serde(default)]
+    precipitation_probability: Vec<Option<f64>>,
+    #[serde(default)]
+    is_day: Vec<Option<u8>>,
+}
+
+pub struct OpenMeteo {
+    base_url: String,
+}
+
+impl OpenMeteo {
+    pub fn new() -> Self {
+        Self::with_base_url("https://api.open-meteo.com")
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for OpenMeteo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &'static str {
+        "Open-Meteo"
+    }
+
+    fn fetch_forecast(&self, location: &WeatherLocation) -> Result<Vec<WeatherEntry>, Box<dyn Error>> {
+        let (lat, lon) = require_coordinates(location)?;
+
+        let response: OpenMeteoResponse = http_client()
+            .get(format!("{}/v1/forecast", self.base_url))
+            .query(&[
+                ("latitude", lat.to_string()),
+                ("longitude", lon.to_string()),
+                (
+                    "hourly",
+                    "temperature_2m,relative_humidity_2m,pressure_msl,wind_speed_10m,weather_code,precipitation_probability,is_day"
+                        .to_string(),
+                ),
+                ("wind_speed_unit", "ms".to_string()),
+                ("timeformat", "unixtime".to_string()),
+                ("timezone", "UTC".to_string()),
+                ("forecast_days", "5".to_string()),
+            ])
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        let hourly = response.hourly;
+        let value = |values: &[Option<f64>], i: usize| values.get(i).copied().flatten().unwrap_or_default();
+
+        Ok(hourly
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, &dt)| {
+                let condition = hourly
+                    .weather_code
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .and_then(WeatherCondition::from_wmo)
+                    .unwrap_or_default();
+
+                WeatherEntry {
+                    dt,
+                    temp: value(&hourly.temperature_2m, i),
+                    humidity: value(&hourly.relative_humidity_2m, i),
+                    pressure: value(&hourly.pressure_msl, i),
+                    wind_speed: value(&hourly.wind_speed_10m, i),
+                    description: condition.description().to_string(),
+                    precipitation_chance: hourly.precipitation_probability.get(i).copied().flatten(),
+                    condition,
+                    is_day: hourly.is_day.get(i).copied().flatten().is_none_or(|day| day == 1),
+                }
+            })
+            .collect())
+    }
+}
+
+// ---------- MET Norway ----------
+
+#[derive(Deserialize, Debug)]
+struct MetResponse {
+    properties: MetProperties,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetProperties {
+    timeseries: Vec<MetTimestep>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetTimestep {
+    time: String,
+    data: MetData,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetData {
+    instant: MetInstant,
+    next_1_hours: Option<MetPeriod>,
+    next_6_hours: Option<MetPeriod>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetInstant {
+    details: MetDetails,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetDetails {
+    #[serde(default)]
+    air_temperature: f64,
+    #[serde(default)]
+    relative_humidity: f64,
+    #[serde(default)]
+    air_pressure_at_sea_level: f64,
+    #[serde(default)]
+    wind_speed: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetPeriod {
+    summary: MetSummary,
+    #[serde(default)]
+    details: Option<MetPeriodDetails>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetPeriodDetails {
+    probability_of_precipitation: Option<f64>,
+}
+
+// MetAlerts отдаёт GeoJSON, нужны только свойства и интервал действия
+#[derive(Deserialize, Debug)]
+struct MetAlertsResponse {
+    #[serde(default)]
+    features: Vec<MetAlertFeature>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetAlertFeature {
+    properties: MetAlertProperties,
+    #[serde(default)]
+    when: Option<MetAlertWhen>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetAlertProperties {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetAlertWhen {
+    #[serde(default)]
+    interval: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetSummary {
+    symbol_code: String,
+}
+
+pub struct MetNorway {
+    base_url: String,
+}
+
+impl MetNorway {
+    pub fn new() -> Self {
+        Self::with_base_url("https://api.met.no")
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for MetNorway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherProvider for MetNorway {
+    fn name(&self) -> &'static str {
+        "MET Norway"
+    }
+
+    fn fetch_forecast(&self, location: &WeatherLocation) -> Result<Vec<WeatherEntry>, Box<dyn Error>> {
+        let (lat, lon) = require_coordinates(location)?;
+
+        // MET просит не передавать больше 4 знаков после запятой.
+        // В compact нет вероятности осадков, поэтому берём complete
+        let response: MetResponse = http_client()
+            .get(format!("{}/weatherapi/locationforecast/2.0/complete", self.base_url))
+            .query(&[
+                ("lat", format!("{:.4}", lat)),
+                ("lon", format!("{:.4}", lon)),
+            ])
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(response
+            .properties
+            .timeseries
+            .into_iter()
+            .filter_map(|step| {
+                let dt = DateTime::parse_from_rfc3339(&step.time).ok()?.timestamp();
+                let details = step.data.instant.details;
+                let period = step.data.next_1_hours.or(step.data.next_6_hours);
+                let precipitation_chance = period
+                    .as_ref()
+                    .and_then(|period| period.details.as_ref())
+                    .and_then(|details| details.probability_of_precipitation);
+                let symbol = period
+                    .map(|period| period.summary.symbol_code)
+                    .unwrap_or_default();
+
+                let condition = WeatherCondition::from_met_symbol(&symbol).unwrap_or_default();
+
+                Some(WeatherEntry {
+                    dt,
+                    temp: details.air_temperature,
+                    humidity: details.relative_humidity,
+                    pressure: details.air_pressure_at_sea_level,
+                    wind_speed: details.wind_speed,
+                    description: condition.description().to_string(),
+                    precipitation_chance,
+                    condition,
+                    // Полярные сумерки показываем как ночь
+                    is_day: !symbol.ends_with("_night") && !symbol.ends_with("_polartwilight"),
+                })
+            })
+            .collect())
+    }
+
+    fn fetch_alerts(&self, location: &WeatherLocation) -> Result<Vec<WeatherAlert>, Box<dyn Error>> {
+        let (lat, lon) = require_coordinates(location)?;
+
+        let response: MetAlertsResponse = http_client()
+            .get(format!("{}/weatherapi/metalerts/2.0/current.json", self.base_url))
+            .query(&[
+                ("lat", format!("{:.4}", lat)),
+                ("lon", format!("{:.4}", lon)),
+            ])
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        let timestamp = |value: Option<&String>| {
+            value
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|time| time.timestamp())
+                .unwrap_or_default()
+        };
+
+        Ok(response
+            .features
+            .into_iter()
+            .map(|feature| {
+                let interval = feature.when.map(|when| when.interval).unwrap_or_default();
+                WeatherAlert {
+                    id: format!("met:{}", feature.properties.id),
+                    event: feature.properties.title,
+                    description: feature.properties.description,
+                    start: timestamp(interval.first()),
+                    end: timestamp(interval.get(1)),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::MockServer;
+    use crate::ui::widgets::weather_plugin::{build_forecast, WeatherForecast};
+    use chrono::Utc;
+
+    // Ответы записаны с настоящих API и сокращены до нескольких точек
+    const OWM_FORECAST: &str = include_str!("fixtures/owm_forecast.json");
+    const OWM_ONECALL: &str = include_str!("fixtures/owm_onecall.json");
+    const OPEN_METEO: &str = include_str!("fixtures/open_meteo.json");
+    const MET_FORECAST: &str = include_str!("fixtures/met_locationforecast.json");
+    const MET_ALERTS: &str = include_str!("fixtures/met_alerts.json");
+
+    // 2024-05-01 09:30 UTC; в фикстурах точки в 09:00, 12:00 и 21:00 этого дня,
+    // затем по полудню следующих дней
+    const NOW: i64 = 1714555800;
+    const H09: i64 = 1714554000;
+    const H12: i64 = 1714564800;
+    const H21: i64 = 1714597200;
+    const MAY_2: i64 = 1714651200;
+    const MAY_3: i64 = 1714737600;
+    const MAY_4: i64 = 1714824000;
+
+    fn location() -> WeatherLocation {
+        WeatherLocation {
+            city: "Berlin".to_string(),
+            country: "DE".to_string(),
+            latitude: Some(52.52),
+            longitude: Some(13.405),
+        }
+    }
+
+    fn forecast(provider: &dyn WeatherProvider) -> WeatherForecast {
+        build_forecast(provider, &location(), DateTime::from_timestamp(NOW, 0).unwrap()).unwrap()
+    }
+
+    fn times(entries: &[WeatherEntry]) -> Vec<i64> {
+        entries.iter().map(|entry| entry.dt).collect()
+    }
+
+    #[test]
+    fn openweathermap_forecast_and_one_call_alerts() {
+        let server = MockServer::start(vec![
+            ("/data/2.5/forecast", OWM_FORECAST.to_string()),
+            ("/data/3.0/onecall", OWM_ONECALL.to_string()),
+        ]);
+        let provider = OpenWeatherMap::with_base_url("test-key", &server.base_url);
+
+        let forecast = forecast(&provider);
+
+        // Сегодня ближайшая точка, дальше полдень; пятый день за пределами четырёх
+        assert_eq!(times(&forecast.list), vec![H09, MAY_2, MAY_3, MAY_4]);
+        assert_eq!(times(&forecast.hourly), vec![H09, H12, H21, MAY_2]);
+
+        let conditions: Vec<WeatherCondition> = forecast.hourly.iter().map(|entry| entry.condition).collect();
+        assert_eq!(
+            conditions,
+            vec![
+                WeatherCondition::Clear,
+                WeatherCondition::Rain,
+                WeatherCondition::PartlyCloudy,
+                WeatherCondition::Thunderstorm
+            ]
+        );
+        let is_day: Vec<bool> = forecast.hourly.iter().map(|entry| entry.is_day).collect();
+        assert_eq!(is_day, vec![true, true, false, true]);
+
+        let rain = &forecast.hourly[1];
+        assert_eq!(rain.description, "light rain");
+        assert_eq!(rain.temp, 17.9);
+        assert_eq!(rain.pressure, 1014.0);
+        assert_eq!(rain.precipitation_chance, Some(42.0));
+
+        // Заморозки уже закончились и отфильтрованы
+        assert_eq!(forecast.alerts.len(), 1);
+        let alert = &forecast.alerts[0];
+        assert_eq!(alert.id, format!("owm:Deutscher Wetterdienst:Thunderstorm warning:{}", H12));
+        assert_eq!(alert.event, "Thunderstorm warning");
+        assert_eq!((alert.start, alert.end), (H12, 1714629600));
+
+        let requests = server.requests();
+        assert!(requests.iter().any(|request| request.contains("appid=test-key") && request.contains("lat=52.52")));
+    }
+
+    #[test]
+    fn openweathermap_requires_an_api_key() {
+        let server = MockServer::start(vec![("/data/2.5/forecast", OWM_FORECAST.to_string())]);
+        let provider = OpenWeatherMap::with_base_url("", &server.base_url);
+
+        assert!(build_forecast(&provider, &location(), Utc::now()).is_err());
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn openweathermap_forecast_survives_missing_one_call() {
+        let server = MockServer::start(vec![("/data/2.5/forecast", OWM_FORECAST.to_string())]);
+        let provider = OpenWeatherMap::with_base_url("test-key", &server.base_url);
+
+        let forecast = forecast(&provider);
+        assert_eq!(forecast.list.len(), 4);
+        assert!(forecast.alerts.is_empty());
+    }
+
+    #[test]
+    fn open_meteo_maps_wmo_codes_and_day_flag() {
+        let server = MockServer::start(vec![("/v1/forecast", OPEN_METEO.to_string())]);
+        let provider = OpenMeteo::with_base_url(&server.base_url);
+
+        let forecast = forecast(&provider);
+
+        assert_eq!(times(&forecast.list), vec![H09, MAY_2, MAY_3, MAY_4]);
+        assert_eq!(times(&forecast.hourly), vec![H09, H12, H21, MAY_2]);
+
+        let conditions: Vec<WeatherCondition> = forecast.hourly.iter().map(|entry| entry.condition).collect();
+        assert_eq!(
+            conditions,
+            vec![
+                WeatherCondition::Clear,
+                WeatherCondition::Rain,
+                WeatherCondition::Cloudy,
+                WeatherCondition::Thunderstorm
+            ]
+        );
+        let is_day: Vec<bool> = forecast.hourly.iter().map(|entry| entry.is_day).collect();
+        assert_eq!(is_day, vec![true, true, false, true]);
+        assert_eq!(forecast.hourly[1].description, "rain");
+        assert_eq!(forecast.hourly[1].precipitation_chance, Some(45.0));
+
+        // null в ответе не ломает разбор
+        let last = forecast.list.last().unwrap();
+        assert_eq!(last.temp, 0.0);
+        assert_eq!(last.condition, WeatherCondition::Unknown);
+
+        assert!(forecast.alerts.is_empty());
+        assert!(server.requests()[0].contains("timeformat=unixtime"));
+    }
+
+    #[test]
+    fn open_meteo_requires_coordinates() {
+        let provider = OpenMeteo::with_base_url("http://127.0.0.1:9");
+        let location = WeatherLocation {
+            city: "Berlin".to_string(),
+            ..Default::default()
+        };
+        assert!(provider.fetch_forecast(&location).is_err());
+    }
+
+    #[test]
+    fn met_norway_maps_symbols_and_metalerts() {
+        let server = MockServer::start(vec![
+            ("/weatherapi/locationforecast/2.0/complete", MET_FORECAST.to_string()),
+            ("/weatherapi/metalerts/2.0/current.json", MET_ALERTS.to_string()),
+        ]);
+        let provider = MetNorway::with_base_url(&server.base_url);
+
+        let forecast = forecast(&provider);
+
+        assert_eq!(times(&forecast.list), vec![H09, MAY_2, MAY_3, MAY_4]);
+        assert_eq!(times(&forecast.hourly), vec![H09, H12, H21, MAY_2]);
+
+        let conditions: Vec<WeatherCondition> = forecast.hourly.iter().map(|entry| entry.condition).collect();
+        assert_eq!(
+            conditions,
+            vec![
+                WeatherCondition::Clear,
+                WeatherCondition::Rain,
+                WeatherCondition::Cloudy,
+                WeatherCondition::Thunderstorm
+            ]
+        );
+        let is_day: Vec<bool> = forecast.hourly.iter().map(|entry| entry.is_day).collect();
+        assert_eq!(is_day, vec![true, true, false, true]);
+
+        // Вероятность осадков берётся из ближайшего периода, при его отсутствии — из шестичасового
+        assert_eq!(forecast.hourly[0].precipitation_chance, Some(1.2));
+        assert_eq!(forecast.hourly[3].precipitation_chance, Some(91.0));
+        assert_eq!(forecast.hourly[0].temp, 11.8);
+
+        assert_eq!(forecast.alerts.len(), 1);
+        let alert = &forecast.alerts[0];
+        assert_eq!(alert.id, "met:2.49.0.1.578.0.20240501080000.001");
+        assert_eq!(alert.event, "Gale warning, yellow level, Oslofjord");
+        assert_eq!((alert.start, alert.end), (H12, 1714629600));
+
+        assert!(server.requests().iter().all(|request| request.contains("lat=52.5200")));
+    }
+}
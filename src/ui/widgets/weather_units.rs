@@ -0,0 +1,195 @@
+use crate::ui::i18n::tr;
+use crate::ui::settings::get_weather_setting;
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+// Провайдеры приводятся к °C, м/с и гПа; здесь только пересчёт для отображения
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindUnit {
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PressureUnit {
+    Hectopascal,
+    MillimetersHg,
+    InchesHg,
+}
+
+impl TemperatureUnit {
+    pub const ALL: [TemperatureUnit; 2] = [TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit];
+
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "fahrenheit" => TemperatureUnit::Fahrenheit,
+            _ => TemperatureUnit::Celsius,
+        }
+    }
+
+    pub fn setting_value(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "celsius",
+            TemperatureUnit::Fahrenheit => "fahrenheit",
+        }
+    }
+
+    pub fn convert(&self, celsius: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+}
+
+impl WindUnit {
+    pub const ALL: [WindUnit; 3] = [
+        WindUnit::MetersPerSecond,
+        WindUnit::KilometersPerHour,
+        WindUnit::MilesPerHour,
+    ];
+
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "kmh" => WindUnit::KilometersPerHour,
+            "mph" => WindUnit::MilesPerHour,
+            _ => WindUnit::MetersPerSecond,
+        }
+    }
+
+    pub fn setting_value(&self) -> &'static str {
+        match self {
+            WindUnit::MetersPerSecond => "ms",
+            WindUnit::KilometersPerHour => "kmh",
+            WindUnit::MilesPerHour => "mph",
+        }
+    }
+
+    pub fn convert(&self, meters_per_second: f64) -> f64 {
+        match self {
+            WindUnit::MetersPerSecond => meters_per_second,
+            WindUnit::KilometersPerHour => meters_per_second * 3.6,
+            WindUnit::MilesPerHour => meters_per_second * 2.236_936,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            WindUnit::MetersPerSecond => tr("m/s"),
+            WindUnit::KilometersPerHour => tr("km/h"),
+            WindUnit::MilesPerHour => tr("mph"),
+        }
+    }
+}
+
+impl PressureUnit {
+    pub const ALL: [PressureUnit; 3] = [
+        PressureUnit::Hectopascal,
+        PressureUnit::MillimetersHg,
+        PressureUnit::InchesHg,
+    ];
+
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "mmhg" => PressureUnit::MillimetersHg,
+            "inhg" => PressureUnit::InchesHg,
+            _ => PressureUnit::Hectopascal,
+        }
+    }
+
+    pub fn setting_value(&self) -> &'static str {
+        match self {
+            PressureUnit::Hectopascal => "hpa",
+            PressureUnit::MillimetersHg => "mmhg",
+            PressureUnit::InchesHg => "inhg",
+        }
+    }
+
+    pub fn convert(&self, hectopascal: f64) -> f64 {
+        match self {
+            PressureUnit::Hectopascal => hectopascal,
+            PressureUnit::MillimetersHg => hectopascal * 0.750_062,
+            PressureUnit::InchesHg => hectopascal * 0.029_530,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            PressureUnit::Hectopascal => tr("hPa"),
+            PressureUnit::MillimetersHg => tr("mmHg"),
+            PressureUnit::InchesHg => tr("inHg"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WeatherUnits {
+    pub temperature: TemperatureUnit,
+    pub wind: WindUnit,
+    pub pressure: PressureUnit,
+}
+
+static UNITS_CACHE: Lazy<RwLock<Option<WeatherUnits>>> = Lazy::new(|| RwLock::new(None));
+
+impl WeatherUnits {
+    pub fn current() -> Self {
+        if let Ok(cache) = UNITS_CACHE.read() {
+            if let Some(units) = *cache {
+                return units;
+            }
+        }
+
+        let units = Self {
+            temperature: TemperatureUnit::from_setting(&get_weather_setting("temperature_unit", "celsius")),
+            wind: WindUnit::from_setting(&get_weather_setting("wind_unit", "ms")),
+            pressure: PressureUnit::from_setting(&get_weather_setting("pressure_unit", "hpa")),
+        };
+
+        if let Ok(mut cache) = UNITS_CACHE.write() {
+            *cache = Some(units);
+        }
+
+        units
+    }
+
+    pub fn temperature(&self, celsius: f64) -> String {
+        format!("{}{}", self.temperature.convert(celsius).round() as i32, self.temperature.label())
+    }
+
+    // Без единицы, для подписей на графике
+    pub fn temperature_short(&self, celsius: f64) -> String {
+        format!("{}°", self.temperature.convert(celsius).round() as i32)
+    }
+
+    pub fn wind(&self, meters_per_second: f64) -> String {
+        format!("{:.1} {}", self.wind.convert(meters_per_second), self.wind.label())
+    }
+
+    pub fn pressure(&self, hectopascal: f64) -> String {
+        match self.pressure {
+            PressureUnit::InchesHg => format!("{:.2} {}", self.pressure.convert(hectopascal), self.pressure.label()),
+            _ => format!("{:.0} {}", self.pressure.convert(hectopascal), self.pressure.label()),
+        }
+    }
+}
+
+pub fn invalidate_units_cache() {
+    if let Ok(mut cache) = UNITS_CACHE.write() {
+        *cache = None;
+    }
+}